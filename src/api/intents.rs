@@ -0,0 +1,166 @@
+use futures::future::join_all;
+
+use crate::{
+    api::{rpc::RpcError, state::AppState},
+    models::{a2a::Artifact, repository::TrendingRepo},
+    services::{
+        intent::{Intent, Smalltalk},
+        subscription_store::{DigestFrequency, Subscription},
+    },
+    utils::helpers::{
+        create_artifacts, create_repository_artifact, format_comparison, format_help_message,
+        format_repo_details,
+    },
+};
+
+pub struct IntentReply {
+    pub text: String,
+    pub artifacts: Vec<Artifact>,
+}
+
+impl IntentReply {
+    fn text(text: String) -> Self {
+        Self {
+            artifacts: create_artifacts(text.clone()),
+            text,
+        }
+    }
+
+    fn with_repos(text: String, repos: &[TrendingRepo]) -> Self {
+        let mut reply = Self::text(text);
+        reply.artifacts.push(create_repository_artifact(repos));
+        reply
+    }
+}
+
+/// Answers every intent except trending searches, which return `None` and go
+/// through the query parser.
+pub async fn respond(
+    state: &AppState,
+    intent: &Intent,
+    task_id: &str,
+    context_id: &str,
+) -> Option<Result<IntentReply, RpcError>> {
+    let reply = match intent {
+        Intent::TrendingSearch => return None,
+        Intent::Help | Intent::OffTopic => Ok(IntentReply::text(format_help_message())),
+        Intent::Smalltalk(kind) => Ok(IntentReply::text(smalltalk_reply(*kind))),
+        Intent::RepoDetails(full_name) => repo_details(state, full_name).await,
+        Intent::Compare(full_names) => compare(state, full_names).await,
+        Intent::Subscribe(frequency) => Ok(subscribe(state, *frequency, task_id, context_id)),
+        Intent::Unsubscribe => Ok(unsubscribe(state, context_id)),
+    };
+
+    Some(reply)
+}
+
+fn smalltalk_reply(kind: Smalltalk) -> String {
+    let reply = match kind {
+        Smalltalk::Greeting => {
+            "Hi! I'm GitPulse. Ask me what's trending on GitHub, e.g. \"Top 5 Rust repos this week\"."
+        }
+        Smalltalk::Thanks => "You're welcome! Ask me for more trending repositories any time.",
+        Smalltalk::Farewell => "Bye! Come back for more trending repositories any time.",
+    };
+
+    reply.to_string()
+}
+
+async fn repo_details(state: &AppState, full_name: &str) -> Result<IntentReply, RpcError> {
+    let repo = match state.github_client.fetch_repo(full_name).await {
+        Ok(Some(repo)) => repo,
+        Ok(None) => {
+            return Ok(IntentReply::text(format!(
+                "I couldn't find **{}** on GitHub.",
+                full_name
+            )));
+        }
+        Err(e) => {
+            tracing::error!("GitHub API error: {}", e);
+            return Err(RpcError::GitHubUnavailable);
+        }
+    };
+
+    let mut repos = vec![repo];
+
+    if let Some(enricher) = &state.enricher {
+        enricher.enrich(&mut repos).await;
+    }
+
+    Ok(IntentReply::with_repos(
+        format_repo_details(&repos[0]),
+        &repos,
+    ))
+}
+
+async fn compare(state: &AppState, full_names: &[String]) -> Result<IntentReply, RpcError> {
+    let lookups = join_all(
+        full_names
+            .iter()
+            .map(|full_name| state.github_client.fetch_repo(full_name)),
+    )
+    .await;
+
+    let mut repos = vec![];
+    let mut missing = vec![];
+
+    for (full_name, lookup) in full_names.iter().zip(lookups) {
+        match lookup {
+            Ok(Some(repo)) => repos.push(repo),
+            Ok(None) => missing.push(full_name.as_str()),
+            Err(e) => {
+                tracing::error!("GitHub API error: {}", e);
+                return Err(RpcError::GitHubUnavailable);
+            }
+        }
+    }
+
+    if repos.len() < 2 {
+        return Ok(IntentReply::text(format!(
+            "I need at least two existing repositories to compare. Couldn't find: {}.",
+            missing.join(", ")
+        )));
+    }
+
+    let mut text = format_comparison(&repos);
+
+    if !missing.is_empty() {
+        text.push_str(&format!("\n_Couldn't find: {}_\n", missing.join(", ")));
+    }
+
+    Ok(IntentReply::with_repos(text, &repos))
+}
+
+fn subscribe(
+    state: &AppState,
+    frequency: DigestFrequency,
+    task_id: &str,
+    context_id: &str,
+) -> IntentReply {
+    let Some(push_config) = state.push_notifier.get(task_id) else {
+        return IntentReply::text(format!(
+            "To receive the {}, send this message with a `pushNotificationConfig` so I know where to deliver it.",
+            frequency.as_str()
+        ));
+    };
+
+    state.subscription_store.subscribe(Subscription {
+        context_id: context_id.to_string(),
+        frequency,
+        push_config,
+    });
+
+    IntentReply::text(format!(
+        "Subscribed to the {}. Say \"unsubscribe\" to stop it.",
+        frequency.as_str()
+    ))
+}
+
+fn unsubscribe(state: &AppState, context_id: &str) -> IntentReply {
+    let text = match state.subscription_store.unsubscribe(context_id) {
+        Some(subscription) => format!("Unsubscribed from the {}.", subscription.frequency.as_str()),
+        None => "This conversation isn't subscribed to any digests.".to_string(),
+    };
+
+    IntentReply::text(text)
+}
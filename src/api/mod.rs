@@ -10,34 +10,80 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
     api::{
-        routes::{get_trending, health_check},
+        routes::{agent_card, get_trending, health_check, metrics, reload_prompts},
         state::AppState,
     },
-    models::a2a::{
-        A2ARequest, A2AResponse, Artifact, Configuration, ErrorDetail, Message, MessagePart,
-        RequestParams, TaskResult, TaskStatus, TelexMetadata,
+    models::{
+        a2a::{
+            A2ARequest, A2AResponse, Artifact, Configuration, ErrorDetail, Message, MessagePart,
+            PushNotificationConfig, RequestId, RequestParams, StreamEvent, TaskArtifactUpdateEvent,
+            TaskIdParams, TaskPushNotificationConfig, TaskQueryParams, TaskResult, TaskStatus,
+            TaskStatusUpdateEvent, TelexMetadata,
+        },
+        agent_card::{AgentCapabilities, AgentCard, AgentSkill},
+        query::{QueryParams, SortBy},
+        repository::{
+            Release, RepoBlurb, RepoDetails, SearchResult, StarVelocity, TrendingData, TrendingRepo,
+        },
+    },
+    services::{
+        github_quota::{GitHubQuota, QuotaStatus},
+        github_tokens::TokenUsage,
+        prompts::PromptStatus,
+        rate_limiter::rate_limit_middleware,
     },
-    services::rate_limiter::rate_limit_middleware,
 };
 
+pub mod intents;
 pub mod routes;
+pub mod rpc;
 pub mod state;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(crate::api::routes::health_check, crate::api::routes::get_trending,),
+    paths(
+        crate::api::routes::health_check,
+        crate::api::routes::metrics,
+        crate::api::routes::agent_card,
+        crate::api::routes::get_trending,
+        crate::api::routes::reload_prompts,
+    ),
     components(schemas(
         A2AResponse,
         A2ARequest,
+        AgentCapabilities,
+        AgentCard,
+        AgentSkill,
         Artifact,
         Configuration,
         ErrorDetail,
         RequestParams,
         Message,
         MessagePart,
+        PushNotificationConfig,
+        QueryParams,
+        RepoBlurb,
+        RequestId,
+        SearchResult,
+        StreamEvent,
+        TaskArtifactUpdateEvent,
+        TaskIdParams,
+        TaskPushNotificationConfig,
+        TaskQueryParams,
         TaskResult,
         TaskStatus,
+        TaskStatusUpdateEvent,
         TelexMetadata,
+        TrendingData,
+        TrendingRepo,
+        RepoDetails,
+        Release,
+        SortBy,
+        StarVelocity,
+        PromptStatus,
+        GitHubQuota,
+        QuotaStatus,
+        TokenUsage,
     )),
     info(title = "GitPulse API", version = "1.0.0")
 )]
@@ -65,11 +111,14 @@ pub fn build_router(state: AppState) -> Router {
 
     let api_routes = Router::new()
         .route("/health", get(health_check))
+        .route("/metrics", get(metrics))
+        .route("/.well-known/agent.json", get(agent_card))
         .route("/trending", post(get_trending))
+        .route("/admin/prompts/reload", post(reload_prompts))
         .layer(middleware::from_fn(rate_limit_middleware))
         .layer(Extension(state.rate_limiter.clone()));
-        
-        Router::new()
+
+    Router::new()
         .merge(api_routes)
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .layer(cors)
@@ -1,29 +1,155 @@
+use anyhow::Result;
 use axum::{
     Json,
     body::Bytes,
     extract::State,
-    response::{IntoResponse, Response},
+    http::{
+        HeaderMap,
+        header::{AUTHORIZATION, CONTENT_TYPE},
+    },
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures::{
+    StreamExt,
+    channel::mpsc::{self, UnboundedSender},
+    stream,
 };
 use reqwest::StatusCode;
+use serde::Serialize;
 use serde_json::{Value, json};
 use uuid::Uuid;
 
 use crate::{
-    api::state::AppState,
-    models::a2a::{A2ARequest, A2AResponse},
-    utils::helpers::{create_artifacts, extract_user_query, format_trending_message},
+    api::{
+        intents,
+        rpc::{RpcError, check_batch, parse_call},
+        state::AppState,
+    },
+    models::{
+        a2a::{
+            A2ARequest, A2AResponse, Artifact, MESSAGE_SEND, MESSAGE_STREAM, Message, MessagePart,
+            RequestId, RequestParams, StreamEvent, TASKS_CANCEL, TASKS_GET,
+            TASKS_PUSH_NOTIFICATION_CONFIG_GET, TASKS_PUSH_NOTIFICATION_CONFIG_SET, TaskIdParams,
+            TaskPushNotificationConfig, TaskQueryParams, TaskResult, TelexMetadata,
+        },
+        agent_card::AgentCard,
+        query::QueryParams,
+        repository::SearchResult,
+    },
+    services::{
+        intent::Intent,
+        llm::RULES_PROVIDER,
+        prompts::PromptStatus,
+        task_store::{TaskStore, TaskStoreError},
+    },
+    utils::helpers::{
+        create_artifacts, create_data_artifact, extract_user_query, format_trending_message,
+    },
 };
 
+const MAX_SEARCH_RESULTS: usize = 100;
+const BATCH_CONCURRENCY: usize = 4;
+
 #[utoipa::path(
     get,
     path = "/health",
     responses(
-        (status = 200),
+        (status = 200, description = "Service status and the GitHub rate-limit quota"),
+    ),
+    tag = "health",
+)]
+pub async fn health_check(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        Json(json!({"status": "OK", "github": state.github_client.quota()})),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "GitHub quota metrics in the Prometheus text format", content_type = "text/plain"),
     ),
     tag = "health",
 )]
-pub async fn health_check() -> Response {
-    (StatusCode::OK, Json(json!({"status": "OK"}))).into_response()
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.github_client.quota().prometheus(),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/agent.json",
+    responses(
+        (status = 200, body = AgentCard),
+    ),
+    tag = "A2A",
+)]
+pub async fn agent_card(State(state): State<AppState>) -> Response {
+    (StatusCode::OK, Json(AgentCard::from_config(&state.config))).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/prompts/reload",
+    responses(
+        (status = 200, body = PromptStatus),
+        (status = 401, description = "Missing or wrong admin token"),
+        (status = 404, description = "ADMIN_TOKEN is not configured"),
+        (status = 500, description = "A prompt file could not be read"),
+    ),
+    tag = "admin",
+)]
+pub async fn reload_prompts(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let Some(admin_token) = state
+        .config
+        .admin_token
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if !bearer.is_some_and(|token| constant_time_eq(token.as_bytes(), admin_token.as_bytes())) {
+        tracing::warn!("Rejected prompt reload with a missing or wrong admin token");
+
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "Invalid admin token"})),
+        )
+            .into_response();
+    }
+
+    match state.query_parser.prompts().reload() {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to reload prompts: {:#}", e);
+
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("{:#}", e)})),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
 }
 
 #[utoipa::path(
@@ -31,8 +157,13 @@ pub async fn health_check() -> Response {
     path = "/trending",
     request_body = A2ARequest,
     responses(
-        (status = 200, body = A2AResponse),
+        (status = 200, content(
+            (A2AResponse = "application/json"),
+            (A2AResponse<StreamEvent> = "text/event-stream"),
+        )),
+        (status = 204, description = "Notification or batch of notifications"),
         (status = 400, body = A2AResponse),
+        (status = 429, body = A2AResponse),
         (status = 500, body = A2AResponse),
     ),
     tag = "A2A",
@@ -41,146 +172,824 @@ pub async fn get_trending(State(state): State<AppState>, body: Bytes) -> Respons
     if body.is_empty() {
         tracing::warn!("Received empty request body");
 
-        return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-            -32600,
-            "Empty request received".to_string(),
-        )))
-        .into_response();
+        return rpc_error(
+            None,
+            RpcError::InvalidRequest("Empty request received".to_string()),
+        );
     }
 
-    let parsed_json_value: Value = match serde_json::from_slice(&body) {
+    let payload: Value = match serde_json::from_slice(&body) {
         Ok(val) => val,
         Err(e) => {
             tracing::error!("JSON parse error: {}", e);
 
-            return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-                -32700,
-                "Parse error: Invalid JSON".to_string(),
-            )))
-            .into_response();
+            return rpc_error(None, RpcError::ParseError(e.to_string()));
         }
     };
 
-    if parsed_json_value
-        .as_object()
-        .map_or(false, |obj| obj.is_empty())
-    {
-        tracing::info!("Received empty JSON object");
+    match payload {
+        Value::Array(calls) => dispatch_batch(state, calls).await,
+        call => dispatch_single(state, call).await,
+    }
+}
+
+async fn dispatch_single(state: AppState, call: Value) -> Response {
+    let request = match parse_call(call) {
+        Ok(request) => request,
+        Err((request_id, e)) => return rpc_error(request_id, e),
+    };
 
-        return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-            -32600,
-            "Empty JSON object received".to_string(),
-        )))
-        .into_response();
+    tracing::info!(
+        "Received A2A request: {} ({})",
+        request.method,
+        request
+            .id
+            .as_ref()
+            .map_or("notification".to_string(), |id| id.to_string())
+    );
+
+    if request.method == MESSAGE_STREAM
+        && let Some(request_id) = request.id.clone()
+    {
+        return match request
+            .with_params::<RequestParams>()
+            .map_err(RpcError::from)
+            .and_then(|request| stream_message(state, request, request_id.clone()))
+        {
+            Ok(response) => response,
+            Err(e) => rpc_error(Some(request_id), e),
+        };
     }
 
-    let request: A2ARequest = match serde_json::from_value(parsed_json_value.clone()) {
-        Ok(req) => req,
-        Err(e) => {
-            tracing::error!("A2ARequest deserialization error: {}", e);
+    let request_id = request.id.clone();
+    let result = call_method(state, request).await;
 
-            return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-                -32602,
-                "Required fields may be missing or have wrong types".to_string(),
-            )))
-            .into_response();
+    match (request_id, result) {
+        (None, _) => StatusCode::NO_CONTENT.into_response(),
+        (Some(request_id), Ok(result)) => {
+            (StatusCode::OK, Json(A2AResponse::ok(request_id, result))).into_response()
         }
+        (Some(request_id), Err(e)) => rpc_error(Some(request_id), e),
+    }
+}
+
+async fn dispatch_batch(state: AppState, calls: Vec<Value>) -> Response {
+    if let Err(e) = check_batch(&calls) {
+        return rpc_error(None, e);
+    }
+
+    tracing::info!("Received A2A batch of {} calls", calls.len());
+
+    let responses = stream::iter(calls)
+        .map(|call| dispatch_batch_call(state.clone(), call))
+        .buffered(BATCH_CONCURRENCY)
+        .filter_map(|response| async move { response })
+        .collect::<Vec<_>>()
+        .await;
+
+    if responses.is_empty() {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    (StatusCode::OK, Json(responses)).into_response()
+}
+
+async fn dispatch_batch_call(state: AppState, call: Value) -> Option<A2AResponse<Value>> {
+    let request = match parse_call(call) {
+        Ok(request) => request,
+        Err((request_id, e)) => return Some(A2AResponse::error(request_id, e)),
     };
 
-    get_trending_inner(state, request).await
+    let request_id = request.id.clone();
+    let result = call_method(state, request).await;
+
+    Some(match result {
+        Ok(result) => A2AResponse::ok(request_id?, result),
+        Err(e) => A2AResponse::error(Some(request_id?), e),
+    })
 }
 
-async fn get_trending_inner(state: AppState, request: A2ARequest) -> Response {
-    tracing::info!("Received A2A request: ?{}", request.id);
+async fn call_method(state: AppState, request: A2ARequest<Value>) -> Result<Value, RpcError> {
+    match request.method.as_str() {
+        MESSAGE_SEND => to_result(send_message(state, request.with_params()?).await?),
+        MESSAGE_STREAM => Err(RpcError::UnsupportedOperation(
+            "message/stream cannot be batched or sent as a notification".to_string(),
+        )),
+        TASKS_GET => to_result(get_task(&state, request.with_params()?)?),
+        TASKS_CANCEL => to_result(cancel_task(&state, request.with_params()?)?),
+        TASKS_PUSH_NOTIFICATION_CONFIG_SET => to_result(set_push_notification_config(
+            &state,
+            request.with_params()?,
+        )?),
+        TASKS_PUSH_NOTIFICATION_CONFIG_GET => to_result(get_push_notification_config(
+            &state,
+            request.with_params()?,
+        )?),
+        method => Err(RpcError::MethodNotFound(method.to_string())),
+    }
+}
 
-    if request.jsonrpc != "2.0".to_string() {
-        return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-            -32602,
-            "Invalid params: jsonrpc must be '2.0'".to_string(),
-        )))
-        .into_response();
+fn to_result<T: Serialize>(result: T) -> Result<Value, RpcError> {
+    serde_json::to_value(result).map_err(|e| RpcError::Internal(e.to_string()))
+}
+
+fn rpc_error(request_id: Option<RequestId>, error: RpcError) -> Response {
+    (
+        error.status(),
+        Json(A2AResponse::<Value>::error(request_id, error)),
+    )
+        .into_response()
+}
+
+fn get_task(
+    state: &AppState,
+    request: A2ARequest<TaskQueryParams>,
+) -> Result<TaskResult, RpcError> {
+    state
+        .task_store
+        .get(&request.params.id, request.params.history_length)
+        .ok_or(RpcError::TaskNotFound(request.params.id))
+}
+
+fn cancel_task(
+    state: &AppState,
+    request: A2ARequest<TaskIdParams>,
+) -> Result<TaskResult, RpcError> {
+    state
+        .task_store
+        .cancel(&request.params.id)
+        .map_err(task_store_error)
+}
+
+fn task_store_error(error: TaskStoreError) -> RpcError {
+    match error {
+        TaskStoreError::NotFound(task_id) => RpcError::TaskNotFound(task_id),
+        TaskStoreError::NotCancelable(state) => RpcError::TaskNotCancelable(state),
+        TaskStoreError::Closed(_) | TaskStoreError::Busy(_) => {
+            RpcError::InvalidParams(error.to_string())
+        }
     }
+}
+
+fn set_push_notification_config(
+    state: &AppState,
+    request: A2ARequest<TaskPushNotificationConfig>,
+) -> Result<TaskPushNotificationConfig, RpcError> {
+    let task_id = &request.params.task_id;
 
-    if request.method != "message/send" {
-        return Json(A2AResponse::error(-32601, "Method not found".to_string())).into_response();
+    if state.task_store.get(task_id, Some(0)).is_none() {
+        return Err(RpcError::TaskNotFound(task_id.clone()));
     }
 
-    let user_text = match extract_user_query(&request) {
-        Some(text) => text,
-        None => {
-            tracing::error!("Failed to extract user query from request");
+    state
+        .push_notifier
+        .set(task_id, request.params.push_notification_config.clone())
+        .map_err(|e| RpcError::InvalidParams(e.to_string()))?;
 
-            return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-                -32602,
-                "no message text found".to_string(),
-            )))
-            .into_response();
+    Ok(request.params)
+}
+
+fn get_push_notification_config(
+    state: &AppState,
+    request: A2ARequest<TaskIdParams>,
+) -> Result<TaskPushNotificationConfig, RpcError> {
+    let task_id = request.params.id;
+
+    if state.task_store.get(&task_id, Some(0)).is_none() {
+        return Err(RpcError::TaskNotFound(task_id));
+    }
+
+    match state.push_notifier.get(&task_id) {
+        Some(config) => Ok(TaskPushNotificationConfig {
+            task_id,
+            push_notification_config: config,
+        }),
+        None => Err(RpcError::PushNotificationConfigNotFound(task_id)),
+    }
+}
+
+struct PreparedMessage {
+    task_id: String,
+    context_id: String,
+    user_text: String,
+    intent: Intent,
+    telex_metadata: Option<TelexMetadata>,
+    blocking: bool,
+    submitted: TaskResult,
+}
+
+fn prepare_message(state: &AppState, request: &A2ARequest) -> Result<PreparedMessage, RpcError> {
+    let user_text = extract_user_query(request).ok_or_else(|| {
+        tracing::error!("Failed to extract user query from request");
+
+        RpcError::InvalidParams("no message text found".to_string())
+    })?;
+
+    tracing::info!("User query: {}", user_text);
+
+    let task_id = request
+        .params
+        .message
+        .task_id
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let context_id = resolve_context_id(state, &request.params.message);
+
+    state
+        .task_store
+        .accepts(&task_id, &context_id)
+        .map_err(task_store_error)?;
+
+    // Answers to a clarifying question always continue the trending search.
+    let (user_text, intent) = match resume_clarification(state, &task_id, &user_text) {
+        Some(resumed_query) => (resumed_query, Intent::TrendingSearch),
+        None => {
+            let intent = classify_intent(state, &context_id, &user_text);
+            (user_text, intent)
         }
     };
 
-    tracing::info!("User query: {}", user_text);
+    let configuration = request.params.configuration.as_ref();
 
-    let params = if let Some(cached_params) = state.cache.get_llm(&user_text) {
-        cached_params
-    } else {
-        match state.query_parser.parse(&user_text).await {
-            Ok(param) => {
-                tracing::info!("Parsed parameters: {:?}", param);
+    if let Some(push_config) = configuration.and_then(|c| c.push_notification_config.clone())
+        && let Err(e) = state.push_notifier.set(&task_id, push_config)
+    {
+        tracing::error!("Rejected push notification config: {}", e);
 
-                state.cache.set(Some(&user_text), &param, None);
-                param
+        return Err(RpcError::InvalidParams(e.to_string()));
+    }
+
+    let submitted = state
+        .task_store
+        .submit(&task_id, &context_id, &request.params.message)
+        .map_err(task_store_error)?;
+
+    Ok(PreparedMessage {
+        task_id,
+        context_id,
+        user_text,
+        intent,
+        telex_metadata: request.params.message.telex_metadata.clone(),
+        blocking: configuration.is_none_or(|c| c.blocking),
+        submitted,
+    })
+}
+
+async fn send_message(state: AppState, request: A2ARequest) -> Result<TaskResult, RpcError> {
+    let prepared = prepare_message(&state, &request)?;
+
+    if !prepared.blocking {
+        tracing::info!("Processing task {} in the background", prepared.task_id);
+
+        let submitted = prepared.submitted.clone();
+
+        tokio::spawn(async move {
+            let _ = process_message(
+                &state,
+                &prepared.task_id,
+                &prepared.context_id,
+                &prepared.user_text,
+                &prepared.intent,
+                prepared.telex_metadata.as_ref(),
+            )
+            .await;
+
+            if let Some(task) = state.task_store.get(&prepared.task_id, None) {
+                state.push_notifier.notify(&task).await;
             }
+        });
+
+        return Ok(submitted);
+    }
+
+    process_message(
+        &state,
+        &prepared.task_id,
+        &prepared.context_id,
+        &prepared.user_text,
+        &prepared.intent,
+        prepared.telex_metadata.as_ref(),
+    )
+    .await
+}
+
+fn stream_message(
+    state: AppState,
+    request: A2ARequest,
+    request_id: RequestId,
+) -> Result<Response, RpcError> {
+    let prepared = prepare_message(&state, &request)?;
+
+    Ok(stream_trending(
+        state,
+        request_id,
+        prepared.task_id,
+        prepared.context_id,
+        prepared.user_text,
+        prepared.intent,
+    ))
+}
+
+async fn process_message(
+    state: &AppState,
+    task_id: &str,
+    context_id: &str,
+    user_text: &str,
+    intent: &Intent,
+    telex_metadata: Option<&TelexMetadata>,
+) -> Result<TaskResult, RpcError> {
+    if let Some(reply) = intents::respond(state, intent, task_id, context_id).await {
+        let reply = match reply {
+            Ok(reply) => reply,
             Err(e) => {
-                tracing::error!("Failed to parse query with LLM: {}", e);
-                return (StatusCode::BAD_REQUEST, Json(A2AResponse::error(
-                    -32700,
-                    "Unable to process your query. Please try rephrasing.".to_string(),
-                )))
-                .into_response();
+                fail_task(state, task_id, "Failed to fetch repository details");
+
+                return Err(e);
             }
+        };
+
+        return state
+            .task_store
+            .complete(
+                task_id,
+                "completed",
+                Message::agent(reply.text, task_id, telex_metadata),
+                reply.artifacts,
+            )
+            .or_else(|| state.task_store.get(task_id, None))
+            .ok_or_else(|| RpcError::TaskNotFound(task_id.to_string()));
+    }
+
+    state.task_store.transition(
+        task_id,
+        "working",
+        Message::agent("Parsing query".to_string(), task_id, None),
+    );
+
+    let turn = match resolve_params(state, context_id, user_text).await {
+        Ok(turn) => turn,
+        Err(e) => {
+            tracing::error!("Failed to parse query with LLM: {}", e);
+
+            fail_task(state, task_id, "Unable to process your query");
+
+            return Err(RpcError::QueryParseFailed);
+        }
+    };
+
+    if let Some(question) = &turn.clarification {
+        state
+            .conversation_store
+            .await_clarification(task_id, user_text);
+
+        return state
+            .task_store
+            .complete(
+                task_id,
+                "input-required",
+                Message::agent(question.clone(), task_id, telex_metadata),
+                vec![],
+            )
+            .or_else(|| state.task_store.get(task_id, None))
+            .ok_or_else(|| RpcError::TaskNotFound(task_id.to_string()));
+    }
+
+    let mut result = match fetch_repos(state, &turn).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("GitHub API error: {}", e);
+
+            fail_task(state, task_id, "Failed to fetch trending repositories");
+
+            return Err(RpcError::GitHubUnavailable);
         }
     };
 
-    let repos = if let Some(cached_repos) = state.cache.get_repo(&params) {
-        cached_repos
-    } else {
-        match state.github_client.search_with_params(&params).await {
-            Ok(repos) => {
-                state.cache.set(None, &params, Some(repos.clone()));
-                repos
+    if let Some(enricher) = &state.enricher {
+        enricher.enrich(&mut result.repos).await;
+    }
+
+    state
+        .conversation_store
+        .record(context_id, &turn.params, &result.repos, turn.is_more());
+
+    let data_artifact = create_data_artifact(&turn.params, &result, turn.parsed_by.as_deref());
+
+    let response_text = format_trending_message(&result.repos, turn.params);
+
+    let mut artifacts = create_artifacts(response_text.clone());
+    artifacts.push(data_artifact);
+
+    let response_message = Message::agent(response_text, task_id, telex_metadata);
+
+    let task = state
+        .task_store
+        .complete(task_id, "completed", response_message, artifacts)
+        .or_else(|| state.task_store.get(task_id, None))
+        .ok_or_else(|| RpcError::TaskNotFound(task_id.to_string()))?;
+
+    tracing::info!(
+        "Task {} completed with {} repos",
+        task_id,
+        result.repos.len()
+    );
+
+    Ok(task)
+}
+
+fn fail_task(state: &AppState, task_id: &str, text: &str) {
+    state.task_store.complete(
+        task_id,
+        "failed",
+        Message::agent(text.to_string(), task_id, None),
+        vec![],
+    );
+}
+
+/// Returns the original query combined with the answer when the task was
+/// waiting on a clarification.
+fn resume_clarification(state: &AppState, task_id: &str, user_text: &str) -> Option<String> {
+    let awaiting_input = state
+        .task_store
+        .get(task_id, Some(0))
+        .is_some_and(|task| task.status.state == "input-required");
+
+    if !awaiting_input {
+        return None;
+    }
+
+    let original_query = state.conversation_store.take_pending_query(task_id)?;
+
+    tracing::info!("Resuming task {} with clarification", task_id);
+
+    Some(format!("{}\nClarification: {}", original_query, user_text))
+}
+
+fn classify_intent(state: &AppState, context_id: &str, user_text: &str) -> Intent {
+    match state.intent_router.classify(user_text) {
+        // Terse follow-ups like "and python?" read as off-topic on their own.
+        Intent::OffTopic if state.conversation_store.get(context_id).is_some() => {
+            Intent::TrendingSearch
+        }
+        intent => intent,
+    }
+}
+
+fn resolve_context_id(state: &AppState, message: &Message) -> String {
+    message
+        .context_id
+        .clone()
+        .or_else(|| {
+            message
+                .task_id
+                .as_ref()
+                .and_then(|task_id| state.task_store.get(task_id, Some(0)))
+                .map(|task| task.context_id)
+        })
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+struct SearchTurn {
+    params: QueryParams,
+    exclude: Vec<String>,
+    clarification: Option<String>,
+    parsed_by: Option<String>,
+}
+
+impl SearchTurn {
+    fn is_more(&self) -> bool {
+        !self.exclude.is_empty()
+    }
+}
+
+async fn resolve_params(state: &AppState, context_id: &str, user_text: &str) -> Result<SearchTurn> {
+    if let Some(conversation) = state.conversation_store.get(context_id) {
+        let follow_up = state
+            .query_parser
+            .parse_follow_up(user_text, &conversation.params)
+            .await?;
+
+        tracing::info!(
+            "Parsed follow-up for context {}: {:?} (more results: {})",
+            context_id,
+            follow_up.params,
+            follow_up.more_results
+        );
+
+        let exclude = match follow_up.more_results {
+            true => conversation.shown,
+            false => vec![],
+        };
+
+        return Ok(SearchTurn {
+            params: follow_up.params,
+            exclude,
+            clarification: None,
+            parsed_by: follow_up.parsed_by,
+        });
+    }
+
+    if let Some(cached_params) = state.cache.get_llm(user_text) {
+        return Ok(SearchTurn {
+            params: cached_params,
+            exclude: vec![],
+            clarification: None,
+            parsed_by: Some("cache".to_string()),
+        });
+    }
+
+    let parsed = state.query_parser.parse(user_text).await?;
+
+    if let Some(question) = parsed.clarification() {
+        tracing::info!(
+            "Query needs clarification (confidence: {}): {}",
+            parsed.confidence,
+            user_text
+        );
+
+        return Ok(SearchTurn {
+            params: parsed.params,
+            exclude: vec![],
+            clarification: Some(question),
+            parsed_by: parsed.parsed_by,
+        });
+    }
+
+    let params = parsed.params;
+    let parsed_by = parsed.parsed_by;
+
+    tracing::info!(
+        "Parsed parameters ({}): {:?}",
+        parsed_by.as_deref().unwrap_or("unknown"),
+        params
+    );
+
+    // Rule-based parses are cheap and shouldn't outlive an LLM outage.
+    if parsed_by.as_deref() != Some(RULES_PROVIDER) {
+        state.cache.set(Some(user_text), &params, None);
+    }
+
+    Ok(SearchTurn {
+        params,
+        exclude: vec![],
+        clarification: None,
+        parsed_by,
+    })
+}
+
+async fn fetch_repos(state: &AppState, turn: &SearchTurn) -> Result<SearchResult> {
+    if !turn.is_more() {
+        return fetch_cached_repos(state, &turn.params).await;
+    }
+
+    let mut params = turn.params.clone();
+    params.count = (params.count + turn.exclude.len()).min(MAX_SEARCH_RESULTS);
+
+    let mut result = fetch_cached_repos(state, &params).await?;
+
+    result.repos = result
+        .repos
+        .into_iter()
+        .filter(|repo| !turn.exclude.contains(&repo.name))
+        .take(turn.params.count)
+        .collect();
+
+    Ok(result)
+}
+
+async fn fetch_cached_repos(state: &AppState, params: &QueryParams) -> Result<SearchResult> {
+    if let Some(cached_result) = state.cache.get_repo(params) {
+        return Ok(cached_result);
+    }
+
+    let result = state.github_client.search_with_params(params).await?;
+
+    state.cache.set(None, params, Some(result.clone()));
+
+    Ok(result)
+}
+
+/// Records task updates and streams them. The methods return `false` once
+/// the task is canceled. A client that disconnects doesn't stop the task:
+/// it still runs to a final state that `tasks/get` can report.
+struct StreamEmitter {
+    tx: UnboundedSender<A2AResponse<StreamEvent>>,
+    task_store: TaskStore,
+    request_id: RequestId,
+    task_id: String,
+    context_id: String,
+}
+
+impl StreamEmitter {
+    fn status(&self, state: &str, text: &str) -> bool {
+        let message = Message::agent(text.to_string(), &self.task_id, None);
+
+        if self
+            .task_store
+            .transition(&self.task_id, state, message)
+            .is_none()
+        {
+            return false;
+        }
+
+        self.send_status(state, text.to_string(), false);
+        true
+    }
+
+    fn finish(&self, state: &str, text: String, artifacts: Vec<Artifact>) -> bool {
+        let message = Message::agent(text.clone(), &self.task_id, None);
+
+        if self
+            .task_store
+            .complete(&self.task_id, state, message, artifacts)
+            .is_none()
+        {
+            return false;
+        }
+
+        self.send_status(state, text, true);
+        true
+    }
+
+    fn send_status(&self, state: &str, text: String, is_final: bool) {
+        self.send(A2AResponse::status_update(
+            &self.request_id,
+            &self.task_id,
+            &self.context_id,
+            state,
+            text,
+            is_final,
+        ));
+    }
+
+    fn artifact(&self, artifact: Artifact, append: bool, last_chunk: bool) -> bool {
+        if self.task_store.is_canceled(&self.task_id) {
+            return false;
+        }
+
+        self.send(A2AResponse::artifact_update(
+            &self.request_id,
+            &self.task_id,
+            &self.context_id,
+            artifact,
+            append,
+            last_chunk,
+        ));
+        true
+    }
+
+    fn send(&self, event: A2AResponse<StreamEvent>) {
+        if self.tx.unbounded_send(event).is_err() {
+            tracing::debug!(
+                "Stream for task {} is closed, processing continues",
+                self.task_id
+            );
+        }
+    }
+}
+
+fn stream_trending(
+    state: AppState,
+    request_id: RequestId,
+    task_id: String,
+    context_id: String,
+    user_text: String,
+    intent: Intent,
+) -> Response {
+    let (tx, rx) = mpsc::unbounded();
+
+    let emitter = StreamEmitter {
+        tx,
+        task_store: state.task_store.clone(),
+        request_id,
+        task_id,
+        context_id,
+    };
+
+    emitter.send_status("submitted", "Request received".to_string(), false);
+
+    tokio::spawn(run_stream(state, emitter, user_text, intent));
+
+    Sse::new(rx.map(|event| Event::default().json_data(event)))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn run_stream(state: AppState, emitter: StreamEmitter, user_text: String, intent: Intent) {
+    if let Some(reply) =
+        intents::respond(&state, &intent, &emitter.task_id, &emitter.context_id).await
+    {
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(_) => {
+                emitter.finish(
+                    "failed",
+                    "Failed to fetch repository details. Try again later".to_string(),
+                    vec![],
+                );
+                return;
             }
-            Err(e) => {
-                tracing::error!("GitHub API error: {}", e);
+        };
 
-                return (StatusCode::INTERNAL_SERVER_ERROR, Json(A2AResponse::error(
-                    -32600,
-                    "Failed to fetch trending repositories. Try again later".to_string(),
-                )))
-                .into_response();
+        for artifact in &reply.artifacts {
+            if !emitter.artifact(artifact.clone(), false, true) {
+                return;
             }
         }
+
+        emitter.finish("completed", reply.text, reply.artifacts);
+        return;
+    }
+
+    if !emitter.status("working", "Parsing query") {
+        return;
+    }
+
+    let turn = match resolve_params(&state, &emitter.context_id, &user_text).await {
+        Ok(turn) => turn,
+        Err(e) => {
+            tracing::error!("Failed to parse query with LLM: {}", e);
+            emitter.finish(
+                "failed",
+                "Unable to process your query. Please try rephrasing.".to_string(),
+                vec![],
+            );
+            return;
+        }
     };
 
-    let response_text = format_trending_message(&repos, params);
+    if let Some(question) = &turn.clarification {
+        state
+            .conversation_store
+            .await_clarification(&emitter.task_id, &user_text);
+        emitter.finish("input-required", question.clone(), vec![]);
+        return;
+    }
 
-    let artifacts = create_artifacts(response_text.clone());
+    if !emitter.status("working", "Searching GitHub") {
+        return;
+    }
 
-    let response = A2AResponse::success(
-        request.id,
-        Some(
-            request
-                .params
-                .message
-                .task_id
-                .clone()
-                .unwrap_or_else(|| Uuid::new_v4().to_string()),
-        ),
-        response_text,
-        artifacts,
-        &request.params.message,
+    let mut result = match fetch_repos(&state, &turn).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("GitHub API error: {}", e);
+            emitter.finish(
+                "failed",
+                "Failed to fetch trending repositories. Try again later".to_string(),
+                vec![],
+            );
+            return;
+        }
+    };
+
+    if let Some(enricher) = &state.enricher {
+        enricher.enrich(&mut result.repos).await;
+    }
+
+    state.conversation_store.record(
+        &emitter.context_id,
+        &turn.params,
+        &result.repos,
+        turn.is_more(),
     );
 
-    tracing::info!("Sending successful response with {} repos", repos.len());
+    let data_artifact = create_data_artifact(&turn.params, &result, turn.parsed_by.as_deref());
+
+    let response_text = format_trending_message(&result.repos, turn.params);
 
-    (StatusCode::OK, Json(response)).into_response()
+    let artifact_id = Uuid::new_v4().to_string();
+    let chunks = response_text.split_inclusive("---\n").collect::<Vec<_>>();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let artifact = Artifact {
+            artifact_id: artifact_id.clone(),
+            name: "gitpulseAgentResponse".to_string(),
+            parts: vec![MessagePart::Text {
+                kind: "text".to_string(),
+                text: chunk.to_string(),
+            }],
+        };
+
+        if !emitter.artifact(artifact, i > 0, i == chunks.len() - 1) {
+            return;
+        }
+    }
+
+    if !emitter.artifact(data_artifact.clone(), false, true) {
+        return;
+    }
+
+    let mut artifacts = create_artifacts(response_text.clone());
+    artifacts[0].artifact_id = artifact_id;
+    artifacts.push(data_artifact);
+
+    if !emitter.finish("completed", response_text, artifacts) {
+        return;
+    }
+
+    tracing::info!(
+        "Streamed successful response with {} repos",
+        result.repos.len()
+    );
 }
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use axum::{
+    Json,
+    http::{HeaderValue, header::RETRY_AFTER},
+    response::{IntoResponse, Response},
+};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::{
+    models::a2a::{A2ARequest, A2AResponse, ErrorDetail, RequestId},
+    services::rate_limiter::RateLimitError,
+};
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+
+pub const TASK_NOT_FOUND: i32 = -32001;
+pub const TASK_NOT_CANCELABLE: i32 = -32002;
+pub const UNSUPPORTED_OPERATION: i32 = -32004;
+
+pub const QUERY_PARSE_FAILED: i32 = -32050;
+pub const GITHUB_UNAVAILABLE: i32 = -32051;
+pub const PUSH_NOTIFICATION_CONFIG_NOT_FOUND: i32 = -32052;
+pub const RATE_LIMITED: i32 = -32053;
+
+pub const MAX_BATCH_SIZE: usize = 20;
+
+const QUERY_RETRY_AFTER_SECS: u64 = 5;
+const GITHUB_RETRY_AFTER_SECS: u64 = 60;
+
+#[derive(Debug)]
+pub enum RpcError {
+    ParseError(String),
+    InvalidRequest(String),
+    MethodNotFound(String),
+    InvalidParams(String),
+    Internal(String),
+    TaskNotFound(String),
+    TaskNotCancelable(String),
+    UnsupportedOperation(String),
+    QueryParseFailed,
+    GitHubUnavailable,
+    PushNotificationConfigNotFound(String),
+    RateLimited(u64),
+}
+
+impl RpcError {
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::ParseError(_) => PARSE_ERROR,
+            Self::InvalidRequest(_) => INVALID_REQUEST,
+            Self::MethodNotFound(_) => METHOD_NOT_FOUND,
+            Self::InvalidParams(_) => INVALID_PARAMS,
+            Self::Internal(_) => INTERNAL_ERROR,
+            Self::TaskNotFound(_) => TASK_NOT_FOUND,
+            Self::TaskNotCancelable(_) => TASK_NOT_CANCELABLE,
+            Self::UnsupportedOperation(_) => UNSUPPORTED_OPERATION,
+            Self::QueryParseFailed => QUERY_PARSE_FAILED,
+            Self::GitHubUnavailable => GITHUB_UNAVAILABLE,
+            Self::PushNotificationConfigNotFound(_) => PUSH_NOTIFICATION_CONFIG_NOT_FOUND,
+            Self::RateLimited(_) => RATE_LIMITED,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::ParseError(_)
+            | Self::InvalidRequest(_)
+            | Self::InvalidParams(_)
+            | Self::QueryParseFailed => StatusCode::BAD_REQUEST,
+            Self::Internal(_) | Self::GitHubUnavailable => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::OK,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::QueryParseFailed => Some(QUERY_RETRY_AFTER_SECS),
+            Self::GitHubUnavailable => Some(GITHUB_RETRY_AFTER_SECS),
+            Self::RateLimited(secs) => Some(*secs),
+            _ => None,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Internal(_)) || self.retry_after().is_some()
+    }
+
+    fn data(&self) -> Value {
+        let mut data = json!({ "retryable": self.is_retryable() });
+
+        if let Some(secs) = self.retry_after() {
+            data["retryAfterSeconds"] = json!(secs);
+        }
+
+        match self {
+            Self::ParseError(details)
+            | Self::InvalidRequest(details)
+            | Self::InvalidParams(details) => data["details"] = json!(details),
+            Self::MethodNotFound(method) => data["method"] = json!(method),
+            Self::TaskNotFound(task_id) | Self::PushNotificationConfigNotFound(task_id) => {
+                data["taskId"] = json!(task_id)
+            }
+            _ => {}
+        }
+
+        data
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError(_) => write!(f, "Parse error: Invalid JSON"),
+            Self::InvalidRequest(message) => write!(f, "Invalid request: {}", message),
+            Self::MethodNotFound(_) => write!(f, "Method not found"),
+            Self::InvalidParams(_) => write!(
+                f,
+                "Invalid params: required fields may be missing or have wrong types"
+            ),
+            Self::Internal(_) => write!(f, "Internal error"),
+            Self::TaskNotFound(_) => write!(f, "Task not found"),
+            Self::TaskNotCancelable(state) => {
+                write!(f, "Task cannot be canceled in state '{}'", state)
+            }
+            Self::UnsupportedOperation(message) => write!(f, "{}", message),
+            Self::QueryParseFailed => {
+                write!(f, "Unable to process your query. Please try rephrasing.")
+            }
+            Self::GitHubUnavailable => {
+                write!(f, "Failed to fetch trending repositories. Try again later")
+            }
+            Self::PushNotificationConfigNotFound(_) => {
+                write!(f, "No push notification config set for task")
+            }
+            Self::RateLimited(_) => write!(f, "Too many requests"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<serde_json::Error> for RpcError {
+    fn from(e: serde_json::Error) -> Self {
+        tracing::error!("A2A params deserialization error: {}", e);
+
+        Self::InvalidParams(e.to_string())
+    }
+}
+
+impl From<RateLimitError> for RpcError {
+    fn from(error: RateLimitError) -> Self {
+        match error {
+            RateLimitError::Exceeded(retry_after) => Self::RateLimited(retry_after),
+            RateLimitError::Unconfigured => Self::Internal(error.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for RateLimitError {
+    fn into_response(self) -> Response {
+        let error = RpcError::from(self);
+        let retry_after = error.retry_after();
+
+        let mut response = (
+            error.status(),
+            Json(A2AResponse::<Value>::error(None, error)),
+        )
+            .into_response();
+
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+
+        response
+    }
+}
+
+impl From<RpcError> for ErrorDetail {
+    fn from(error: RpcError) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            data: Some(error.data()),
+        }
+    }
+}
+
+/// Rejects batches that are empty or larger than [`MAX_BATCH_SIZE`].
+pub fn check_batch(calls: &[Value]) -> Result<(), RpcError> {
+    match calls.len() {
+        0 => Err(RpcError::InvalidRequest("Empty batch received".to_string())),
+        len if len > MAX_BATCH_SIZE => Err(RpcError::InvalidRequest(format!(
+            "Batch of {} calls exceeds the limit of {}",
+            len, MAX_BATCH_SIZE
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn parse_call(mut call: Value) -> Result<A2ARequest<Value>, (Option<RequestId>, RpcError)> {
+    let Some(object) = call.as_object_mut() else {
+        return Err((
+            None,
+            RpcError::InvalidRequest("Request must be a JSON object".to_string()),
+        ));
+    };
+
+    if object.is_empty() {
+        return Err((
+            None,
+            RpcError::InvalidRequest("Empty JSON object received".to_string()),
+        ));
+    }
+
+    let request_id = object
+        .get("id")
+        .and_then(|id| serde_json::from_value::<RequestId>(id.clone()).ok());
+
+    let request = serde_json::from_value::<A2ARequest<Value>>(call).map_err(|e| {
+        tracing::error!("A2ARequest deserialization error: {}", e);
+
+        (
+            request_id.clone(),
+            RpcError::InvalidRequest(
+                "Required fields may be missing or have wrong types".to_string(),
+            ),
+        )
+    })?;
+
+    if request.jsonrpc != "2.0" {
+        return Err((
+            request_id,
+            RpcError::InvalidRequest("jsonrpc must be '2.0'".to_string()),
+        ));
+    }
+
+    Ok(request)
+}
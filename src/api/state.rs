@@ -1,6 +1,11 @@
 use crate::{
     config::settings::Config,
-    services::{ai::QueryParser, cache::Cache, github::GitHubClient, rate_limiter::RateLimiter},
+    services::{
+        ai::QueryParser, cache::Cache, conversation_store::ConversationStore,
+        enricher::RepoEnricher, github::GitHubClient, intent::IntentRouter,
+        push_notifier::PushNotifier, rate_limiter::RateLimiter,
+        subscription_store::SubscriptionStore, task_store::TaskStore,
+    },
 };
 
 #[derive(Clone)]
@@ -10,4 +15,10 @@ pub struct AppState {
     pub query_parser: QueryParser,
     pub cache: Cache,
     pub rate_limiter: RateLimiter,
+    pub task_store: TaskStore,
+    pub push_notifier: PushNotifier,
+    pub conversation_store: ConversationStore,
+    pub enricher: Option<RepoEnricher>,
+    pub intent_router: IntentRouter,
+    pub subscription_store: SubscriptionStore,
 }
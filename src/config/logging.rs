@@ -5,8 +5,10 @@ use tracing_appender::{
 };
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-pub fn setup_logging() -> non_blocking::WorkerGuard {
-    let env_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".into());
+/// Logs to the console and a daily log file. `RUST_LOG` overrides
+/// `default_filter`.
+pub fn setup_logging(default_filter: &str) -> non_blocking::WorkerGuard {
+    let env_filter = env::var("RUST_LOG").unwrap_or_else(|_| default_filter.into());
 
     let console_layer = fmt::layer()
         .with_target(false)
@@ -1,37 +1,78 @@
-use std::fs;
+use std::{fmt::Display, str::FromStr};
 
-use anyhow::{Error, Ok, anyhow};
+use anyhow::{Error, anyhow};
 use dotenvy::dotenv;
 use envy::from_env;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, de};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub llm_provider: String,
     pub github_search_url: String,
-    pub github_access_token: String,
+    pub github_access_token: Option<String>,
+    pub github_access_tokens: Option<String>,
+    pub github_app_id: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub github_app_installation_id: Option<u64>,
+    pub github_app_private_key_path: Option<String>,
+    pub github_search_backend: Option<String>,
     pub external_webhook_url: String,
     pub llm_api_key: String,
     pub llm_model: String,
-    pub system_prompt: Option<String>,
+    pub llm_base_url: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub llm_json_mode: Option<bool>,
+    pub llm_fallbacks: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub llm_timeout_secs: Option<u64>,
+    pub anthropic_api_key: Option<String>,
+    pub gemini_api_key: Option<String>,
+    pub openai_api_key: Option<String>,
+    pub system_prompt_path: Option<String>,
+    pub few_shot_examples_path: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub prompt_reload_secs: Option<u64>,
+    pub admin_token: Option<String>,
     pub cache_ttl: u64,
     pub host: String,
     pub port: u32,
+    pub public_url: Option<String>,
     pub cors_allowed_origins: String,
     pub rate_limit_ms: u32,
+    pub task_store_dir: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub task_ttl_secs: Option<u64>,
+    pub snapshot_db_path: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub enrich_results: Option<bool>,
+    pub topic_synonyms_path: Option<String>,
+    pub topic_match: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub search_fanout_budget: Option<usize>,
 }
 
 impl Config {
     pub fn load() -> Result<Self, Error> {
         dotenv().ok();
 
-        let mut config = from_env::<Self>().map_err(|e| anyhow!("Configuration error: {}", e))?;
-
-        let system_prompt = fs::read_to_string("system_prompt.txt")
-            .map_err(|e| anyhow!("Failed to load system prompt: {}", e))?;
-
-        config.system_prompt = Some(system_prompt);
+        let config = from_env::<Self>().map_err(|e| anyhow!("Configuration error: {}", e))?;
 
         Ok(config)
     }
 }
+
+/// envy reads `KEY=` as an empty string, which doesn't parse as a number or
+/// bool. Treats it as unset instead.
+fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if !value.trim().is_empty() => {
+            value.trim().parse().map(Some).map_err(de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
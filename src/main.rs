@@ -1,25 +1,62 @@
-use std::net::SocketAddr;
+use std::{env, net::SocketAddr, process, time::Duration};
 
 use anyhow::{Context, Error, Result};
 use gitpulse::{
     api::{build_router, state::AppState},
     config::{logging::setup_logging, settings::Config},
+    models::{
+        normalize::{DEFAULT_TOPIC_SYNONYMS_PATH, TopicMap},
+        search_query::TopicMatch,
+    },
     services::{
-        ai::QueryParser, cache::Cache, github::GitHubClient, rate_limiter::RateLimiter,
+        ai::{DEFAULT_LLM_TIMEOUT_SECS, QueryParser},
+        cache::Cache,
+        conversation_store::ConversationStore,
+        enricher::RepoEnricher,
+        eval,
+        github::{DEFAULT_FANOUT_BUDGET, GitHubClient, SearchBackend},
+        github_app::GitHubAppAuth,
+        intent::IntentRouter,
+        llm::{LlmSettings, build_chain},
+        prompts::{
+            DEFAULT_EXAMPLES_PATH, DEFAULT_PROMPT_RELOAD_SECS, DEFAULT_SYSTEM_PROMPT_PATH,
+            PromptStore,
+        },
+        push_notifier::PushNotifier,
+        rate_limiter::RateLimiter,
         scheduler::AgentScheduler,
+        snapshot_store::SnapshotStore,
+        subscription_store::SubscriptionStore,
+        task_store::{DEFAULT_TASK_TTL, TaskStore},
     },
 };
 use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let _guard = setup_logging();
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if args.first().is_some_and(|command| command == "eval") {
+        let _guard = setup_logging("warn");
+
+        if !eval::run_command(&args[1..]).await? {
+            process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    let _guard = setup_logging("info");
 
     let config = Config::load()?;
 
     tracing::info!(
         "GitHub access token: {}",
-        match !config.github_access_token.is_empty() {
+        match config
+            .github_access_token
+            .as_deref()
+            .is_some_and(|token| !token.trim().is_empty())
+        {
             true => "configured",
             false => "not configured",
         }
@@ -57,26 +94,146 @@ async fn main() -> Result<(), Error> {
         }
     );
 
-    let github_client =
-        GitHubClient::new(Some(&config.github_access_token), &config.github_search_url)?;
+    let topic_map = TopicMap::load(
+        config
+            .topic_synonyms_path
+            .as_deref()
+            .unwrap_or(DEFAULT_TOPIC_SYNONYMS_PATH),
+    )?;
+
+    let topic_match = match &config.topic_match {
+        Some(topic_match) => topic_match.parse()?,
+        None => TopicMatch::default(),
+    };
+
+    let github_tokens = config
+        .github_access_token
+        .as_deref()
+        .into_iter()
+        .chain(
+            config
+                .github_access_tokens
+                .as_deref()
+                .unwrap_or_default()
+                .split(','),
+        )
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+
+    let search_backend = match &config.github_search_backend {
+        Some(backend) => backend.parse()?,
+        None => SearchBackend::default(),
+    };
+
+    let github_app = config
+        .github_app_id
+        .as_deref()
+        .is_some_and(|app_id| !app_id.trim().is_empty());
+
+    if search_backend == SearchBackend::GraphQl && github_tokens.is_empty() && !github_app {
+        anyhow::bail!("The GraphQL search backend needs a GitHub token or GitHub App");
+    }
+
+    let github_client = GitHubClient::new(None, &config.github_search_url)?
+        .with_tokens(&github_tokens)?
+        .with_search_backend(search_backend)
+        .with_topic_map(topic_map)
+        .with_topic_match(topic_match)
+        .with_fanout_budget(config.search_fanout_budget.unwrap_or(DEFAULT_FANOUT_BUDGET));
 
-    tracing::info!("GitHub client initialized");
+    let github_client = match config.github_app_id.as_deref() {
+        Some(app_id) if !app_id.trim().is_empty() => {
+            let installation_id = config
+                .github_app_installation_id
+                .context("GITHUB_APP_INSTALLATION_ID is required with GITHUB_APP_ID")?;
 
-    let system_prompt = config
-        .system_prompt
-        .clone()
-        .context("System prompt is required but not configured")?;
+            let key_path = config
+                .github_app_private_key_path
+                .as_deref()
+                .context("GITHUB_APP_PRIVATE_KEY_PATH is required with GITHUB_APP_ID")?;
 
-    let query_parser = QueryParser::new(
-        &config.llm_provider,
-        &config.llm_api_key,
-        &config.llm_model,
-        system_prompt.as_str(),
+            let app = GitHubAppAuth::from_key_file(app_id, installation_id, key_path)?;
+
+            if !github_tokens.is_empty() {
+                tracing::warn!("GITHUB_APP_ID is set, ignoring the GitHub access tokens");
+            }
+
+            tracing::info!(
+                "GitHub client initialized (GitHub App installation {})",
+                installation_id
+            );
+
+            github_client.with_app_auth(app)
+        }
+        _ => {
+            tracing::info!("GitHub client initialized ({} tokens)", github_tokens.len());
+
+            github_client
+        }
+    };
+
+    let github_client = match config.snapshot_db_path.as_deref() {
+        Some(path) if !path.trim().is_empty() => {
+            tracing::info!("Star snapshot store initialized ({})", path);
+
+            github_client.with_snapshot_store(SnapshotStore::open(path)?)
+        }
+        _ => github_client,
+    };
+
+    let prompts = PromptStore::load(
+        config
+            .system_prompt_path
+            .as_deref()
+            .unwrap_or(DEFAULT_SYSTEM_PROMPT_PATH),
+        config
+            .few_shot_examples_path
+            .as_deref()
+            .unwrap_or(DEFAULT_EXAMPLES_PATH),
     )
-    .await
-    .context("Failed to initialize query parser")?;
+    .context("Failed to load prompts")?;
 
-    tracing::info!("Query parser initialized");
+    let prompt_status = prompts.status();
+
+    tracing::info!(
+        "Prompts loaded (system prompt: {}, examples: {})",
+        prompt_status.system_prompt,
+        prompt_status.examples
+    );
+
+    match config
+        .prompt_reload_secs
+        .unwrap_or(DEFAULT_PROMPT_RELOAD_SECS)
+    {
+        0 => tracing::info!("Prompt file watching disabled"),
+        secs => prompts.watch(Duration::from_secs(secs)),
+    }
+
+    let llm_chain = LlmSettings::chain_from_config(&config)?;
+
+    let query_parser = match llm_chain.is_empty() {
+        false => {
+            let providers = build_chain(&llm_chain)
+                .await
+                .context("Failed to initialize LLM providers")?;
+
+            let timeout = config.llm_timeout_secs.unwrap_or(DEFAULT_LLM_TIMEOUT_SECS);
+
+            tracing::info!(
+                "Query parser initialized ({} LLM providers, {}s timeout)",
+                providers.len(),
+                timeout
+            );
+
+            QueryParser::with_chain(providers, prompts, Duration::from_secs(timeout))
+        }
+        true => {
+            tracing::warn!("No LLM configured, using the rule-based query parser");
+
+            QueryParser::rule_based()
+        }
+    };
 
     let cache = Cache::new(config.cache_ttl);
 
@@ -86,18 +243,65 @@ async fn main() -> Result<(), Error> {
 
     let rate_limiter = RateLimiter::new(config.rate_limit_ms, 60);
 
+    let task_store = TaskStore::new(config.task_store_dir.as_deref())?.with_ttl(
+        config
+            .task_ttl_secs
+            .map_or(DEFAULT_TASK_TTL, Duration::from_secs),
+    );
+
+    tracing::info!(
+        "Task store initialized ({})",
+        match config.task_store_dir.is_some() {
+            true => "persistent",
+            false => "in-memory",
+        }
+    );
+
+    let push_notifier = PushNotifier::new()?;
+
+    let conversation_store = ConversationStore::new(config.cache_ttl);
+
+    tracing::info!(
+        "Conversation store initialized (TTL: {}s)",
+        config.cache_ttl
+    );
+
+    let enricher = match config.enrich_results.unwrap_or(false) {
+        true => {
+            tracing::info!("Repository blurbs enabled");
+
+            Some(RepoEnricher::new(
+                query_parser.chain().fork(),
+                github_client.clone(),
+                config.cache_ttl,
+            ))
+        }
+        false => None,
+    };
+
     let state = AppState {
         github_client,
         config,
         query_parser,
         cache,
         rate_limiter,
+        task_store,
+        push_notifier,
+        conversation_store,
+        enricher,
+        intent_router: IntentRouter::new(),
+        subscription_store: SubscriptionStore::new(),
     };
 
     let scheduler = AgentScheduler::new(state.clone()).await?;
 
     scheduler.add_daily_digest().await?;
     scheduler.add_weekly_roundup().await?;
+    scheduler.add_cleanup().await?;
+
+    if state.github_client.tracks_snapshots() {
+        scheduler.add_star_snapshots().await?;
+    }
 
     scheduler.start().await?;
 
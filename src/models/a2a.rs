@@ -1,23 +1,56 @@
 use chrono::{SecondsFormat, Utc};
-use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+pub const MESSAGE_SEND: &str = "message/send";
+pub const MESSAGE_STREAM: &str = "message/stream";
+pub const TASKS_GET: &str = "tasks/get";
+pub const TASKS_CANCEL: &str = "tasks/cancel";
+pub const TASKS_PUSH_NOTIFICATION_CONFIG_SET: &str = "tasks/pushNotificationConfig/set";
+pub const TASKS_PUSH_NOTIFICATION_CONFIG_GET: &str = "tasks/pushNotificationConfig/get";
+
+pub const SUPPORTED_METHODS: &[&str] = &[
+    MESSAGE_SEND,
+    MESSAGE_STREAM,
+    TASKS_GET,
+    TASKS_CANCEL,
+    TASKS_PUSH_NOTIFICATION_CONFIG_SET,
+    TASKS_PUSH_NOTIFICATION_CONFIG_GET,
+];
+
 #[derive(Debug, Deserialize, ToSchema)]
-pub struct A2ARequest {
+pub struct A2ARequest<P = RequestParams> {
     pub jsonrpc: String,
-    pub id: String,
+    #[serde(default, deserialize_with = "deserialize_request_id")]
+    pub id: Option<RequestId>,
     pub method: String,
-    pub params: RequestParams,
+    pub params: P,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+    Null,
+}
+
+fn deserialize_request_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RequestId>, D::Error> {
+    RequestId::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct A2AResponse {
+pub struct A2AResponse<T = TaskResult> {
     pub jsonrpc: String,
-    pub id: Option<String>,
+    pub id: Option<RequestId>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub result: Option<TaskResult>,
+    pub result: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<ErrorDetail>,
 }
@@ -38,15 +71,58 @@ pub struct Message {
     pub message_id: String,
     #[serde(rename = "taskId")]
     pub task_id: Option<String>,
+    #[serde(rename = "contextId")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
     #[serde(rename = "metadata")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub telex_metadata: Option<TelexMetadata>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
-pub struct Configuration {
+pub struct TaskQueryParams {
+    pub id: String,
+    #[serde(rename = "historyLength")]
     #[serde(default)]
+    pub history_length: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskIdParams {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct Configuration {
+    #[serde(default = "default_blocking")]
     pub blocking: bool,
+    #[serde(rename = "pushNotificationConfig")]
+    #[serde(default)]
+    pub push_notification_config: Option<PushNotificationConfig>,
+}
+
+fn default_blocking() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PushNotificationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub id: Option<String>,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskPushNotificationConfig {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    #[serde(rename = "pushNotificationConfig")]
+    pub push_notification_config: PushNotificationConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
@@ -80,7 +156,7 @@ pub struct TelexMetadata {
     org_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TaskResult {
     pub kind: String,
     pub id: String,
@@ -91,7 +167,7 @@ pub struct TaskResult {
     pub history: Vec<Message>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TaskStatus {
     pub state: String,
     pub timestamp: String,
@@ -106,6 +182,38 @@ pub struct Artifact {
     pub parts: Vec<MessagePart>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskStatusUpdateEvent {
+    pub kind: String,
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    #[serde(rename = "contextId")]
+    pub context_id: String,
+    pub status: TaskStatus,
+    #[serde(rename = "final")]
+    pub is_final: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskArtifactUpdateEvent {
+    pub kind: String,
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    #[serde(rename = "contextId")]
+    pub context_id: String,
+    pub artifact: Artifact,
+    pub append: bool,
+    #[serde(rename = "lastChunk")]
+    pub last_chunk: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum StreamEvent {
+    StatusUpdate(TaskStatusUpdateEvent),
+    ArtifactUpdate(TaskArtifactUpdateEvent),
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorDetail {
     pub code: i32,
@@ -114,6 +222,50 @@ pub struct ErrorDetail {
     pub data: Option<Value>,
 }
 
+impl A2ARequest<Value> {
+    pub fn with_params<P: DeserializeOwned>(self) -> serde_json::Result<A2ARequest<P>> {
+        Ok(A2ARequest {
+            jsonrpc: self.jsonrpc,
+            id: self.id,
+            method: self.method,
+            params: serde_json::from_value(self.params)?,
+        })
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(id) => write!(f, "{}", id),
+            Self::String(id) => write!(f, "{}", id),
+            Self::Null => write!(f, "null"),
+        }
+    }
+}
+
+impl From<&str> for RequestId {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl Message {
+    pub fn agent(text: String, task_id: &str, telex_metadata: Option<&TelexMetadata>) -> Self {
+        Self {
+            message_id: Uuid::new_v4().to_string(),
+            role: "agent".to_string(),
+            parts: vec![MessagePart::Text {
+                kind: "text".to_string(),
+                text,
+            }],
+            task_id: Some(task_id.to_string()),
+            context_id: None,
+            kind: "message".to_string(),
+            telex_metadata: telex_metadata.cloned(),
+        }
+    }
+}
+
 impl A2AResponse {
     pub fn success(
         request_id: String,
@@ -127,41 +279,23 @@ impl A2AResponse {
             Some(val) => val,
             None => Uuid::new_v4().to_string(),
         };
-        let response_message = Message {
-            message_id: Uuid::new_v4().to_string(),
-            role: "agent".to_string(),
-            parts: vec![MessagePart::Text {
-                kind: "text".to_string(),
-                text: response_text,
-            }],
-            task_id: Some(task_id.clone()),
-            kind: "message".to_string(),
-            telex_metadata: match &request_message.telex_metadata {
-                Some(metadata) => Some(TelexMetadata {
-                    telex_user_id: match metadata.telex_user_id.clone() {
-                        Some(val) => Some(val),
-                        None => None,
-                    },
-                    telex_channel_id: match metadata.telex_channel_id.clone() {
-                        Some(val) => Some(val),
-                        None => None,
-                    },
-                    org_id: match metadata.org_id.clone() {
-                        Some(val) => Some(val),
-                        None => None,
-                    },
-                }),
-                None => None,
-            },
-        };
+        let context_id = request_message
+            .context_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let response_message = Message::agent(
+            response_text,
+            &task_id,
+            request_message.telex_metadata.as_ref(),
+        );
 
         Self {
             jsonrpc: "2.0".to_string(),
-            id: Some(request_id),
+            id: Some(RequestId::String(request_id)),
             result: Some(TaskResult {
                 kind: "task".to_string(),
                 id: task_id,
-                context_id: Uuid::new_v4().to_string(),
+                context_id,
                 status: TaskStatus {
                     state: "completed".to_string(),
                     timestamp: now,
@@ -173,17 +307,79 @@ impl A2AResponse {
             error: None,
         }
     }
+}
 
-    pub fn error(code: i32, message: String) -> Self {
+impl<T> A2AResponse<T> {
+    pub fn ok(request_id: RequestId, result: T) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
-            id: None,
-            error: Some(ErrorDetail {
-                code,
-                message,
-                data: None,
-            }),
+            id: Some(request_id),
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(request_id: Option<RequestId>, error: impl Into<ErrorDetail>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: request_id,
+            error: Some(error.into()),
             result: None,
         }
     }
 }
+
+impl A2AResponse<StreamEvent> {
+    pub fn status_update(
+        request_id: &RequestId,
+        task_id: &str,
+        context_id: &str,
+        state: &str,
+        text: String,
+        is_final: bool,
+    ) -> Self {
+        let event = TaskStatusUpdateEvent {
+            kind: "status-update".to_string(),
+            task_id: task_id.to_string(),
+            context_id: context_id.to_string(),
+            status: TaskStatus {
+                state: state.to_string(),
+                timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                message: Message::agent(text, task_id, None),
+            },
+            is_final,
+        };
+
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(request_id.clone()),
+            result: Some(StreamEvent::StatusUpdate(event)),
+            error: None,
+        }
+    }
+
+    pub fn artifact_update(
+        request_id: &RequestId,
+        task_id: &str,
+        context_id: &str,
+        artifact: Artifact,
+        append: bool,
+        last_chunk: bool,
+    ) -> Self {
+        let event = TaskArtifactUpdateEvent {
+            kind: "artifact-update".to_string(),
+            task_id: task_id.to_string(),
+            context_id: context_id.to_string(),
+            artifact,
+            append,
+            last_chunk,
+        };
+
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(request_id.clone()),
+            result: Some(StreamEvent::ArtifactUpdate(event)),
+            error: None,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+
+use crate::{config::settings::Config, models::a2a::SUPPORTED_METHODS};
+
+pub const PROTOCOL_VERSION: &str = "0.3.0";
+
+/// Responses carry Markdown text and a JSON data artifact.
+const OUTPUT_MODES: &[&str] = &["text/plain", "application/json"];
+
+/// Security scheme of the admin endpoints. A2A calls need no credentials,
+/// so it isn't listed under `security`.
+const ADMIN_SCHEME: &str = "adminToken";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AgentCard {
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: String,
+    pub name: String,
+    pub description: String,
+    pub url: String,
+    pub version: String,
+    #[serde(rename = "preferredTransport")]
+    pub preferred_transport: String,
+    pub capabilities: AgentCapabilities,
+    #[serde(rename = "supportedMethods")]
+    pub supported_methods: Vec<String>,
+    #[serde(rename = "defaultInputModes")]
+    pub default_input_modes: Vec<String>,
+    #[serde(rename = "defaultOutputModes")]
+    pub default_output_modes: Vec<String>,
+    pub skills: Vec<AgentSkill>,
+    #[serde(
+        rename = "securitySchemes",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub security_schemes: BTreeMap<String, Value>,
+    pub security: Vec<BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AgentCapabilities {
+    pub streaming: bool,
+    #[serde(rename = "pushNotifications")]
+    pub push_notifications: bool,
+    #[serde(rename = "stateTransitionHistory")]
+    pub state_transition_history: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AgentSkill {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub examples: Vec<String>,
+    #[serde(rename = "inputModes")]
+    pub input_modes: Vec<String>,
+    #[serde(rename = "outputModes")]
+    pub output_modes: Vec<String>,
+}
+
+impl AgentCard {
+    pub fn from_config(config: &Config) -> Self {
+        let base_url = config
+            .public_url
+            .clone()
+            .unwrap_or_else(|| format!("http://{}:{}", config.host, config.port));
+
+        Self {
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            name: "GitPulse".to_string(),
+            description: "Finds trending GitHub repositories from natural-language queries and sends scheduled digests.".to_string(),
+            url: format!("{}/trending", base_url.trim_end_matches('/')),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            preferred_transport: "JSONRPC".to_string(),
+            capabilities: AgentCapabilities {
+                streaming: true,
+                push_notifications: true,
+                state_transition_history: true,
+            },
+            supported_methods: SUPPORTED_METHODS
+                .iter()
+                .map(|method| method.to_string())
+                .collect(),
+            default_input_modes: vec!["text/plain".to_string()],
+            default_output_modes: output_modes(),
+            skills: vec![
+                AgentSkill {
+                    id: "trending-search".to_string(),
+                    name: "Trending search".to_string(),
+                    description: "Searches GitHub for trending repositories filtered by language, topic, timeframe and star count.".to_string(),
+                    tags: vec![
+                        "github".to_string(),
+                        "trending".to_string(),
+                        "search".to_string(),
+                    ],
+                    examples: vec![
+                        "What's trending in Rust?".to_string(),
+                        "Top 5 AI repos from last month".to_string(),
+                    ],
+                    input_modes: vec!["text/plain".to_string()],
+                    output_modes: output_modes(),
+                },
+                AgentSkill {
+                    id: "repo-details".to_string(),
+                    name: "Repository details".to_string(),
+                    description: "Describes a single repository or compares up to four side by side.".to_string(),
+                    tags: vec![
+                        "github".to_string(),
+                        "repository".to_string(),
+                        "compare".to_string(),
+                    ],
+                    examples: vec![
+                        "Tell me about tokio-rs/axum".to_string(),
+                        "Compare tokio-rs/axum vs actix/actix-web".to_string(),
+                    ],
+                    input_modes: vec!["text/plain".to_string()],
+                    output_modes: output_modes(),
+                },
+                AgentSkill {
+                    id: "scheduled-digests".to_string(),
+                    name: "Scheduled digests".to_string(),
+                    description: "Posts a daily digest and a weekly roundup of trending repositories to the configured webhook and to subscribed conversations.".to_string(),
+                    tags: vec![
+                        "github".to_string(),
+                        "digest".to_string(),
+                        "schedule".to_string(),
+                    ],
+                    examples: vec![
+                        "Subscribe to the daily digest".to_string(),
+                        "Unsubscribe".to_string(),
+                    ],
+                    input_modes: vec!["text/plain".to_string()],
+                    output_modes: output_modes(),
+                },
+            ],
+            security_schemes: config
+                .admin_token
+                .iter()
+                .filter(|t| !t.trim().is_empty())
+                .map(|_| {
+                    (
+                        ADMIN_SCHEME.to_string(),
+                        json!({
+                            "type": "http",
+                            "scheme": "bearer",
+                            "description": "ADMIN_TOKEN, required by POST /admin/prompts/reload only",
+                        }),
+                    )
+                })
+                .collect(),
+            security: vec![],
+        }
+    }
+}
+
+fn output_modes() -> Vec<String> {
+    OUTPUT_MODES.iter().map(|mode| mode.to_string()).collect()
+}
@@ -0,0 +1,126 @@
+/// Language aliases matched against single query tokens. Languages that are
+/// too ambiguous to pick out of free text (`d`, `r`, `v`) only resolve through
+/// `normalize::find_language`.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("rust", "rust"),
+    ("python", "python"),
+    ("py", "python"),
+    ("javascript", "javascript"),
+    ("js", "javascript"),
+    ("node", "javascript"),
+    ("node.js", "javascript"),
+    ("nodejs", "javascript"),
+    ("typescript", "typescript"),
+    ("ts", "typescript"),
+    ("go", "go"),
+    ("golang", "go"),
+    ("java", "java"),
+    ("kotlin", "kotlin"),
+    ("swift", "swift"),
+    ("ruby", "ruby"),
+    ("php", "php"),
+    ("c", "c"),
+    ("c++", "c++"),
+    ("cpp", "c++"),
+    ("c#", "c#"),
+    ("csharp", "c#"),
+    ("scala", "scala"),
+    ("haskell", "haskell"),
+    ("elixir", "elixir"),
+    ("erlang", "erlang"),
+    ("zig", "zig"),
+    ("dart", "dart"),
+    ("lua", "lua"),
+    ("julia", "julia"),
+    ("clojure", "clojure"),
+    ("ocaml", "ocaml"),
+    ("nim", "nim"),
+    ("shell", "shell"),
+    ("bash", "shell"),
+    ("solidity", "solidity"),
+];
+
+/// Aliases that are also everyday words. They only name a language when a
+/// neighbouring word says so ("go repos", "written in c", "only go").
+pub const AMBIGUOUS_LANGUAGES: &[&str] = &["go", "c"];
+
+/// Words before an ambiguous alias that mark it as a language.
+pub const LANGUAGE_CUES: &[&str] = &["in", "only", "just", "using"];
+
+/// Words after an ambiguous alias that mark it as a language.
+pub const LANGUAGE_CONTEXT: &[&str] = &[
+    "lang",
+    "language",
+    "code",
+    "repo",
+    "repos",
+    "repositories",
+    "project",
+    "projects",
+    "libraries",
+    "libs",
+    "tools",
+];
+
+/// Multi-word topics, matched on the query with hyphens turned into spaces.
+pub const TOPIC_PHRASES: &[(&str, &str)] = &[
+    ("natural language processing", "natural-language-processing"),
+    ("artificial intelligence", "artificial-intelligence"),
+    ("large language model", "llm"),
+    ("machine learning", "machine-learning"),
+    ("deep learning", "deep-learning"),
+    ("computer vision", "computer-vision"),
+    ("web framework", "web-framework"),
+    ("data science", "data-science"),
+    ("game engine", "game-engine"),
+    ("command line", "cli"),
+    ("self hosted", "self-hosted"),
+];
+
+/// Single-word topic aliases.
+pub const TOPICS: &[(&str, &str)] = &[
+    ("ai", "ai"),
+    ("ml", "machine-learning"),
+    ("nlp", "natural-language-processing"),
+    ("llm", "llm"),
+    ("agent", "agents"),
+    ("rag", "rag"),
+    ("chatbot", "chatbot"),
+    ("web3", "web3"),
+    ("blockchain", "blockchain"),
+    ("crypto", "cryptocurrency"),
+    ("cryptocurrency", "cryptocurrency"),
+    ("defi", "defi"),
+    ("biotech", "biotech"),
+    ("bioinformatics", "bioinformatics"),
+    ("quantum", "quantum-computing"),
+    ("devops", "devops"),
+    ("kubernetes", "kubernetes"),
+    ("k8s", "kubernetes"),
+    ("docker", "docker"),
+    ("security", "security"),
+    ("database", "database"),
+    ("gamedev", "game-development"),
+    ("game", "game"),
+    ("robotics", "robotics"),
+    ("iot", "iot"),
+    ("embedded", "embedded"),
+    ("compiler", "compiler"),
+    ("wasm", "webassembly"),
+    ("webassembly", "webassembly"),
+    ("frontend", "frontend"),
+    ("backend", "backend"),
+    ("cli", "cli"),
+    ("terminal", "terminal"),
+    ("api", "api"),
+    ("framework", "framework"),
+    ("mobile", "mobile"),
+    ("android", "android"),
+    ("ios", "ios"),
+    ("react", "react"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("nextjs", "nextjs"),
+    ("observability", "observability"),
+    ("networking", "networking"),
+];
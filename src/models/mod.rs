@@ -1,3 +1,7 @@
 pub mod a2a;
+pub mod agent_card;
+pub mod lexicon;
+pub mod normalize;
 pub mod query;
 pub mod repository;
+pub mod search_query;
@@ -0,0 +1,267 @@
+use std::{collections::HashMap, fs, io};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+pub const DEFAULT_TOPIC_SYNONYMS_PATH: &str = "topic_synonyms.json";
+
+const BUILT_IN_TOPIC_SYNONYMS: &str = include_str!("../../topic_synonyms.json");
+
+/// A GitHub language, after Linguist's `languages.yml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// Name as GitHub shows it, e.g. `C++`.
+    pub name: &'static str,
+    /// Form that can go into a search URL unescaped, e.g. `cpp`.
+    pub search_name: &'static str,
+    pub aliases: &'static [&'static str],
+}
+
+const fn language(
+    name: &'static str,
+    search_name: &'static str,
+    aliases: &'static [&'static str],
+) -> Language {
+    Language {
+        name,
+        search_name,
+        aliases,
+    }
+}
+
+pub const GITHUB_LANGUAGES: &[Language] = &[
+    language("Assembly", "assembly", &["asm", "nasm"]),
+    language("Astro", "astro", &[]),
+    language("C", "c", &[]),
+    language("C#", "csharp", &["c sharp", "dotnet", ".net"]),
+    language("C++", "cpp", &["c plus plus", "cplusplus"]),
+    language("Cairo", "cairo", &[]),
+    language("Clojure", "clojure", &["clj"]),
+    language("CMake", "cmake", &[]),
+    language("COBOL", "cobol", &[]),
+    language("CoffeeScript", "coffeescript", &["coffee"]),
+    language("Common Lisp", "common-lisp", &["lisp"]),
+    language("Crystal", "crystal", &[]),
+    language("CSS", "css", &[]),
+    language("Cuda", "cuda", &[]),
+    language("D", "d", &["dlang"]),
+    language("Dart", "dart", &["flutter"]),
+    language("Dockerfile", "dockerfile", &[]),
+    language("Elixir", "elixir", &[]),
+    language("Elm", "elm", &[]),
+    language("Emacs Lisp", "emacs-lisp", &["elisp"]),
+    language("Erlang", "erlang", &[]),
+    language("F#", "fsharp", &["f sharp"]),
+    language("Fortran", "fortran", &[]),
+    language("GDScript", "gdscript", &[]),
+    language("Gleam", "gleam", &[]),
+    language("GLSL", "glsl", &[]),
+    language("Go", "go", &["golang"]),
+    language("Groovy", "groovy", &[]),
+    language("Haskell", "haskell", &["hs"]),
+    language("HCL", "hcl", &["terraform"]),
+    language("HTML", "html", &[]),
+    language("Java", "java", &[]),
+    language(
+        "JavaScript",
+        "javascript",
+        &["js", "node", "nodejs", "node.js", "ecmascript"],
+    ),
+    language("Julia", "julia", &[]),
+    language(
+        "Jupyter Notebook",
+        "jupyter-notebook",
+        &["jupyter", "ipython notebook"],
+    ),
+    language("Kotlin", "kotlin", &["kt"]),
+    language("Lua", "lua", &[]),
+    language("Makefile", "makefile", &[]),
+    language("MATLAB", "matlab", &[]),
+    language("MDX", "mdx", &[]),
+    language("Mojo", "mojo", &[]),
+    language("Move", "move", &[]),
+    language("Nim", "nim", &[]),
+    language("Nix", "nix", &[]),
+    language(
+        "Objective-C",
+        "objective-c",
+        &["objc", "obj-c", "objectivec"],
+    ),
+    language("OCaml", "ocaml", &[]),
+    language("Odin", "odin", &[]),
+    language("Pascal", "pascal", &["delphi"]),
+    language("Perl", "perl", &[]),
+    language("PHP", "php", &[]),
+    language("PowerShell", "powershell", &["pwsh"]),
+    language("Prolog", "prolog", &[]),
+    language("PureScript", "purescript", &[]),
+    language("Python", "python", &["py", "python3"]),
+    language("R", "r", &["rlang"]),
+    language("Racket", "racket", &[]),
+    language("ReScript", "rescript", &[]),
+    language("Ruby", "ruby", &["rb"]),
+    language("Rust", "rust", &["rs"]),
+    language("Scala", "scala", &[]),
+    language("Scheme", "scheme", &[]),
+    language("SCSS", "scss", &["sass"]),
+    language("Shell", "shell", &["bash", "sh", "zsh"]),
+    language("Solidity", "solidity", &[]),
+    language("Svelte", "svelte", &[]),
+    language("Swift", "swift", &[]),
+    language("TeX", "tex", &["latex"]),
+    language("TypeScript", "typescript", &["ts"]),
+    language("Typst", "typst", &[]),
+    language("V", "v", &["vlang"]),
+    language("Vala", "vala", &[]),
+    language("Verilog", "verilog", &[]),
+    language("VHDL", "vhdl", &[]),
+    language("Vim Script", "vim-script", &["vimscript", "viml", "vim"]),
+    language("Vue", "vue", &[]),
+    language("WebAssembly", "webassembly", &["wasm", "wast"]),
+    language("Zig", "zig", &[]),
+];
+
+/// Looks a language up by name, search name or alias, ignoring case and
+/// treating spaces, hyphens and underscores alike.
+pub fn find_language(name: &str) -> Option<&'static Language> {
+    let key = slug(name);
+
+    if key.is_empty() {
+        return None;
+    }
+
+    GITHUB_LANGUAGES.iter().find(|language| {
+        slug(language.name) == key
+            || slug(language.search_name) == key
+            || language.aliases.iter().any(|alias| slug(alias) == key)
+    })
+}
+
+/// Maps a language name or alias to the name GitPulse searches with: the
+/// lowercased GitHub name, e.g. `c++` for `cpp` or `objective-c` for
+/// `Objective C`.
+pub fn canonical_language(name: &str) -> Option<String> {
+    find_language(name).map(|language| language.name.to_lowercase())
+}
+
+/// The `language:` qualifier value for a language, e.g. `csharp` for `c#`.
+/// Unknown languages are lowercased with spaces turned into hyphens.
+pub fn language_search_name(name: &str) -> String {
+    match find_language(name) {
+        Some(language) => language.search_name.to_string(),
+        None => slug(name),
+    }
+}
+
+fn slug(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TopicMapFile {
+    #[serde(default)]
+    synonyms: HashMap<String, String>,
+    #[serde(default)]
+    expansions: HashMap<String, Vec<String>>,
+}
+
+/// Topic synonyms (`ml` → `machine-learning`) and expansions (`ai` →
+/// `artificial-intelligence`, `llm`, `machine-learning`), read from a JSON
+/// file with `synonyms` and `expansions` objects.
+#[derive(Debug, Clone, Default)]
+pub struct TopicMap {
+    synonyms: HashMap<String, String>,
+    expansions: HashMap<String, Vec<String>>,
+}
+
+impl TopicMap {
+    pub fn built_in() -> Self {
+        Self::from_json(BUILT_IN_TOPIC_SYNONYMS).expect("invalid built-in topic synonyms")
+    }
+
+    /// Reads the map from `path`, or falls back to the built-in map when
+    /// there is no such file.
+    pub fn load(path: &str) -> Result<Self> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                tracing::warn!("{} not found, using the built-in topic synonyms", path);
+
+                return Ok(Self::built_in());
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read topic synonyms {}", path));
+            }
+        };
+
+        Self::from_json(&json).with_context(|| format!("Invalid topic synonyms in {}", path))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: TopicMapFile = serde_json::from_str(json)?;
+
+        Ok(Self {
+            synonyms: file
+                .synonyms
+                .into_iter()
+                .map(|(from, to)| (slug(&from), slug(&to)))
+                .collect(),
+            expansions: file
+                .expansions
+                .into_iter()
+                .map(|(topic, related)| {
+                    (
+                        slug(&topic),
+                        related.iter().map(|topic| slug(topic)).collect(),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// The GitHub topic slug for a topic, with synonyms applied.
+    pub fn canonical(&self, topic: &str) -> String {
+        let topic = slug(topic);
+
+        self.synonyms.get(&topic).cloned().unwrap_or(topic)
+    }
+
+    /// Canonical topics without duplicates, in their original order.
+    pub fn normalize(&self, topics: &[String]) -> Vec<String> {
+        let mut normalized = vec![];
+
+        for topic in topics {
+            let topic = self.canonical(topic);
+
+            if !topic.is_empty() && !normalized.contains(&topic) {
+                normalized.push(topic);
+            }
+        }
+
+        normalized
+    }
+
+    /// Canonical topics followed by what they expand to, without duplicates.
+    /// Expansions go through the synonyms too, so one naming an alias can't
+    /// add a topic that is already there.
+    pub fn expand(&self, topics: &[String]) -> Vec<String> {
+        let mut expanded = self.normalize(topics);
+
+        for topic in self.normalize(topics) {
+            for related in self.expansions.get(&topic).into_iter().flatten() {
+                let related = self.canonical(related);
+
+                if !related.is_empty() && !expanded.contains(&related) {
+                    expanded.push(related);
+                }
+            }
+        }
+
+        expanded
+    }
+}
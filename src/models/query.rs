@@ -1,6 +1,56 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::normalize::canonical_language;
+
+pub const MIN_CONFIDENCE: f32 = 0.5;
+
+pub const MAX_COUNT: usize = 20;
+
+pub const TIMEFRAMES: &[&str] = &["day", "week", "month", "quarter", "year"];
+
+const DEFAULT_CLARIFYING_QUESTION: &str = "I'm not sure what you're looking for. Which programming language, topic or time period should I search trending repositories for?";
+
+/// How search results are ranked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    /// Most stars overall among recently created repositories.
+    #[default]
+    Stars,
+    /// Most stars gained per day, from the snapshot store. Older
+    /// repositories qualify too.
+    Velocity,
+}
+
+impl SortBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stars => "stars",
+            Self::Velocity => "velocity",
+        }
+    }
+}
+
+impl FromStr for SortBy {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "stars" => Ok(Self::Stars),
+            "velocity" => Ok(Self::Velocity),
+            other => Err(anyhow!(
+                "Unknown sort '{}', expected stars or velocity",
+                other
+            )),
+        }
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct QueryParams {
     pub language: Option<String>,
     #[serde(default)]
@@ -19,6 +69,8 @@ pub struct QueryParams {
     pub pushed_after: Option<String>,
     #[serde(default)]
     pub has_specific_date: bool,
+    #[serde(default)]
+    pub sort: SortBy,
 }
 
 fn default_timeframe() -> String {
@@ -33,6 +85,10 @@ fn default_min_stars() -> u32 {
     10
 }
 
+fn default_confidence() -> f32 {
+    1.0
+}
+
 impl Default for QueryParams {
     fn default() -> Self {
         Self {
@@ -45,6 +101,7 @@ impl Default for QueryParams {
             created_after: None,
             pushed_after: None,
             has_specific_date: false,
+            sort: SortBy::default(),
         }
     }
 }
@@ -53,4 +110,115 @@ impl QueryParams {
     pub fn uses_specific_dates(&self) -> bool {
         self.created_after.is_some() || self.pushed_after.is_some()
     }
+
+    /// Normalizes and clamps values in place. Returns the problems that
+    /// can't be fixed up, such as an unknown language or timeframe.
+    pub fn validate(&mut self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        self.language = match self.language.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(language) => match canonical_language(language) {
+                Some(language) => Some(language),
+                None => {
+                    errors.push(format!("language '{}' is not a known language", language));
+                    None
+                }
+            },
+        };
+
+        let mut topics: Vec<String> = vec![];
+        for topic in &self.topics {
+            let topic = topic.trim().to_lowercase().replace(' ', "-");
+
+            if !topic.is_empty() && !topics.contains(&topic) {
+                topics.push(topic);
+            }
+        }
+        self.topics = topics;
+
+        self.timeframe = self.timeframe.trim().to_lowercase();
+        if !TIMEFRAMES.contains(&self.timeframe.as_str()) {
+            errors.push(format!(
+                "timeframe '{}' must be one of {}",
+                self.timeframe,
+                TIMEFRAMES.join(", ")
+            ));
+        }
+
+        self.count = self.count.clamp(1, MAX_COUNT);
+
+        if self
+            .date_string
+            .as_deref()
+            .is_none_or(|date| date.trim().is_empty())
+        {
+            self.date_string = None;
+            self.has_specific_date = false;
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FollowUp {
+    #[serde(flatten)]
+    pub params: QueryParams,
+    #[serde(default)]
+    pub more_results: bool,
+    #[serde(skip)]
+    pub parsed_by: Option<String>,
+}
+
+impl FollowUp {
+    pub fn validate(&mut self) -> Result<(), Vec<String>> {
+        self.params.validate()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParsedQuery {
+    #[serde(flatten)]
+    pub params: QueryParams,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub clarifying_question: Option<String>,
+    #[serde(skip)]
+    pub parsed_by: Option<String>,
+}
+
+impl ParsedQuery {
+    pub fn ambiguous() -> Self {
+        Self {
+            params: QueryParams::default(),
+            confidence: 0.0,
+            clarifying_question: None,
+            parsed_by: None,
+        }
+    }
+
+    pub fn validate(&mut self) -> Result<(), Vec<String>> {
+        self.confidence = self.confidence.clamp(0.0, 1.0);
+
+        self.params.validate()
+    }
+
+    pub fn clarification(&self) -> Option<String> {
+        if let Some(question) = &self.clarifying_question
+            && !question.trim().is_empty()
+        {
+            return Some(question.trim().to_string());
+        }
+
+        if self.confidence < MIN_CONFIDENCE {
+            return Some(DEFAULT_CLARIFYING_QUESTION.to_string());
+        }
+
+        None
+    }
 }
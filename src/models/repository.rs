@@ -1,4 +1,8 @@
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::query::QueryParams;
 
 #[derive(Debug, Deserialize)]
 pub struct SearchResponse {
@@ -27,18 +31,196 @@ pub struct Owner {
     pub login: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Deserialize)]
+pub struct GraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQlError {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQlSearchData {
+    pub search: GraphQlSearch,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQlSearch {
+    pub repository_count: u32,
+    pub nodes: Vec<GraphQlRepository>,
+}
+
+/// Stargazers the GraphQL search query selects per repository.
+pub const STARGAZERS_FETCHED: usize = 100;
+
+/// A repository as selected by the GraphQL search query in `GitHubClient`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQlRepository {
+    pub database_id: Option<u64>,
+    pub name_with_owner: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub stargazer_count: u32,
+    pub fork_count: u32,
+    pub primary_language: Option<Named>,
+    pub repository_topics: Nodes<TopicNode>,
+    pub license_info: Option<License>,
+    pub latest_release: Option<GraphQlRelease>,
+    pub issues: Count,
+    pub languages: Option<Languages>,
+    pub stargazers: Edges<Starred>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Named {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Nodes<T> {
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Edges<T> {
+    pub edges: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopicNode {
+    pub topic: Named,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct License {
+    pub spdx_id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQlRelease {
+    pub tag_name: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Count {
+    pub total_count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Languages {
+    pub total_size: u64,
+    pub edges: Vec<LanguageEdge>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LanguageEdge {
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Starred {
+    pub starred_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TrendingRepo {
+    #[serde(default)]
+    pub id: u64,
     pub name: String,
     pub description: String,
     pub url: String,
     pub language: String,
     pub stars: u32,
+    pub forks: u32,
+    pub topics: Vec<String>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blurb: Option<RepoBlurb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<RepoDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<StarVelocity>,
+}
+
+/// Fields only the GraphQL search backend fills in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RepoDetails {
+    /// SPDX id, or the license name when it has none.
+    pub license: Option<String>,
+    pub open_issues: u32,
+    pub latest_release: Option<Release>,
+    /// Percentage of the code in the primary language.
+    pub language_share: Option<f64>,
+    /// Stars in the last seven days, counted over the latest 100 stargazers.
+    pub stars_last_week: u32,
+    /// All 100 fell inside the window, so `stars_last_week` is a lower bound.
+    #[serde(default)]
+    pub stars_last_week_saturated: bool,
+}
+
+/// Stars gained between snapshots, see `SnapshotStore`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StarVelocity {
+    /// Between the two latest snapshots, per day.
+    pub stars_per_day: f64,
+    /// Since the latest snapshot at least a week old, or the oldest one.
+    pub stars_last_week: i64,
+    /// Over the same window as `stars_last_week`.
+    pub forks_last_week: i64,
+    /// Days between the oldest and the latest snapshot.
+    pub tracked_days: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Release {
+    pub tag: String,
+    pub published_at: Option<String>,
+}
+
+/// LLM-written summary of a repository, see `RepoEnricher`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RepoBlurb {
+    pub summary: String,
+    pub why_notable: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SearchResult {
+    pub queries: Vec<String>,
+    /// GitHub's match count. With several queries, the largest single count,
+    /// since their matches overlap.
+    pub total_count: u32,
+    pub repos: Vec<TrendingRepo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrendingData {
+    pub params: QueryParams,
+    /// LLM provider, `rules` or `cache`, whichever produced `params`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed_by: Option<String>,
+    #[serde(flatten)]
+    pub result: SearchResult,
 }
 
 impl From<Repository> for TrendingRepo {
     fn from(value: Repository) -> Self {
         Self {
+            id: value.id,
             name: value.full_name,
             description: value
                 .description
@@ -46,6 +228,75 @@ impl From<Repository> for TrendingRepo {
             url: value.html_url,
             language: value.language.unwrap_or_else(|| "Unknown".to_string()),
             stars: value.stargazers_count,
+            forks: value.forks_count,
+            topics: value.topics,
+            created_at: value.created_at,
+            blurb: None,
+            details: None,
+            velocity: None,
+        }
+    }
+}
+
+impl From<GraphQlRepository> for TrendingRepo {
+    fn from(value: GraphQlRepository) -> Self {
+        let week_ago = Utc::now() - TimeDelta::days(7);
+
+        let stars_last_week = value
+            .stargazers
+            .edges
+            .iter()
+            .filter_map(|star| DateTime::parse_from_rfc3339(&star.starred_at).ok())
+            .filter(|starred_at| *starred_at > week_ago)
+            .count();
+
+        let stars_last_week_saturated = stars_last_week >= STARGAZERS_FETCHED;
+
+        let language_share = value.languages.and_then(|languages| {
+            let primary = languages.edges.first()?;
+
+            (languages.total_size > 0).then(|| {
+                (primary.size as f64 * 1000.0 / languages.total_size as f64).round() / 10.0
+            })
+        });
+
+        let details = RepoDetails {
+            license: value
+                .license_info
+                .map(|license| license.spdx_id.unwrap_or(license.name)),
+            open_issues: value.issues.total_count,
+            latest_release: value.latest_release.map(|release| Release {
+                tag: release.tag_name,
+                published_at: release.published_at,
+            }),
+            language_share,
+            stars_last_week: stars_last_week as u32,
+            stars_last_week_saturated,
+        };
+
+        Self {
+            id: value.database_id.unwrap_or_default(),
+            name: value.name_with_owner,
+            description: value
+                .description
+                .unwrap_or_else(|| "No description".to_string()),
+            url: value.url,
+            language: value
+                .primary_language
+                .map(|language| language.name)
+                .unwrap_or_else(|| "Unknown".to_string()),
+            stars: value.stargazer_count,
+            forks: value.fork_count,
+            topics: value
+                .repository_topics
+                .nodes
+                .into_iter()
+                .map(|node| node.topic.name)
+                .collect(),
+            created_at: value.created_at,
+            blurb: None,
+            details: Some(details),
+            velocity: None,
         }
     }
 }
@@ -0,0 +1,336 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::anyhow;
+use chrono::NaiveDate;
+
+/// A comparison in a range qualifier, e.g. `>=100` or `2025-01-01..2025-02-01`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Range<T> {
+    Exactly(T),
+    Greater(T),
+    AtLeast(T),
+    Less(T),
+    AtMost(T),
+    Between(T, T),
+}
+
+impl<T: fmt::Display> fmt::Display for Range<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exactly(value) => write!(f, "{}", value),
+            Self::Greater(value) => write!(f, ">{}", value),
+            Self::AtLeast(value) => write!(f, ">={}", value),
+            Self::Less(value) => write!(f, "<{}", value),
+            Self::AtMost(value) => write!(f, "<={}", value),
+            Self::Between(from, to) => write!(f, "{}..{}", from, to),
+        }
+    }
+}
+
+/// How forks are treated. GitHub leaves them out unless asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkFilter {
+    Include,
+    Only,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Qualifier {
+    Language(String),
+    Topic(String),
+    Created(Range<NaiveDate>),
+    Pushed(Range<NaiveDate>),
+    Stars(Range<u32>),
+    User(String),
+    Org(String),
+    Archived(bool),
+    Fork(ForkFilter),
+    License(String),
+}
+
+impl fmt::Display for Qualifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Language(language) => write!(f, "language:{}", quote(language)),
+            Self::Topic(topic) => write!(f, "topic:{}", quote(topic)),
+            Self::Created(range) => write!(f, "created:{}", range),
+            Self::Pushed(range) => write!(f, "pushed:{}", range),
+            Self::Stars(range) => write!(f, "stars:{}", range),
+            Self::User(user) => write!(f, "user:{}", quote(user)),
+            Self::Org(org) => write!(f, "org:{}", quote(org)),
+            Self::Archived(archived) => write!(f, "archived:{}", archived),
+            Self::Fork(ForkFilter::Include) => write!(f, "fork:true"),
+            Self::Fork(ForkFilter::Only) => write!(f, "fork:only"),
+            Self::License(license) => write!(f, "license:{}", quote(license)),
+        }
+    }
+}
+
+/// One alternative in an `OR` group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    Keyword(String),
+    Qualifier(Qualifier),
+}
+
+impl fmt::Display for SearchTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keyword(keyword) => write!(f, "{}", quote(keyword)),
+            Self::Qualifier(qualifier) => write!(f, "{}", qualifier),
+        }
+    }
+}
+
+/// Most `OR` operators GitHub accepts in one search query.
+pub const MAX_OR_OPERATORS: usize = 5;
+
+/// How a topic is matched against repositories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TopicMatch {
+    /// Repositories tagged with the topic, via `topic:`.
+    #[default]
+    Topic,
+    /// Repositories that mention the topic in their name, description or
+    /// topics.
+    Keyword,
+    /// Either of the above.
+    Both,
+}
+
+impl TopicMatch {
+    /// The alternatives that match `topic` in an `OR` group.
+    pub fn terms(&self, topic: &str) -> Vec<SearchTerm> {
+        let qualifier = SearchTerm::Qualifier(Qualifier::Topic(topic.to_string()));
+        let keyword = SearchTerm::Keyword(topic.to_string());
+
+        match self {
+            Self::Topic => vec![qualifier],
+            Self::Keyword => vec![keyword],
+            Self::Both => vec![qualifier, keyword],
+        }
+    }
+
+    /// Adds `topic` to a query that must match every topic. A keyword
+    /// already covers tagged repositories, so `Both` requires a keyword.
+    pub fn require(&self, query: SearchQuery, topic: &str) -> SearchQuery {
+        match self {
+            Self::Topic => query.topic(topic),
+            Self::Keyword | Self::Both => query.keyword(topic),
+        }
+    }
+}
+
+impl FromStr for TopicMatch {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "topic" => Ok(Self::Topic),
+            "keyword" => Ok(Self::Keyword),
+            "both" => Ok(Self::Both),
+            other => Err(anyhow!(
+                "Unknown topic match '{}', expected topic, keyword or both",
+                other
+            )),
+        }
+    }
+}
+
+/// A GitHub repository search query. Keywords and qualifier values are
+/// quoted when they contain anything that GitHub would read as syntax, so
+/// text from a user or an LLM can't add qualifiers of its own. The query is
+/// URL-encoded by `url_params`, never spliced into a URL by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    any_of: Vec<SearchTerm>,
+    keywords: Vec<String>,
+    qualifiers: Vec<Qualifier>,
+}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a free-text keyword. Blank keywords are ignored.
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        let keyword = sanitize(keyword);
+
+        if !keyword.is_empty() && !self.keywords.contains(&keyword) {
+            self.keywords.push(keyword);
+        }
+
+        self
+    }
+
+    /// Adds a qualifier. Qualifiers with a blank value are ignored.
+    pub fn qualifier(mut self, qualifier: Qualifier) -> Self {
+        if let Some(qualifier) = sanitize_qualifier(qualifier)
+            && !self.qualifiers.contains(&qualifier)
+        {
+            self.qualifiers.push(qualifier);
+        }
+
+        self
+    }
+
+    /// Sets the `OR` group: the query matches any one of `terms`. GitHub
+    /// has no parentheses, so a query holds a single group, and terms past
+    /// `MAX_OR_OPERATORS + 1` are dropped.
+    pub fn any_of(mut self, terms: Vec<SearchTerm>) -> Self {
+        self.any_of.clear();
+
+        for term in terms {
+            let term = match term {
+                SearchTerm::Keyword(keyword) => {
+                    let keyword = sanitize(&keyword);
+                    (!keyword.is_empty()).then_some(SearchTerm::Keyword(keyword))
+                }
+                SearchTerm::Qualifier(qualifier) => {
+                    sanitize_qualifier(qualifier).map(SearchTerm::Qualifier)
+                }
+            };
+
+            if let Some(term) = term
+                && !self.any_of.contains(&term)
+                && self.any_of.len() <= MAX_OR_OPERATORS
+            {
+                self.any_of.push(term);
+            }
+        }
+
+        self
+    }
+
+    pub fn language(self, language: &str) -> Self {
+        self.qualifier(Qualifier::Language(language.to_string()))
+    }
+
+    pub fn topic(self, topic: &str) -> Self {
+        self.qualifier(Qualifier::Topic(topic.to_string()))
+    }
+
+    pub fn created(self, range: Range<NaiveDate>) -> Self {
+        self.qualifier(Qualifier::Created(range))
+    }
+
+    pub fn pushed(self, range: Range<NaiveDate>) -> Self {
+        self.qualifier(Qualifier::Pushed(range))
+    }
+
+    pub fn stars(self, range: Range<u32>) -> Self {
+        self.qualifier(Qualifier::Stars(range))
+    }
+
+    pub fn user(self, user: &str) -> Self {
+        self.qualifier(Qualifier::User(user.to_string()))
+    }
+
+    pub fn org(self, org: &str) -> Self {
+        self.qualifier(Qualifier::Org(org.to_string()))
+    }
+
+    pub fn archived(self, archived: bool) -> Self {
+        self.qualifier(Qualifier::Archived(archived))
+    }
+
+    pub fn fork(self, fork: ForkFilter) -> Self {
+        self.qualifier(Qualifier::Fork(fork))
+    }
+
+    pub fn license(self, license: &str) -> Self {
+        self.qualifier(Qualifier::License(license.to_string()))
+    }
+
+    pub fn alternatives(&self) -> &[SearchTerm] {
+        &self.any_of
+    }
+
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    pub fn qualifiers(&self) -> &[Qualifier] {
+        &self.qualifiers
+    }
+
+    /// Query string parameters for `/search/repositories`.
+    pub fn url_params(&self, per_page: usize) -> Vec<(&'static str, String)> {
+        vec![
+            ("q", self.to_string()),
+            ("sort", "stars".to_string()),
+            ("order", "desc".to_string()),
+            ("per_page", per_page.to_string()),
+        ]
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let any_of = self
+            .any_of
+            .iter()
+            .map(SearchTerm::to_string)
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        let parts = Some(any_of)
+            .filter(|any_of| !any_of.is_empty())
+            .into_iter()
+            .chain(self.keywords.iter().map(|keyword| quote(keyword)))
+            .chain(self.qualifiers.iter().map(Qualifier::to_string))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Sanitizes a qualifier's value, or returns `None` when it is blank.
+fn sanitize_qualifier(qualifier: Qualifier) -> Option<Qualifier> {
+    let qualifier = match qualifier {
+        Qualifier::Language(value) => Qualifier::Language(sanitize(&value)),
+        Qualifier::Topic(value) => Qualifier::Topic(sanitize(&value)),
+        Qualifier::User(value) => Qualifier::User(sanitize(&value)),
+        Qualifier::Org(value) => Qualifier::Org(sanitize(&value)),
+        Qualifier::License(value) => Qualifier::License(sanitize(&value)),
+        other => other,
+    };
+
+    let blank = match &qualifier {
+        Qualifier::Language(value)
+        | Qualifier::Topic(value)
+        | Qualifier::User(value)
+        | Qualifier::Org(value)
+        | Qualifier::License(value) => value.is_empty(),
+        _ => false,
+    };
+
+    (!blank).then_some(qualifier)
+}
+
+/// Drops quotes and control characters and collapses whitespace.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| *c != '"' && !c.is_control())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quotes a sanitized value unless it is a plain word GitHub won't treat
+/// as an operator or qualifier.
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_alphanumeric() || "._+#-".contains(c))
+        && !value.starts_with('-')
+        && !matches!(value, "AND" | "OR" | "NOT");
+
+    match plain {
+        true => value.to_string(),
+        false => format!("\"{}\"", value),
+    }
+}
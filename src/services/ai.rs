@@ -1,134 +1,406 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use anthropic_sdk::{Anthropic, ContentBlock, MessageCreateBuilder};
 use anyhow::{Context, Result};
-use google_ai_rs::Client;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
 
-use crate::{models::query::QueryParams, services::date_parser::DateParser};
+use crate::{
+    models::query::{FollowUp, MAX_COUNT, ParsedQuery, QueryParams, TIMEFRAMES},
+    services::{
+        circuit_breaker::CircuitBreaker,
+        date_parser::DateParser,
+        llm::{LlmProvider, RULES_PROVIDER},
+        prompts::PromptStore,
+        rule_parser::RuleParser,
+    },
+};
 
+pub const DEFAULT_LLM_TIMEOUT_SECS: u64 = 20;
+
+const BREAKER_THRESHOLD: u32 = 3;
+const BREAKER_COOLDOWN_SECS: u64 = 60;
+
+const REQUIRED_FIELDS: &[&str] = &[
+    "language",
+    "topics",
+    "timeframe",
+    "count",
+    "min_stars",
+    "has_specific_date",
+];
+
+struct ChainLink {
+    provider: Arc<dyn LlmProvider>,
+    breaker: CircuitBreaker,
+}
+
+/// LLM providers tried in order. Every call gets its own `timeout`, and a
+/// provider that keeps failing is skipped until its breaker cools down.
 #[derive(Clone)]
-enum LlmClient {
-    Claude(Arc<Anthropic>),
-    Gemini(Client),
+pub struct LlmChain {
+    links: Arc<Vec<ChainLink>>,
+    timeout: Duration,
+}
+
+impl LlmChain {
+    pub fn new(providers: Vec<Arc<dyn LlmProvider>>, timeout: Duration) -> Self {
+        let links = providers
+            .into_iter()
+            .map(|provider| ChainLink {
+                provider,
+                breaker: CircuitBreaker::new(
+                    BREAKER_THRESHOLD,
+                    Duration::from_secs(BREAKER_COOLDOWN_SECS),
+                ),
+            })
+            .collect();
+
+        Self {
+            links: Arc::new(links),
+            timeout,
+        }
+    }
+
+    /// The same providers behind breakers of their own, so failures of
+    /// another LLM task don't open the query parser's circuits.
+    pub fn fork(&self) -> Self {
+        Self::new(
+            self.links
+                .iter()
+                .map(|link| link.provider.clone())
+                .collect(),
+            self.timeout,
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Walks the chain and returns the first answer together with the name
+    /// of the provider that gave it.
+    pub async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: &Value,
+    ) -> Option<(String, String)> {
+        for link in self.links.iter() {
+            let name = link.provider.name();
+
+            if !link.breaker.allow() {
+                tracing::info!("Skipping LLM provider {}: circuit open", name);
+                continue;
+            }
+
+            let response = tokio::time::timeout(
+                self.timeout,
+                link.provider
+                    .complete(system_prompt, user_query, Some(schema)),
+            )
+            .await
+            .context("LLM call timed out")
+            .and_then(|response| response);
+
+            match response {
+                Ok(response_text) => {
+                    link.breaker.record_success();
+
+                    return Some((response_text, name.to_string()));
+                }
+                Err(e) => {
+                    tracing::warn!("LLM provider {} failed: {:#}", name, e);
+
+                    if link.breaker.record_failure() {
+                        tracing::warn!(
+                            "LLM provider {} opened its circuit after {} failures",
+                            name,
+                            BREAKER_THRESHOLD
+                        );
+                    }
+                }
+            }
+        }
+
+        if !self.links.is_empty() {
+            tracing::warn!("No LLM provider answered");
+        }
+
+        None
+    }
 }
 
 #[derive(Clone)]
 pub struct QueryParser {
-    client: LlmClient,
-    model: String,
-    system_prompt: String,
+    chain: LlmChain,
+    rules: RuleParser,
+    prompts: PromptStore,
 }
 
 impl QueryParser {
-    pub async fn new(
-        llm_provider: &str,
-        api_key: &str,
-        model: &str,
-        system_prompt: &str,
-    ) -> Result<Self> {
-        let client = if llm_provider == "anthropic" {
-            let anthropic = Arc::new(Anthropic::new(api_key)?);
-
-            LlmClient::Claude(anthropic)
-        } else if llm_provider == "gemini" {
-            let gemini = Client::new(api_key)
-                .await
-                .context("Failed to initialize Gemini client")?;
-
-            LlmClient::Gemini(gemini)
-        } else {
-            anyhow::bail!(
-                "Unknown provider: {}. Must be either 'claude' or 'gemini'",
-                model
-            );
+    pub fn new(provider: Arc<dyn LlmProvider>, system_prompt: &str) -> Self {
+        Self::with_chain(
+            vec![provider],
+            PromptStore::from_template(system_prompt, ""),
+            Duration::from_secs(DEFAULT_LLM_TIMEOUT_SECS),
+        )
+    }
+
+    /// Tries each provider in order, see [`LlmChain`].
+    pub fn with_chain(
+        providers: Vec<Arc<dyn LlmProvider>>,
+        prompts: PromptStore,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            chain: LlmChain::new(providers, timeout),
+            rules: RuleParser::new(),
+            prompts,
+        }
+    }
+
+    /// A parser that never calls an LLM and relies on `RuleParser` alone.
+    pub fn rule_based() -> Self {
+        Self::with_chain(
+            vec![],
+            PromptStore::built_in(),
+            Duration::from_secs(DEFAULT_LLM_TIMEOUT_SECS),
+        )
+    }
+
+    pub async fn parse(&self, user_query: &str) -> Result<ParsedQuery> {
+        let (parsed, parsed_by) = match self
+            .request::<ParsedQuery>(user_query, &query_schema())
+            .await
+        {
+            Some((Ok(parsed), provider)) => (parsed, provider),
+            Some((Err(errors), provider)) => {
+                tracing::warn!(
+                    "LLM response invalid after retry ({}), marking query as ambiguous",
+                    errors.join("; ")
+                );
+                (ParsedQuery::ambiguous(), provider)
+            }
+            None => (self.rules.parse(user_query), RULES_PROVIDER.to_string()),
         };
 
-        Ok(Self {
-            client,
-            model: model.to_string(),
-            system_prompt: system_prompt.to_string(),
+        Ok(ParsedQuery {
+            params: resolve_dates(parsed.params),
+            parsed_by: Some(parsed_by),
+            ..parsed
         })
     }
 
-    pub async fn parse(&self, user_query: &str) -> Result<QueryParams> {
-        let response_text = match &self.client {
-            LlmClient::Claude(anthropic_client) => {
-                let response = anthropic_client
-                    .messages()
-                    .create(
-                        MessageCreateBuilder::new(&self.model, 100)
-                            .system(&self.system_prompt)
-                            .user(user_query)
-                            .build(),
-                    )
-                    .await
-                    .context("Failed to call Anthropic API")?;
-
-                response
-                    .content
-                    .into_iter()
-                    .filter_map(|block| match block {
-                        ContentBlock::Text { text } => Some(text),
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-                    .join("")
+    pub async fn parse_follow_up(
+        &self,
+        user_query: &str,
+        previous: &QueryParams,
+    ) -> Result<FollowUp> {
+        let mut previous_params = previous.clone();
+        previous_params.created_after = None;
+        previous_params.pushed_after = None;
+
+        let prompt = format!(
+            "Previous parameters: {}\nFollow-up: \"{}\"",
+            serde_json::to_string(&previous_params)?,
+            user_query
+        );
+
+        let (follow_up, parsed_by) = match self
+            .request::<FollowUp>(&prompt, &follow_up_schema())
+            .await
+        {
+            Some((Ok(follow_up), provider)) => (follow_up, provider),
+            Some((Err(errors), provider)) => {
+                tracing::warn!(
+                    "LLM follow-up invalid after retry ({}), falling back to previous parameters",
+                    errors.join("; ")
+                );
+                (
+                    FollowUp {
+                        params: previous.clone(),
+                        more_results: false,
+                        parsed_by: None,
+                    },
+                    provider,
+                )
             }
-            LlmClient::Gemini(gemini_client) => {
-                let full_prompt = format!("{}\n\nQuery: \"{}\"", self.system_prompt, user_query);
+            None => (
+                self.rules.parse_follow_up(user_query, previous),
+                RULES_PROVIDER.to_string(),
+            ),
+        };
 
-                let model = gemini_client.generative_model(&self.model);
+        Ok(FollowUp {
+            params: resolve_dates(follow_up.params),
+            more_results: follow_up.more_results,
+            parsed_by: Some(parsed_by),
+        })
+    }
 
-                let response = model
-                    .generate_content(full_prompt)
-                    .await
-                    .context("Failed to call Gemini API")?;
+    /// Asks the LLM for a response and validates it. An invalid response is
+    /// sent back once together with the validation errors. Returns the
+    /// result with the name of the provider that answered, or `None` when
+    /// no provider answered, so the caller falls back to the rule-based
+    /// parser.
+    async fn request<T: LlmOutput>(
+        &self,
+        prompt: &str,
+        schema: &Value,
+    ) -> Option<(Result<T, Vec<String>>, String)> {
+        let (response_text, provider) = self.complete(prompt, schema).await?;
 
-                response.text()
-            }
+        let errors = match decode::<T>(&response_text) {
+            Ok(output) => return Some((Ok(output), provider)),
+            Err(errors) => errors,
         };
 
-        let params = self.parse_llm_response(&response_text)?;
+        tracing::warn!(
+            "LLM response from {} rejected ({}), asking for a repair",
+            provider,
+            errors.join("; ")
+        );
 
-        Ok(params)
+        let repair_prompt = format!(
+            "{}\n\nYour previous response was rejected:\n- {}\nPrevious response: {}\nReply with a corrected JSON object only.",
+            prompt,
+            errors.join("\n- "),
+            response_text.trim()
+        );
+
+        let (response_text, provider) = self.complete(&repair_prompt, schema).await?;
+
+        Some((decode(&response_text), provider))
     }
 
-    fn parse_llm_response(&self, response_text: &str) -> Result<QueryParams> {
-        let cleaned = response_text
-            .trim()
-            .trim_start_matches("```json")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim();
+    async fn complete(&self, user_query: &str, schema: &Value) -> Option<(String, String)> {
+        self.chain
+            .complete(&self.prompts.render(), user_query, schema)
+            .await
+    }
 
-        let mut params: QueryParams = match serde_json::from_str(cleaned) {
-            Ok(params) => params,
-            Err(e) => {
-                tracing::warn!("LLM response parsing failed: {e}, falling back to defaults");
-                QueryParams::default()
-            }
-        };
+    pub fn has_llm(&self) -> bool {
+        !self.chain.is_empty()
+    }
 
-        if params.has_specific_date {
-            if let Some(date_string) = &params.date_string {
-                tracing::info!("Date string: {}", date_string);
-
-                match DateParser::parse(&date_string) {
-                    Ok(date_range) => {
-                        params.created_after =
-                            Some(date_range.created_after.format("%Y-%m-%d").to_string());
-                        params.pushed_after =
-                            Some(date_range.pushed_after.format("%Y-%m-%d").to_string());
-                        params.timeframe =
-                            DateParser::calculate_timeframe_from_date(date_range.created_after);
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to parse date string: {e}");
-                    }
-                }
-            }
+    pub fn chain(&self) -> &LlmChain {
+        &self.chain
+    }
+
+    pub fn prompts(&self) -> &PromptStore {
+        &self.prompts
+    }
+}
+
+trait LlmOutput: DeserializeOwned {
+    fn validate(&mut self) -> Result<(), Vec<String>>;
+}
+
+impl LlmOutput for ParsedQuery {
+    fn validate(&mut self) -> Result<(), Vec<String>> {
+        ParsedQuery::validate(self)
+    }
+}
+
+impl LlmOutput for FollowUp {
+    fn validate(&mut self) -> Result<(), Vec<String>> {
+        FollowUp::validate(self)
+    }
+}
+
+fn decode<T: LlmOutput>(response_text: &str) -> Result<T, Vec<String>> {
+    let mut output = serde_json::from_str::<T>(clean_response(response_text))
+        .map_err(|e| vec![format!("response is not valid JSON for the schema: {}", e)])?;
+
+    output.validate()?;
+
+    Ok(output)
+}
+
+fn query_params_properties() -> Map<String, Value> {
+    let schema = json!({
+        "language": {
+            "type": ["string", "null"],
+            "description": "Programming language, lowercase"
+        },
+        "topics": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Lowercase, hyphenated topics"
+        },
+        "timeframe": { "type": "string", "enum": TIMEFRAMES },
+        "count": { "type": "integer", "minimum": 1, "maximum": MAX_COUNT },
+        "min_stars": { "type": "integer", "minimum": 0 },
+        "date_string": {
+            "type": ["string", "null"],
+            "description": "Raw date expression from the query"
+        },
+        "has_specific_date": { "type": "boolean" },
+        "sort": {
+            "type": "string",
+            "enum": ["stars", "velocity"],
+            "description": "velocity for repositories gaining stars right now"
         }
+    });
 
-        Ok(params)
+    match schema {
+        Value::Object(properties) => properties,
+        _ => Map::new(),
     }
 }
+
+pub fn query_schema() -> Value {
+    let mut properties = query_params_properties();
+    properties.insert(
+        "confidence".to_string(),
+        json!({ "type": "number", "minimum": 0, "maximum": 1 }),
+    );
+    properties.insert(
+        "clarifying_question".to_string(),
+        json!({ "type": ["string", "null"] }),
+    );
+
+    json!({ "type": "object", "properties": properties, "required": REQUIRED_FIELDS })
+}
+
+pub fn follow_up_schema() -> Value {
+    let mut properties = query_params_properties();
+    properties.insert("more_results".to_string(), json!({ "type": "boolean" }));
+
+    let mut required = REQUIRED_FIELDS.to_vec();
+    required.push("more_results");
+
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+pub(crate) fn clean_response(response_text: &str) -> &str {
+    response_text
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim()
+}
+
+pub(crate) fn resolve_dates(mut params: QueryParams) -> QueryParams {
+    if params.has_specific_date
+        && let Some(date_string) = &params.date_string
+    {
+        tracing::info!("Date string: {}", date_string);
+
+        match DateParser::parse(date_string) {
+            Ok(date_range) => {
+                params.created_after =
+                    Some(date_range.created_after.format("%Y-%m-%d").to_string());
+                params.pushed_after = Some(date_range.pushed_after.format("%Y-%m-%d").to_string());
+                params.timeframe =
+                    DateParser::calculate_timeframe_from_date(date_range.created_after);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to parse date string: {e}");
+            }
+        }
+    }
+
+    params
+}
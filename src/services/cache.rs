@@ -4,12 +4,17 @@ use std::{
 };
 
 use dashmap::DashMap;
+use reqwest::header::{ETAG, HeaderMap, LAST_MODIFIED};
 
-use crate::models::{query::QueryParams, repository::TrendingRepo};
+use crate::models::{query::QueryParams, repository::SearchResult};
+
+/// How long a search response's validators are kept without being
+/// revalidated.
+pub const DEFAULT_CONDITIONAL_TTL: u64 = 24 * 60 * 60;
 
 #[derive(Clone)]
 struct CachedValue {
-    repos: Option<Vec<TrendingRepo>>,
+    repos: Option<SearchResult>,
     params: Option<QueryParams>,
     cached_at: Instant,
 }
@@ -32,7 +37,7 @@ impl Cache {
         let mut sorted_topics = params.topics.clone();
         sorted_topics.sort();
         format!(
-            "{}:{}:{}:{}:{}:{}:{}:{}",
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}",
             params.language.as_deref().unwrap_or("None"),
             sorted_topics.join(","),
             params.timeframe,
@@ -41,6 +46,7 @@ impl Cache {
             params.date_string.as_deref().unwrap_or("None"),
             params.created_after.as_deref().unwrap_or("None"),
             params.pushed_after.as_deref().unwrap_or("None"),
+            params.sort.as_str(),
         )
     }
 
@@ -48,7 +54,7 @@ impl Cache {
         query.trim().to_lowercase()
     }
 
-    pub fn get_repo(&self, params: &QueryParams) -> Option<Vec<TrendingRepo>> {
+    pub fn get_repo(&self, params: &QueryParams) -> Option<SearchResult> {
         let key = Self::make_repo_key(params);
 
         if let Some(entry) = self.store.get(&key) {
@@ -94,7 +100,7 @@ impl Cache {
         None
     }
 
-    pub fn set(&self, query: Option<&str>, params: &QueryParams, repos: Option<Vec<TrendingRepo>>) {
+    pub fn set(&self, query: Option<&str>, params: &QueryParams, repos: Option<SearchResult>) {
         let (key, cached) = match repos {
             Some(val) => {
                 let key = Self::make_repo_key(params);
@@ -121,7 +127,7 @@ impl Cache {
 
                     (key, cached)
                 }
-                None => return (),
+                None => return,
             },
         };
 
@@ -134,3 +140,94 @@ impl Cache {
         tracing::info!("Cache cleared");
     }
 }
+
+/// A search result with the validators GitHub sent for it.
+#[derive(Debug, Clone)]
+pub struct ValidatedResult {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub result: SearchResult,
+    cached_at: Instant,
+}
+
+/// Search results keyed by request URL, kept so an expired result can be
+/// revalidated with `If-None-Match` / `If-Modified-Since`. A 304 costs no
+/// quota and needs no parsing.
+#[derive(Debug, Clone)]
+pub struct ConditionalCache {
+    store: Arc<DashMap<String, ValidatedResult>>,
+    ttl: Duration,
+}
+
+impl Default for ConditionalCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONDITIONAL_TTL)
+    }
+}
+
+impl ConditionalCache {
+    pub fn new(ttl_seconds: u64) -> Self {
+        Self {
+            store: Arc::new(DashMap::new()),
+            ttl: Duration::from_secs(ttl_seconds),
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Option<ValidatedResult> {
+        let entry = self.store.get(url)?;
+
+        if entry.cached_at.elapsed() < self.ttl {
+            return Some(entry.clone());
+        }
+
+        drop(entry);
+        self.store.remove(url);
+
+        None
+    }
+
+    /// Stores `result` with the validators in `headers`. Responses without
+    /// an `ETag` or `Last-Modified` can't be revalidated and aren't kept.
+    pub fn set(&self, url: &str, headers: &HeaderMap, result: &SearchResult) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+
+        if etag.is_none() && last_modified.is_none() {
+            return;
+        }
+
+        self.store.insert(
+            url.to_string(),
+            ValidatedResult {
+                etag,
+                last_modified,
+                result: result.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops results that outlived the TTL, so URLs that are never requested
+    /// again don't pile up. Returns how many were dropped.
+    pub fn evict_expired(&self) -> usize {
+        let before = self.store.len();
+
+        self.store
+            .retain(|_, entry| entry.cached_at.elapsed() < self.ttl);
+
+        before.saturating_sub(self.store.len())
+    }
+
+    /// Marks a result as fresh after GitHub confirmed it is unchanged.
+    pub fn touch(&self, url: &str) {
+        if let Some(mut entry) = self.store.get_mut(url) {
+            entry.cached_at = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Opens after `threshold` consecutive failures and rejects calls until the
+/// cooldown has passed. The first call after that is a trial and holds the
+/// others back: success closes the breaker again, another failure re-opens it
+/// straight away.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Once the cooldown has passed only the first caller gets through, as
+    /// the trial. The rest are rejected until it records its outcome, or for
+    /// another cooldown if it never does.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let Some(open_until) = state.open_until else {
+            return true;
+        };
+
+        let now = Instant::now();
+
+        if now < open_until {
+            return false;
+        }
+
+        state.open_until = Some(now + self.cooldown);
+
+        true
+    }
+
+    /// Whether calls are being rejected. Unlike `allow`, this never starts a
+    /// trial.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state
+            .open_until
+            .is_some_and(|open_until| Instant::now() < open_until)
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.consecutive_failures = 0;
+        state.open_until = None;
+    }
+
+    /// Returns `true` when this failure opened the breaker.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.consecutive_failures += 1;
+
+        if state.consecutive_failures < self.threshold {
+            return false;
+        }
+
+        state.open_until = Some(Instant::now() + self.cooldown);
+
+        true
+    }
+}
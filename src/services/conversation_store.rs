@@ -0,0 +1,110 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+
+use crate::models::{query::QueryParams, repository::TrendingRepo};
+
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub params: QueryParams,
+    pub shown: Vec<String>,
+    updated_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct ConversationStore {
+    conversations: Arc<DashMap<String, Conversation>>,
+    pending_queries: Arc<DashMap<String, String>>,
+    ttl: Duration,
+}
+
+impl ConversationStore {
+    pub fn new(ttl_seconds: u64) -> Self {
+        Self {
+            conversations: Arc::new(DashMap::new()),
+            pending_queries: Arc::new(DashMap::new()),
+            ttl: Duration::from_secs(ttl_seconds),
+        }
+    }
+
+    pub fn get(&self, context_id: &str) -> Option<Conversation> {
+        let entry = self.conversations.get(context_id)?;
+
+        if entry.updated_at.elapsed() < self.ttl {
+            return Some(entry.clone());
+        }
+
+        drop(entry);
+        self.conversations.remove(context_id);
+
+        tracing::info!("Conversation {} expired", context_id);
+
+        None
+    }
+
+    /// Drops conversations that outlived the TTL, so ones that are never
+    /// read again don't pile up. Returns how many were dropped.
+    pub fn evict_expired(&self) -> usize {
+        let before = self.conversations.len();
+
+        self.conversations
+            .retain(|_, conversation| conversation.updated_at.elapsed() < self.ttl);
+
+        before.saturating_sub(self.conversations.len())
+    }
+
+    pub fn record(
+        &self,
+        context_id: &str,
+        params: &QueryParams,
+        repos: &[TrendingRepo],
+        append: bool,
+    ) {
+        let names = repos.iter().map(|repo| repo.name.clone());
+
+        let mut conversation = self
+            .conversations
+            .entry(context_id.to_string())
+            .or_insert_with(|| Conversation {
+                params: params.clone(),
+                shown: vec![],
+                updated_at: Instant::now(),
+            });
+
+        if !append {
+            conversation.shown.clear();
+        }
+
+        conversation.shown.extend(names);
+        conversation.params = params.clone();
+        conversation.updated_at = Instant::now();
+
+        tracing::info!(
+            "Conversation {} updated ({} repos shown)",
+            context_id,
+            conversation.shown.len()
+        );
+    }
+
+    pub fn await_clarification(&self, task_id: &str, query: &str) {
+        self.pending_queries
+            .insert(task_id.to_string(), query.to_string());
+
+        tracing::info!("Task {} is waiting for clarification", task_id);
+    }
+
+    pub fn take_pending_query(&self, task_id: &str) -> Option<String> {
+        self.pending_queries.remove(task_id).map(|(_, query)| query)
+    }
+
+    /// Drops pending queries of tasks `keep` rejects, e.g. evicted ones.
+    /// Returns how many were dropped.
+    pub fn retain_pending(&self, keep: impl Fn(&str) -> bool) -> usize {
+        let before = self.pending_queries.len();
+        self.pending_queries.retain(|task_id, _| keep(task_id));
+        before.saturating_sub(self.pending_queries.len())
+    }
+}
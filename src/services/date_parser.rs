@@ -41,7 +41,7 @@ impl DateParser {
             val if val.contains("last") && val.contains("day") => {
                 // Extract number: "last 30 days"
                 let days = Self::extract_number_from_string(val).unwrap_or(7);
-                let date = (now - Duration::days(days as i64)).date_naive();
+                let date = (now - Duration::days(days)).date_naive();
                 Some(Self::create_range_from_date(date))
             }
             val if val.contains("last") && Self::is_weekday(val) => {
@@ -160,10 +160,10 @@ impl DateParser {
         // Look for numbers that could be days (1-31)
         let parts: Vec<&str> = date_str.split(|c: char| !c.is_numeric()).collect();
         for part in parts {
-            if let Ok(day) = part.parse::<u32>() {
-                if (1..=31).contains(&day) {
-                    return Some(day);
-                }
+            if let Ok(day) = part.parse::<u32>()
+                && (1..=31).contains(&day)
+            {
+                return Some(day);
             }
         }
         None
@@ -0,0 +1,184 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use futures::{StreamExt, stream};
+use serde_json::{Value, json};
+
+use crate::{
+    models::repository::{RepoBlurb, TrendingRepo},
+    services::{
+        ai::{LlmChain, clean_response},
+        github::GitHubClient,
+    },
+};
+
+const README_EXCERPT_CHARS: usize = 1500;
+
+/// Repositories described at once, each needing a README fetch and an LLM
+/// call.
+const ENRICH_CONCURRENCY: usize = 4;
+
+const BLURB_SYSTEM_PROMPT: &str = "You describe GitHub repositories for a trending digest. \
+Given a repository's metadata and README excerpt, reply with a JSON object with two fields: \
+\"summary\", one plain sentence saying what the project is, and \"why_notable\", one sentence \
+saying why it stands out or is trending. Avoid marketing language and do not repeat the star count.";
+
+#[derive(Clone)]
+struct CachedBlurb {
+    blurb: RepoBlurb,
+    cached_at: Instant,
+}
+
+/// Adds LLM-written "what it is / why it's notable" blurbs to search results.
+/// Blurbs are cached per repository id. `chain` should be a
+/// [`LlmChain::fork`] of the query parser's, so failing blurbs can't open
+/// the parser's circuits.
+#[derive(Clone)]
+pub struct RepoEnricher {
+    chain: LlmChain,
+    github_client: GitHubClient,
+    blurbs: Arc<DashMap<u64, CachedBlurb>>,
+    ttl: Duration,
+}
+
+impl RepoEnricher {
+    pub fn new(chain: LlmChain, github_client: GitHubClient, ttl_seconds: u64) -> Self {
+        Self {
+            chain,
+            github_client,
+            blurbs: Arc::new(DashMap::new()),
+            ttl: Duration::from_secs(ttl_seconds),
+        }
+    }
+
+    pub async fn enrich(&self, repos: &mut [TrendingRepo]) {
+        if self.chain.is_empty() {
+            return;
+        }
+
+        let blurbs = stream::iter(0..repos.len())
+            .map(|index| self.blurb(&repos[index]))
+            .buffered(ENRICH_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (repo, blurb) in repos.iter_mut().zip(blurbs) {
+            repo.blurb = blurb;
+        }
+    }
+
+    async fn blurb(&self, repo: &TrendingRepo) -> Option<RepoBlurb> {
+        if let Some(cached) = self.cached(repo.id) {
+            return Some(cached);
+        }
+
+        let readme = match self.github_client.fetch_readme(&repo.name).await {
+            Ok(readme) => readme,
+            Err(e) => {
+                tracing::warn!("Failed to fetch README of {}: {}", repo.name, e);
+                None
+            }
+        };
+
+        let prompt = describe_repo(repo, readme.as_deref());
+
+        let (response_text, provider) = self
+            .chain
+            .complete(BLURB_SYSTEM_PROMPT, &prompt, &blurb_schema())
+            .await?;
+
+        let blurb = match parse_blurb(&response_text) {
+            Some(blurb) => blurb,
+            None => {
+                tracing::warn!("Unusable blurb for {} from {}", repo.name, provider);
+                return None;
+            }
+        };
+
+        self.blurbs.insert(
+            repo.id,
+            CachedBlurb {
+                blurb: blurb.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+
+        Some(blurb)
+    }
+
+    fn cached(&self, repo_id: u64) -> Option<RepoBlurb> {
+        let entry = self.blurbs.get(&repo_id)?;
+
+        if entry.cached_at.elapsed() < self.ttl {
+            return Some(entry.blurb.clone());
+        }
+
+        drop(entry);
+        self.blurbs.remove(&repo_id);
+
+        None
+    }
+}
+
+/// Builds the metadata prompt for one repository.
+pub fn describe_repo(repo: &TrendingRepo, readme: Option<&str>) -> String {
+    let age = DateTime::parse_from_rfc3339(&repo.created_at)
+        .map(|created_at| {
+            let days = (Utc::now() - created_at.with_timezone(&Utc)).num_days();
+            format!("{} days", days.max(0))
+        })
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let mut prompt = format!(
+        "Repository: {}\nDescription: {}\nLanguage: {}\nTopics: {}\nAge: {}\nStars: {}\n",
+        repo.name,
+        repo.description,
+        repo.language,
+        match repo.topics.is_empty() {
+            true => "none".to_string(),
+            false => repo.topics.join(", "),
+        },
+        age,
+        repo.stars
+    );
+
+    if let Some(readme) = readme.map(str::trim).filter(|readme| !readme.is_empty()) {
+        let excerpt: String = readme.chars().take(README_EXCERPT_CHARS).collect();
+        prompt.push_str(&format!("README excerpt:\n{}\n", excerpt));
+    }
+
+    prompt
+}
+
+fn blurb_schema() -> Value {
+    json!({
+        "title": "record_blurb",
+        "description": "Record the summary of the repository",
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "why_notable": { "type": "string" }
+        },
+        "required": ["summary", "why_notable"]
+    })
+}
+
+fn parse_blurb(response_text: &str) -> Option<RepoBlurb> {
+    let blurb = serde_json::from_str::<RepoBlurb>(clean_response(response_text)).ok()?;
+
+    let summary = blurb.summary.trim();
+    let why_notable = blurb.why_notable.trim();
+
+    if summary.is_empty() || why_notable.is_empty() {
+        return None;
+    }
+
+    Some(RepoBlurb {
+        summary: summary.to_string(),
+        why_notable: why_notable.to_string(),
+    })
+}
@@ -0,0 +1,605 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    config::settings::Config,
+    models::query::QueryParams,
+    services::{
+        ai::{DEFAULT_LLM_TIMEOUT_SECS, QueryParser, resolve_dates},
+        llm::{LlmProvider, LlmSettings, build_chain},
+        prompts::{DEFAULT_EXAMPLES_PATH, DEFAULT_SYSTEM_PROMPT_PATH, PromptStore},
+    },
+};
+
+pub const DEFAULT_CORPUS_PATH: &str = "eval/queries.jsonl";
+
+/// One corpus entry: a query and the parameters it should parse to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub query: String,
+    pub expected: QueryParams,
+    /// Whether the parser should ask a clarifying question instead.
+    #[serde(default)]
+    pub ambiguous: bool,
+}
+
+/// Reads a JSON Lines corpus. Blank lines are skipped.
+pub fn load_corpus(path: &str) -> Result<Vec<EvalCase>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read corpus {}", path))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid eval case on line {} of {}", i + 1, path))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Language,
+    Topics,
+    Timeframe,
+    Count,
+    MinStars,
+    Dates,
+    Clarification,
+}
+
+impl Field {
+    const ALL: [Field; 7] = [
+        Field::Language,
+        Field::Topics,
+        Field::Timeframe,
+        Field::Count,
+        Field::MinStars,
+        Field::Dates,
+        Field::Clarification,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Language => "language",
+            Self::Topics => "topics",
+            Self::Timeframe => "timeframe",
+            Self::Count => "count",
+            Self::MinStars => "min_stars",
+            Self::Dates => "dates",
+            Self::Clarification => "clarification",
+        }
+    }
+
+    fn describe(&self, params: &QueryParams, ambiguous: bool) -> String {
+        match self {
+            Self::Language => params
+                .language
+                .clone()
+                .unwrap_or_else(|| "null".to_string()),
+            Self::Topics => {
+                let mut topics = params.topics.clone();
+                topics.sort();
+                format!("[{}]", topics.join(", "))
+            }
+            Self::Timeframe => params.timeframe.clone(),
+            Self::Count => params.count.to_string(),
+            Self::MinStars => params.min_stars.to_string(),
+            Self::Dates => match params.has_specific_date {
+                true => params
+                    .created_after
+                    .clone()
+                    .unwrap_or_else(|| "unresolved".to_string()),
+                false => "none".to_string(),
+            },
+            Self::Clarification => ambiguous.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldScore {
+    pub field: &'static str,
+    pub correct: usize,
+    pub total: usize,
+}
+
+impl FieldScore {
+    pub fn accuracy(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.correct as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseFailure {
+    pub query: String,
+    pub parsed_by: String,
+    pub mismatches: Vec<Mismatch>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencySummary {
+    pub mean_ms: u128,
+    pub p50_ms: u128,
+    pub p95_ms: u128,
+    pub max_ms: u128,
+}
+
+impl LatencySummary {
+    fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut samples = samples.to_vec();
+        samples.sort();
+
+        let percentile = |p: usize| samples[(samples.len() * p).div_ceil(100).max(1) - 1];
+
+        Self {
+            mean_ms: samples.iter().sum::<Duration>().as_millis() / samples.len() as u128,
+            p50_ms: percentile(50).as_millis(),
+            p95_ms: percentile(95).as_millis(),
+            max_ms: samples[samples.len() - 1].as_millis(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub cases: usize,
+    pub exact_matches: usize,
+    pub fields: Vec<FieldScore>,
+    pub latency: LatencySummary,
+    /// How many cases each backend answered, e.g. `gemini` or `rules`.
+    pub parsed_by: BTreeMap<String, usize>,
+    pub failures: Vec<CaseFailure>,
+}
+
+impl EvalReport {
+    pub fn exact_match_rate(&self) -> f64 {
+        match self.cases {
+            0 => 0.0,
+            cases => self.exact_matches as f64 / cases as f64,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldScore> {
+        self.fields.iter().find(|score| score.field == name)
+    }
+
+    /// Plain-text summary for the terminal.
+    pub fn render(&self) -> String {
+        let mut report = format!("Evaluated {} queries\n\n", self.cases);
+
+        report.push_str(&format!(
+            "{:<15} {:>9} {:>9}\n",
+            "field", "correct", "accuracy"
+        ));
+
+        for score in &self.fields {
+            report.push_str(&format!(
+                "{:<15} {:>4}/{:<4} {:>8.1}%\n",
+                score.field,
+                score.correct,
+                score.total,
+                score.accuracy() * 100.0
+            ));
+        }
+
+        report.push_str(&format!(
+            "{:<15} {:>4}/{:<4} {:>8.1}%\n\n",
+            "exact match",
+            self.exact_matches,
+            self.cases,
+            self.exact_match_rate() * 100.0
+        ));
+
+        report.push_str(&format!(
+            "latency: mean {}ms, p50 {}ms, p95 {}ms, max {}ms\n",
+            self.latency.mean_ms, self.latency.p50_ms, self.latency.p95_ms, self.latency.max_ms
+        ));
+
+        let parsed_by = self
+            .parsed_by
+            .iter()
+            .map(|(backend, count)| format!("{} {}", backend, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        report.push_str(&format!("parsed by: {}\n", parsed_by));
+
+        for failure in &self.failures {
+            report.push_str(&format!(
+                "\nFAIL \"{}\" ({})\n",
+                failure.query, failure.parsed_by
+            ));
+
+            for mismatch in &failure.mismatches {
+                report.push_str(&format!(
+                    "  {}: expected {}, got {}\n",
+                    mismatch.field, mismatch.expected, mismatch.actual
+                ));
+            }
+        }
+
+        report
+    }
+}
+
+/// Parses every case with `parser` and scores the result field by field.
+pub async fn run_eval(parser: &QueryParser, cases: &[EvalCase]) -> EvalReport {
+    let mut fields = Field::ALL
+        .iter()
+        .map(|field| FieldScore {
+            field: field.name(),
+            correct: 0,
+            total: 0,
+        })
+        .collect::<Vec<_>>();
+    let mut latencies = vec![];
+    let mut parsed_by = BTreeMap::new();
+    let mut failures = vec![];
+    let mut exact_matches = 0;
+
+    for case in cases {
+        let started = Instant::now();
+        let parsed = parser.parse(&case.query).await;
+        latencies.push(started.elapsed());
+
+        let (actual, actual_ambiguous, backend) = match parsed {
+            Ok(parsed) => (
+                parsed.params.clone(),
+                parsed.clarification().is_some(),
+                parsed.parsed_by.unwrap_or_else(|| "unknown".to_string()),
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to parse \"{}\": {:#}", case.query, e);
+                (QueryParams::default(), false, "error".to_string())
+            }
+        };
+
+        *parsed_by.entry(backend.clone()).or_insert(0) += 1;
+
+        let expected = normalize(case.expected.clone());
+        let actual = normalize(actual);
+
+        let mismatches = Field::ALL
+            .iter()
+            .zip(fields.iter_mut())
+            .filter_map(|(field, score)| {
+                let expected = field.describe(&expected, case.ambiguous);
+                let actual = field.describe(&actual, actual_ambiguous);
+
+                score.total += 1;
+
+                if expected == actual {
+                    score.correct += 1;
+                    return None;
+                }
+
+                Some(Mismatch {
+                    field: field.name(),
+                    expected,
+                    actual,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        match mismatches.is_empty() {
+            true => exact_matches += 1,
+            false => failures.push(CaseFailure {
+                query: case.query.clone(),
+                parsed_by: backend,
+                mismatches,
+            }),
+        }
+    }
+
+    EvalReport {
+        cases: cases.len(),
+        exact_matches,
+        fields,
+        latency: LatencySummary::from_samples(&latencies),
+        parsed_by,
+        failures,
+    }
+}
+
+fn normalize(mut params: QueryParams) -> QueryParams {
+    let _ = params.validate();
+    resolve_dates(params)
+}
+
+/// Replays recorded LLM responses keyed by the exact user query, so the
+/// harness runs without network access.
+pub struct RecordedProvider {
+    responses: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub query: String,
+    pub response: String,
+}
+
+impl RecordedProvider {
+    pub fn new(recordings: Vec<Recording>) -> Self {
+        Self {
+            responses: recordings
+                .into_iter()
+                .map(|recording| (recording.query, recording.response))
+                .collect(),
+        }
+    }
+
+    /// Reads recordings from a JSON Lines file.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recordings {}", path))?;
+
+        let recordings = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Invalid recording on line {} of {}", i + 1, path))
+            })
+            .collect::<Result<Vec<Recording>>>()?;
+
+        Ok(Self::new(recordings))
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RecordedProvider {
+    fn name(&self) -> &str {
+        "recorded"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        self.responses
+            .get(user_query)
+            .cloned()
+            .with_context(|| format!("No recorded response for \"{}\"", user_query))
+    }
+}
+
+/// Wraps a provider and keeps every query and response it sees, for
+/// replaying later with `RecordedProvider`.
+pub struct RecordingProvider {
+    inner: Arc<dyn LlmProvider>,
+    recordings: Arc<Mutex<Vec<Recording>>>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, recordings: Arc<Mutex<Vec<Recording>>>) -> Self {
+        Self { inner, recordings }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RecordingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String> {
+        let response = self
+            .inner
+            .complete(system_prompt, user_query, schema)
+            .await?;
+
+        self.recordings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Recording {
+                query: user_query.to_string(),
+                response: response.clone(),
+            });
+
+        Ok(response)
+    }
+}
+
+/// Writes recordings as JSON Lines.
+pub fn save_recordings(path: &str, recordings: &[Recording]) -> Result<()> {
+    let lines = recordings
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<serde_json::Result<Vec<_>>>()?;
+
+    fs::write(path, lines.join("\n") + "\n")
+        .with_context(|| format!("Failed to write recordings {}", path))
+}
+
+const USAGE: &str = "Usage: gitpulse eval [--corpus PATH] [--backend llm|rules|recorded] [--recordings PATH] [--record PATH] [--min-accuracy RATE] [--json]
+
+  --corpus PATH        JSON Lines corpus of {\"query\", \"expected\", \"ambiguous\"} cases (default: eval/queries.jsonl)
+  --backend NAME       llm: the configured LLM chain (default), rules: the rule-based parser,
+                       recorded: replay responses from --recordings
+  --recordings PATH    JSON Lines file of {\"query\", \"response\"} pairs for the recorded backend
+  --record PATH        save the LLM responses of this run for later replay
+  --min-accuracy RATE  exit with an error when the exact-match rate is below RATE (0-1)
+  --json               print the report as JSON";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalBackend {
+    Llm,
+    Rules,
+    Recorded,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    pub corpus: String,
+    pub backend: EvalBackend,
+    pub recordings: Option<String>,
+    pub record: Option<String>,
+    pub min_accuracy: Option<f64>,
+    pub json: bool,
+}
+
+impl EvalOptions {
+    /// Parses the arguments that follow `gitpulse eval`.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut options = Self {
+            corpus: DEFAULT_CORPUS_PATH.to_string(),
+            backend: EvalBackend::Llm,
+            recordings: None,
+            record: None,
+            min_accuracy: None,
+            json: false,
+        };
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .with_context(|| format!("{} needs a value\n\n{}", arg, USAGE))
+            };
+
+            match arg.as_str() {
+                "--corpus" => options.corpus = value()?,
+                "--backend" => {
+                    options.backend = match value()?.as_str() {
+                        "llm" => EvalBackend::Llm,
+                        "rules" => EvalBackend::Rules,
+                        "recorded" => EvalBackend::Recorded,
+                        other => anyhow::bail!("Unknown backend '{}'\n\n{}", other, USAGE),
+                    }
+                }
+                "--recordings" => options.recordings = Some(value()?),
+                "--record" => options.record = Some(value()?),
+                "--min-accuracy" => {
+                    let rate = value()?;
+                    options.min_accuracy = Some(
+                        rate.parse()
+                            .with_context(|| format!("Invalid --min-accuracy '{}'", rate))?,
+                    );
+                }
+                "--json" => options.json = true,
+                "-h" | "--help" => anyhow::bail!("{}", USAGE),
+                other => anyhow::bail!("Unknown argument '{}'\n\n{}", other, USAGE),
+            }
+        }
+
+        if options.backend == EvalBackend::Recorded && options.recordings.is_none() {
+            anyhow::bail!("The recorded backend needs --recordings\n\n{}", USAGE);
+        }
+
+        if options.record.is_some() && options.backend != EvalBackend::Llm {
+            anyhow::bail!("--record only works with the llm backend\n\n{}", USAGE);
+        }
+
+        Ok(options)
+    }
+}
+
+/// Runs `gitpulse eval` and prints the report. Returns `false` when the
+/// exact-match rate is below `--min-accuracy`.
+pub async fn run_command(args: &[String]) -> Result<bool> {
+    let options = EvalOptions::from_args(args)?;
+    let cases = load_corpus(&options.corpus)?;
+    let recordings = Arc::new(Mutex::new(vec![]));
+
+    let parser = match options.backend {
+        EvalBackend::Rules => QueryParser::rule_based(),
+        EvalBackend::Recorded => {
+            let path = options.recordings.as_deref().unwrap_or_default();
+
+            QueryParser::with_chain(
+                vec![Arc::new(RecordedProvider::from_file(path)?)],
+                PromptStore::built_in(),
+                Duration::from_secs(DEFAULT_LLM_TIMEOUT_SECS),
+            )
+        }
+        EvalBackend::Llm => {
+            let config = Config::load()?;
+            let settings = LlmSettings::chain_from_config(&config)?;
+
+            if settings.is_empty() {
+                anyhow::bail!(
+                    "No LLM provider is configured. Set LLM_PROVIDER and LLM_API_KEY, or use --backend rules"
+                );
+            }
+
+            let mut providers = build_chain(&settings).await?;
+
+            if options.record.is_some() {
+                providers = providers
+                    .into_iter()
+                    .map(|provider| {
+                        Arc::new(RecordingProvider::new(provider, recordings.clone()))
+                            as Arc<dyn LlmProvider>
+                    })
+                    .collect();
+            }
+
+            let prompts = PromptStore::load(
+                config
+                    .system_prompt_path
+                    .as_deref()
+                    .unwrap_or(DEFAULT_SYSTEM_PROMPT_PATH),
+                config
+                    .few_shot_examples_path
+                    .as_deref()
+                    .unwrap_or(DEFAULT_EXAMPLES_PATH),
+            )?;
+
+            QueryParser::with_chain(
+                providers,
+                prompts,
+                Duration::from_secs(config.llm_timeout_secs.unwrap_or(DEFAULT_LLM_TIMEOUT_SECS)),
+            )
+        }
+    };
+
+    let report = run_eval(&parser, &cases).await;
+
+    if let Some(path) = &options.record {
+        save_recordings(path, &recordings.lock().unwrap_or_else(|e| e.into_inner()))?;
+    }
+
+    match options.json {
+        true => println!("{}", serde_json::to_string_pretty(&report)?),
+        false => print!("{}", report.render()),
+    }
+
+    Ok(options
+        .min_accuracy
+        .is_none_or(|min_accuracy| report.exact_match_rate() >= min_accuracy))
+}
@@ -1,22 +1,114 @@
-use std::collections::HashSet;
+use std::{cmp::Ordering, collections::HashSet, str::FromStr};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use axum::http::{HeaderMap, HeaderValue};
-use futures::future::join_all;
-use reqwest::header::{ACCEPT, USER_AGENT};
+use chrono::{TimeDelta, Utc};
+use futures::{StreamExt, future::join_all, stream};
+use reqwest::{
+    RequestBuilder, Response, StatusCode, Url,
+    header::{ACCEPT, AUTHORIZATION, IF_MODIFIED_SINCE, IF_NONE_MATCH, USER_AGENT},
+};
 
 use crate::{
     models::{
-        query::QueryParams,
-        repository::{SearchResponse, TrendingRepo},
+        normalize::TopicMap,
+        query::{QueryParams, SortBy},
+        repository::{
+            GraphQlResponse, GraphQlSearchData, Repository, SearchResponse, SearchResult,
+            TrendingRepo,
+        },
+        search_query::{MAX_OR_OPERATORS, SearchQuery, TopicMatch},
+    },
+    services::{
+        cache::ConditionalCache,
+        github_app::GitHubAppAuth,
+        github_quota::{GitHubQuota, RateLimitResource, RetryPolicy, rate_limit_delay},
+        github_tokens::TokenPool,
+        snapshot_store::SnapshotStore,
     },
-    utils::helpers::build_base_query_parts,
+    utils::helpers::build_base_query,
 };
 
+/// Most search requests one topic query makes by default.
+pub const DEFAULT_FANOUT_BUDGET: usize = 3;
+
+/// How many repositories a velocity search ranks before keeping `count`.
+pub const VELOCITY_CANDIDATES: usize = 100;
+
+/// Repositories fetched at once when refreshing snapshots.
+const REFRESH_CONCURRENCY: usize = 8;
+
+/// Repository fields the GraphQL backend selects, see `RepoDetails`.
+const GRAPHQL_SEARCH: &str = r#"
+query ($query: String!, $first: Int!) {
+  search(query: $query, type: REPOSITORY, first: $first) {
+    repositoryCount
+    nodes {
+      ... on Repository {
+        databaseId
+        nameWithOwner
+        url
+        description
+        createdAt
+        stargazerCount
+        forkCount
+        primaryLanguage { name }
+        repositoryTopics(first: 20) { nodes { topic { name } } }
+        licenseInfo { spdxId name }
+        latestRelease { tagName publishedAt }
+        issues(states: OPEN) { totalCount }
+        languages(first: 1, orderBy: { field: SIZE, direction: DESC }) {
+          totalSize
+          edges { size }
+        }
+        stargazers(last: 100, orderBy: { field: STARRED_AT, direction: ASC }) {
+          edges { starredAt }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// Which GitHub API repository searches go to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchBackend {
+    /// `GET /search/repositories`.
+    #[default]
+    Rest,
+    /// `search(type: REPOSITORY)`, which also returns `RepoDetails` in the
+    /// same round-trip. Needs a token or GitHub App.
+    GraphQl,
+}
+
+impl FromStr for SearchBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "rest" => Ok(Self::Rest),
+            "graphql" => Ok(Self::GraphQl),
+            other => Err(anyhow!(
+                "Unknown GitHub search backend '{}', expected rest or graphql",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GitHubClient {
     client: reqwest::Client,
     search_url: String,
+    api_url: String,
+    topics: TopicMap,
+    topic_match: TopicMatch,
+    fanout_budget: usize,
+    backend: SearchBackend,
+    tokens: TokenPool,
+    retry_policy: RetryPolicy,
+    responses: ConditionalCache,
+    snapshots: Option<SnapshotStore>,
 }
 
 impl GitHubClient {
@@ -29,18 +121,6 @@ impl GitHubClient {
             HeaderValue::from_static("application/vnd.github.v3+json"),
         );
 
-        if let Some(token) = github_access_token {
-            let auth_value = format!("Bearer {}", token);
-
-            headers.insert(
-                "Authorization",
-                HeaderValue::from_str(&auth_value.as_str())
-                    .context("Invalid GitHub access token format")?,
-            );
-        } else {
-            tracing::warn!("No GitHub token provided - using unauthenticated requests");
-        }
-
         let client = reqwest::Client::builder()
             .default_headers(headers)
             .timeout(std::time::Duration::from_secs(7))
@@ -51,162 +131,539 @@ impl GitHubClient {
             .build()
             .context("Failed to build HTTP client")?;
 
+        let api_url = github_search_url
+            .strip_suffix("/search/repositories")
+            .unwrap_or("https://api.github.com")
+            .to_string();
+
         Ok(Self {
             client,
             search_url: github_search_url.to_string(),
+            api_url,
+            topics: TopicMap::built_in(),
+            topic_match: TopicMatch::default(),
+            fanout_budget: DEFAULT_FANOUT_BUDGET,
+            backend: SearchBackend::default(),
+            tokens: TokenPool::new(&github_access_token.into_iter().collect::<Vec<_>>())?,
+            retry_policy: RetryPolicy::default(),
+            responses: ConditionalCache::default(),
+            snapshots: None,
         })
     }
 
-    pub async fn search_with_params(&self, params: &QueryParams) -> Result<Vec<TrendingRepo>> {
-        let base_query_parts = build_base_query_parts(params);
+    /// Replaces the built-in topic synonyms and expansions.
+    pub fn with_topic_map(mut self, topics: TopicMap) -> Self {
+        self.topics = topics;
+        self
+    }
 
-        if !params.topics.is_empty() {
-            let all_topics_future = self.search_all_topics(&base_query_parts, params);
-            let single_topics_future = self.search_single_topic(&base_query_parts, params);
+    /// Spreads requests across several tokens, see `TokenPool`.
+    pub fn with_tokens<T: AsRef<str>>(mut self, tokens: &[T]) -> Result<Self> {
+        self.tokens = TokenPool::new(tokens)?;
+        Ok(self)
+    }
 
-            let (all_topics_result, single_topics_result) =
-                tokio::join!(all_topics_future, single_topics_future);
+    /// Authenticates as a GitHub App installation instead of with tokens.
+    pub fn with_app_auth(mut self, app: GitHubAppAuth) -> Self {
+        let app = app.with_api(self.client.clone(), &self.api_url);
 
-            if let Some(repos) = all_topics_result {
-                if !repos.is_empty() {
-                    tracing::info!("Found {} repos via all-topics search", repos.len());
-                    return Ok(repos);
-                }
-            }
+        self.tokens = TokenPool::app(app);
+        self
+    }
 
-            if let Some(repos) = single_topics_result {
-                if !repos.is_empty() {
-                    tracing::info!("Found {} repos via single-topics search", repos.len());
-                    return Ok(repos);
-                }
-            }
-        }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
-        let query = base_query_parts.join("+");
+    /// The rate-limit quota GitHub last reported.
+    pub fn quota(&self) -> GitHubQuota {
+        self.tokens.status()
+    }
 
-        tracing::info!("GitHub search query (no topics): {}", query);
+    /// Drops cached search responses that can no longer be revalidated, see
+    /// `ConditionalCache::evict_expired`.
+    pub fn evict_expired_responses(&self) -> usize {
+        self.responses.evict_expired()
+    }
 
-        self.search_repositories(&query, params.count).await
+    /// Sets how topics are matched, see `TopicMatch`.
+    pub fn with_topic_match(mut self, topic_match: TopicMatch) -> Self {
+        self.topic_match = topic_match;
+        self
     }
 
-    async fn search_all_topics(
-        &self,
-        base_query_parts: &[String],
-        params: &QueryParams,
-    ) -> Option<Vec<TrendingRepo>> {
-        let mut all_topics_parts = base_query_parts.to_vec();
+    pub fn with_search_backend(mut self, backend: SearchBackend) -> Self {
+        self.backend = backend;
+        self
+    }
 
-        for topic in &params.topics {
-            all_topics_parts.insert(0, topic.clone());
-        }
+    /// Records every search result in `snapshots` and enables
+    /// `SortBy::Velocity`.
+    pub fn with_snapshot_store(mut self, snapshots: SnapshotStore) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
 
-        let query = all_topics_parts.join("+");
+    pub fn tracks_snapshots(&self) -> bool {
+        self.snapshots.is_some()
+    }
 
-        tracing::info!("GitHub search query (all topics): {}", query);
+    /// Sets the most search requests one topic query may make.
+    pub fn with_fanout_budget(mut self, fanout_budget: usize) -> Self {
+        self.fanout_budget = fanout_budget.max(1);
+        self
+    }
 
-        match self.search_repositories(&query, params.count).await {
-            Ok(repos) if !repos.is_empty() => {
-                tracing::info!("Found {} repos with all topics", repos.len());
-                Some(repos)
-            }
-            Ok(_) => {
-                tracing::warn!("No results with all topics, trying individual topics");
-                None
+    pub async fn search_with_params(&self, params: &QueryParams) -> Result<SearchResult> {
+        let Some(snapshots) = &self.snapshots else {
+            if params.sort == SortBy::Velocity {
+                tracing::warn!("No snapshot store configured, ranking by stars instead");
             }
-            Err(e) => {
-                tracing::warn!(
-                    "Error searching with all topics: {}, trying individual topics",
-                    e
-                );
-                None
+
+            let params = QueryParams {
+                sort: SortBy::Stars,
+                ..params.clone()
+            };
+
+            return self.search_candidates(&params, params.count).await;
+        };
+
+        let limit = match params.sort {
+            SortBy::Stars => params.count,
+            SortBy::Velocity => VELOCITY_CANDIDATES,
+        };
+
+        let mut result = self.search_candidates(params, limit).await?;
+
+        let repos = result.repos.clone();
+
+        let velocities = snapshots
+            .run(move |store| {
+                store.record(&repos)?;
+
+                let names = repos
+                    .iter()
+                    .map(|repo| repo.name.as_str())
+                    .collect::<Vec<_>>();
+                store.velocities(&names)
+            })
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to update star snapshots: {}", e);
+                Default::default()
+            });
+
+        for repo in &mut result.repos {
+            repo.velocity = velocities.get(&repo.name).cloned();
+        }
+
+        if params.sort == SortBy::Velocity {
+            rank_by_velocity(&mut result.repos);
+            result.repos.truncate(params.count);
+        }
+
+        Ok(result)
+    }
+
+    /// Re-snapshots up to `limit` repositories tracked during the last
+    /// week, one core API request each. Returns how many were recorded.
+    pub async fn refresh_snapshots(&self, limit: usize) -> Result<usize> {
+        let Some(snapshots) = &self.snapshots else {
+            return Ok(0);
+        };
+
+        let since = Utc::now() - TimeDelta::days(7);
+        let names = snapshots
+            .run(move |store| store.tracked(since, limit))
+            .await?;
+
+        let repos = stream::iter(names.clone())
+            .map(|name| async move {
+                match self.fetch_repo(&name).await {
+                    Ok(repo) => repo,
+                    Err(e) => {
+                        tracing::warn!("Failed to refresh snapshot of {}: {}", name, e);
+                        None
+                    }
+                }
+            })
+            .buffer_unordered(REFRESH_CONCURRENCY)
+            .filter_map(|repo| async { repo })
+            .collect::<Vec<_>>()
+            .await;
+
+        let recorded = snapshots.run(move |store| store.record(&repos)).await?;
+
+        tracing::info!(
+            "Refreshed star snapshots of {} of {} tracked repos",
+            recorded,
+            names.len()
+        );
+
+        Ok(recorded)
+    }
+
+    async fn search_candidates(&self, params: &QueryParams, limit: usize) -> Result<SearchResult> {
+        let base_query = build_base_query(params);
+
+        if !params.topics.is_empty() {
+            let (all_topics, any_topic) = self.topic_queries(&base_query, &params.topics);
+
+            if let Some(result) = self
+                .search_topics(all_topics.as_ref(), &any_topic, limit)
+                .await
+            {
+                tracing::info!("Found {} repos via topic search", result.repos.len());
+                return Ok(result);
             }
         }
+
+        tracing::info!("GitHub search query (no topics): {}", base_query);
+
+        self.search_repositories(&base_query, limit).await
     }
 
-    async fn search_single_topic(
+    /// Splits a topic search into requests that fit the fan-out budget: one
+    /// that needs every topic, when there are several, and `OR` searches
+    /// over the topics and their expansions. Expansions that don't fit the
+    /// budget are dropped.
+    fn topic_queries(
         &self,
-        base_query_parts: &[String],
-        params: &QueryParams,
-    ) -> Option<Vec<TrendingRepo>> {
-        let search_futures = params
+        base_query: &SearchQuery,
+        topics: &[String],
+    ) -> (Option<SearchQuery>, Vec<SearchQuery>) {
+        let topics = self.topics.normalize(topics);
+
+        let all_topics = (topics.len() > 1 && self.fanout_budget > 1).then(|| {
+            topics.iter().fold(base_query.clone(), |query, topic| {
+                self.topic_match.require(query, topic)
+            })
+        });
+
+        let terms = self
             .topics
+            .expand(&topics)
             .iter()
-            .map(|topic| self.search_topic(base_query_parts, topic, params.count));
+            .flat_map(|topic| self.topic_match.terms(topic))
+            .collect::<Vec<_>>();
+
+        let any_topic = terms
+            .chunks(MAX_OR_OPERATORS + 1)
+            .take(self.fanout_budget - usize::from(all_topics.is_some()))
+            .map(|terms| base_query.clone().any_of(terms.to_vec()))
+            .collect();
+
+        (all_topics, any_topic)
+    }
 
-        let results = join_all(search_futures).await;
+    /// Runs the topic searches in parallel. Repositories with every topic
+    /// come first, then the rest by stars.
+    async fn search_topics(
+        &self,
+        all_topics: Option<&SearchQuery>,
+        any_topic: &[SearchQuery],
+        count: usize,
+    ) -> Option<SearchResult> {
+        let (all_topics_result, any_topic_results) = tokio::join!(
+            async {
+                match all_topics {
+                    Some(query) => self.search_logged("all topics", query, count).await,
+                    None => None,
+                }
+            },
+            join_all(
+                any_topic
+                    .iter()
+                    .map(|query| self.search_logged("any topic", query, count)),
+            )
+        );
 
-        let mut all_repos = Vec::new();
+        let mut queries = Vec::new();
+        // The queries overlap, so the largest single count is the only
+        // number that is not an overcount
+        let mut total_count = 0;
         let mut seen_names = HashSet::new();
+        let mut repos = Vec::new();
+        let mut others = Vec::new();
 
-        for result in results {
-            if let Some(repos) = result {
-                for repo in repos {
-                    if seen_names.insert(repo.name.clone()) {
-                        all_repos.push(repo);
-                    }
+        if let Some(result) = all_topics_result {
+            queries.extend(result.queries);
+            total_count = total_count.max(result.total_count);
+
+            for repo in result.repos {
+                if seen_names.insert(repo.name.clone()) {
+                    repos.push(repo);
                 }
             }
         }
 
-        if all_repos.is_empty() {
+        for result in any_topic_results.into_iter().flatten() {
+            queries.extend(result.queries);
+            total_count = total_count.max(result.total_count);
+            others.extend(result.repos);
+        }
+
+        others.sort_by_key(|repo| std::cmp::Reverse(repo.stars));
+
+        for repo in others {
+            if seen_names.insert(repo.name.clone()) {
+                repos.push(repo);
+            }
+        }
+
+        if repos.is_empty() {
             return None;
         }
 
-        all_repos.sort_by(|a, b| b.stars.cmp(&a.stars));
-        all_repos.truncate(params.count);
+        repos.truncate(count);
 
-        tracing::info!(
-            "Returning {} unique repos from individual topic searches",
-            all_repos.len()
-        );
-        Some(all_repos)
+        Some(SearchResult {
+            queries,
+            total_count,
+            repos,
+        })
     }
 
-    async fn search_topic(
+    async fn search_logged(
         &self,
-        base_query_parts: &[String],
-        topic: &str,
+        label: &str,
+        query: &SearchQuery,
         count: usize,
-    ) -> Option<Vec<TrendingRepo>> {
-        let mut single_topic_parts = base_query_parts.to_vec();
-        single_topic_parts.insert(0, topic.to_string());
-
-        let query = single_topic_parts.join("+");
+    ) -> Option<SearchResult> {
+        tracing::info!("GitHub search query ({}): {}", label, query);
 
-        tracing::info!("GitHub search query (topic: {}): {}", topic, query);
-
-        match self.search_repositories(&query, count).await {
-            Ok(repos) => {
-                tracing::info!("Found {} repos for topic '{}'", repos.len(), topic);
-                Some(repos)
+        match self.search_repositories(query, count).await {
+            Ok(result) => {
+                tracing::info!("Found {} repos for {}", result.repos.len(), label);
+                Some(result)
             }
             Err(e) => {
-                tracing::warn!("Failed to search for topic '{}': {}", topic, e);
+                tracing::warn!("Failed to search for {}: {}", label, e);
                 None
             }
         }
     }
 
-    async fn search_repositories(&self, query: &str, limit: usize) -> Result<Vec<TrendingRepo>> {
-        let url = format!(
-            "{}?q={}&sort=stars&order=desc&per_page={}",
-            self.search_url, query, limit
-        );
+    /// Sends a request with the healthiest token once its quota allows it,
+    /// retrying when GitHub rate-limits it or rejects the token. Other
+    /// errors are returned as responses.
+    async fn send(&self, resource: RateLimitResource, request: RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+        let mut switched = 0;
+        let mut refreshed = false;
+
+        loop {
+            let token = self.tokens.pick(resource)?;
+            let quota = token.quota();
+
+            if let Some(wait) = quota.acquire(resource) {
+                if wait > self.retry_policy.max_delay {
+                    anyhow::bail!(
+                        "GitHub {} quota is used up for another {}s",
+                        resource.name(),
+                        wait.as_secs()
+                    );
+                }
 
-        let response = self
+                tracing::warn!(
+                    "GitHub {} quota is low, waiting {}ms for the reset",
+                    resource.name(),
+                    wait.as_millis()
+                );
+
+                quota.record_delay();
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+
+            let mut attempt_request = request
+                .try_clone()
+                .context("GitHub request can't be retried")?;
+
+            if let Some(authorization) = token.authorization().await? {
+                attempt_request = attempt_request.header(AUTHORIZATION, authorization);
+            }
+
+            let response = attempt_request
+                .send()
+                .await
+                .context("Failed to send request to GitHub")?;
+
+            token.record_request();
+            quota.update(resource, response.headers());
+
+            let status = response.status();
+
+            if status == StatusCode::UNAUTHORIZED {
+                if !refreshed && token.refresh().await {
+                    refreshed = true;
+                    continue;
+                }
+
+                if self.tokens.revoke(token) {
+                    continue;
+                }
+            }
+
+            if status == StatusCode::NOT_MODIFIED {
+                quota.record_not_modified();
+            }
+
+            if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::FORBIDDEN {
+                return Ok(response);
+            }
+
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+
+            let Some(delay) =
+                rate_limit_delay(status, &headers, &body, attempt, &self.retry_policy)
+            else {
+                anyhow::bail!("GitHub API error ({}): {}", status, body);
+            };
+
+            // Another token with calls left beats waiting for this one.
+            quota.hold(resource, delay);
+
+            if switched < self.tokens.len() && self.tokens.has_other_headroom(token, resource) {
+                tracing::warn!(
+                    "GitHub rate-limited a {} request with {} ({}), switching tokens",
+                    resource.name(),
+                    token.label(),
+                    status
+                );
+
+                quota.record_retry();
+                switched += 1;
+                continue;
+            }
+
+            if attempt >= self.retry_policy.max_retries || delay > self.retry_policy.max_delay {
+                anyhow::bail!("GitHub API error ({}): {}", status, body);
+            }
+
+            tracing::warn!(
+                "GitHub rate-limited a {} request with {} ({}), retrying in {}ms",
+                resource.name(),
+                token.label(),
+                status,
+                delay.as_millis()
+            );
+
+            quota.record_retry();
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn search_repositories(&self, query: &SearchQuery, limit: usize) -> Result<SearchResult> {
+        match self.backend {
+            SearchBackend::Rest => self.search_rest(query, limit).await,
+            SearchBackend::GraphQl => self.search_graphql(query, limit).await,
+        }
+    }
+
+    async fn search_graphql(&self, query: &SearchQuery, limit: usize) -> Result<SearchResult> {
+        let body = serde_json::json!({
+            "query": GRAPHQL_SEARCH,
+            "variables": {
+                "query": format!("{} sort:stars-desc", query),
+                "first": limit.min(100),
+            },
+        });
+
+        let request = self
             .client
-            .get(&url)
-            .send()
+            .post(format!("{}/graphql", self.api_url))
+            .json(&body);
+
+        let response = self.send(RateLimitResource::GraphQl, request).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub GraphQL error ({}): {}", status, error_text);
+        }
+
+        let response: GraphQlResponse<GraphQlSearchData> = response
+            .json()
             .await
-            .context("Failed to send request to GitHub")?;
+            .context("Failed to parse GitHub GraphQL response")?;
+
+        let Some(data) = response.data else {
+            let errors = response
+                .errors
+                .into_iter()
+                .map(|error| error.message)
+                .collect::<Vec<_>>();
+
+            anyhow::bail!("GitHub GraphQL error: {}", errors.join("; "));
+        };
+
+        for error in &response.errors {
+            tracing::warn!("GitHub GraphQL returned partial results: {}", error.message);
+        }
+
+        tracing::info!(
+            "GitHub GraphQL returned {} total results, {} items",
+            data.search.repository_count,
+            data.search.nodes.len()
+        );
+
+        Ok(SearchResult {
+            queries: vec![query.to_string()],
+            total_count: data.search.repository_count,
+            repos: data
+                .search
+                .nodes
+                .into_iter()
+                .map(TrendingRepo::from)
+                .collect(),
+        })
+    }
+
+    async fn search_rest(&self, query: &SearchQuery, limit: usize) -> Result<SearchResult> {
+        let url = Url::parse_with_params(&self.search_url, query.url_params(limit))
+            .context("Invalid GitHub search URL")?;
+
+        let key = url.to_string();
+        let cached = self.responses.get(&key);
+
+        let mut request = self.client.get(url);
+
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = self.send(RateLimitResource::Search, request).await?;
 
         let status = response.status();
 
+        if status == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            tracing::info!("GitHub search not modified, reusing {}", key);
+
+            self.responses.touch(&key);
+
+            return Ok(cached.result);
+        }
+
         if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
             anyhow::bail!("GitHub API error ({}): {}", status, error_text);
         }
 
+        let headers = response.headers().clone();
+
         let response_text = response
             .text()
             .await
@@ -227,6 +684,88 @@ impl GitHubClient {
             .map(TrendingRepo::from)
             .collect();
 
-        Ok(trending_repos)
+        let result = SearchResult {
+            queries: vec![query.to_string()],
+            total_count: search_response.total_count,
+            repos: trending_repos,
+        };
+
+        self.responses.set(&key, &headers, &result);
+
+        Ok(result)
+    }
+
+    /// Returns the repository README as raw text, or `None` when the
+    /// repository has no README.
+    pub async fn fetch_readme(&self, full_name: &str) -> Result<Option<String>> {
+        let url = format!("{}/repos/{}/readme", self.api_url, full_name);
+
+        let request = self
+            .client
+            .get(&url)
+            .header(ACCEPT, "application/vnd.github.raw");
+
+        let response = self.send(RateLimitResource::Core, request).await?;
+
+        let status = response.status();
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            anyhow::bail!(
+                "GitHub API error ({}) fetching README of {}",
+                status,
+                full_name
+            );
+        }
+
+        let readme = response
+            .text()
+            .await
+            .context("Failed to read README body")?;
+
+        Ok(Some(readme))
     }
+
+    /// Looks up a single repository by full name, or `None` when it doesn't
+    /// exist.
+    pub async fn fetch_repo(&self, full_name: &str) -> Result<Option<TrendingRepo>> {
+        let url = format!("{}/repos/{}", self.api_url, full_name);
+
+        let response = self
+            .send(RateLimitResource::Core, self.client.get(&url))
+            .await?;
+
+        let status = response.status();
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            anyhow::bail!("GitHub API error ({}) fetching {}", status, full_name);
+        }
+
+        let repository: Repository = response
+            .json()
+            .await
+            .context("Failed to parse GitHub repository as JSON")?;
+
+        Ok(Some(TrendingRepo::from(repository)))
+    }
+}
+
+/// Fastest growing first. Repositories without two snapshots yet follow,
+/// by stars.
+fn rank_by_velocity(repos: &mut [TrendingRepo]) {
+    repos.sort_by(|a, b| {
+        let per_day = |repo: &TrendingRepo| repo.velocity.as_ref().map(|v| v.stars_per_day);
+
+        per_day(b)
+            .partial_cmp(&per_day(a))
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.stars.cmp(&a.stars))
+    });
 }
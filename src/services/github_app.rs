@@ -0,0 +1,183 @@
+use std::{fmt, fs, sync::Arc};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderValue};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+/// How long before expiry an installation token is replaced. GitHub issues
+/// them for an hour.
+const REFRESH_MARGIN: TimeDelta = TimeDelta::minutes(5);
+
+/// GitHub rejects app JWTs that live longer than ten minutes.
+const JWT_LIFETIME: TimeDelta = TimeDelta::minutes(9);
+
+#[derive(Serialize)]
+struct Claims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    token: String,
+    expires_at: String,
+}
+
+#[derive(Clone)]
+struct InstallationToken {
+    authorization: HeaderValue,
+    expires_at: DateTime<Utc>,
+}
+
+/// Authenticates as a GitHub App installation: signs an RS256 JWT with the
+/// app's private key and exchanges it for an installation access token,
+/// which is cached and replaced shortly before it expires. Clones share the
+/// cached token.
+#[derive(Clone)]
+pub struct GitHubAppAuth {
+    app_id: String,
+    installation_id: u64,
+    key: EncodingKey,
+    client: reqwest::Client,
+    api_url: String,
+    token: Arc<Mutex<Option<InstallationToken>>>,
+}
+
+impl fmt::Debug for GitHubAppAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitHubAppAuth")
+            .field("app_id", &self.app_id)
+            .field("installation_id", &self.installation_id)
+            .field("api_url", &self.api_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GitHubAppAuth {
+    /// `private_key` is the PEM file GitHub generates for the app.
+    pub fn new(app_id: &str, installation_id: u64, private_key: &[u8]) -> Result<Self> {
+        let key = EncodingKey::from_rsa_pem(private_key)
+            .context("Invalid GitHub App private key, expected an RSA PEM")?;
+
+        Ok(Self {
+            app_id: app_id.trim().to_string(),
+            installation_id,
+            key,
+            client: reqwest::Client::new(),
+            api_url: "https://api.github.com".to_string(),
+            token: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub fn from_key_file(app_id: &str, installation_id: u64, path: &str) -> Result<Self> {
+        let private_key = fs::read(path)
+            .with_context(|| format!("Failed to read GitHub App private key '{}'", path))?;
+
+        Self::new(app_id, installation_id, &private_key)
+    }
+
+    /// Exchanges tokens through `client` against `api_url` instead of
+    /// api.github.com.
+    pub fn with_api(mut self, client: reqwest::Client, api_url: &str) -> Self {
+        self.client = client;
+        self.api_url = api_url.to_string();
+        self
+    }
+
+    pub fn installation_id(&self) -> u64 {
+        self.installation_id
+    }
+
+    /// A JWT identifying the app, backdated a minute for clock drift.
+    pub fn jwt(&self) -> Result<String> {
+        let now = Utc::now();
+
+        let claims = Claims {
+            iat: (now - TimeDelta::minutes(1)).timestamp(),
+            exp: (now + JWT_LIFETIME).timestamp(),
+            iss: self.app_id.clone(),
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .context("Failed to sign GitHub App JWT")
+    }
+
+    /// The `Authorization` header for the installation, exchanging a new
+    /// token when there is none or it is about to expire.
+    pub async fn authorization(&self) -> Result<HeaderValue> {
+        let mut token = self.token.lock().await;
+
+        if let Some(token) = token.as_ref()
+            && token.expires_at - REFRESH_MARGIN > Utc::now()
+        {
+            return Ok(token.authorization.clone());
+        }
+
+        let fresh = self.exchange().await?;
+        let authorization = fresh.authorization.clone();
+
+        *token = Some(fresh);
+
+        Ok(authorization)
+    }
+
+    /// Drops the cached installation token, e.g. after GitHub rejected it.
+    pub async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+
+    async fn exchange(&self) -> Result<InstallationToken> {
+        let url = format!(
+            "{}/app/installations/{}/access_tokens",
+            self.api_url, self.installation_id
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header(AUTHORIZATION, format!("Bearer {}", self.jwt()?))
+            .header(ACCEPT, "application/vnd.github+json")
+            .send()
+            .await
+            .context("Failed to request a GitHub App installation token")?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            bail!(
+                "GitHub App installation token exchange failed ({}): {}",
+                status,
+                error_text
+            );
+        }
+
+        let token: AccessTokenResponse = response
+            .json()
+            .await
+            .context("Failed to parse GitHub App installation token")?;
+
+        let expires_at = DateTime::parse_from_rfc3339(&token.expires_at)
+            .context("Invalid GitHub App installation token expiry")?
+            .with_timezone(&Utc);
+
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token.token))
+            .context("Invalid GitHub App installation token format")?;
+        authorization.set_sensitive(true);
+
+        tracing::info!(
+            "GitHub App installation {} token refreshed, expires at {}",
+            self.installation_id,
+            expires_at
+        );
+
+        Ok(InstallationToken {
+            authorization,
+            expires_at,
+        })
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::services::github_tokens::TokenUsage;
+
+/// Calls left in a window at which GitPulse stops and waits for the reset.
+pub const DEFAULT_QUOTA_RESERVE: u32 = 1;
+
+/// GitHub rate-limits search separately from the rest of the REST API,
+/// and GraphQL separately from both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RateLimitResource {
+    Core,
+    Search,
+    GraphQl,
+}
+
+impl RateLimitResource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Core => "core",
+            Self::Search => "search",
+            Self::GraphQl => "graphql",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    /// Longest GitPulse waits for a retry or a quota reset before giving up.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter: between half and all of
+    /// `base_delay * 2^attempt`, capped at `max_delay`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotaStatus {
+    pub resource: String,
+    pub limit: u32,
+    pub remaining: u32,
+    /// RFC 3339 time the window resets.
+    pub reset_at: String,
+    pub resets_in_secs: u64,
+}
+
+/// GitHub rate-limit state as last reported by GitHub.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct GitHubQuota {
+    pub resources: Vec<QuotaStatus>,
+    /// Requests retried after a 429 or rate-limit 403.
+    pub retries: u64,
+    /// Requests held back until the quota reset.
+    pub delayed: u64,
+    /// Searches GitHub answered with 304 Not Modified, which cost no quota.
+    pub not_modified: u64,
+    /// Usage per configured token.
+    pub tokens: Vec<TokenUsage>,
+}
+
+impl GitHubQuota {
+    /// The quota in the Prometheus text format.
+    pub fn prometheus(&self) -> String {
+        let mut metrics = String::new();
+
+        let per_resource = |value: fn(&QuotaStatus) -> u64| {
+            self.resources
+                .iter()
+                .map(|status| {
+                    (
+                        format!("{{resource=\"{}\"}}", status.resource),
+                        value(status),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut metric = |name: &str, kind: &str, help: &str, values: Vec<(String, u64)>| {
+            metrics.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                name, help, name, kind
+            ));
+
+            for (labels, value) in values {
+                metrics.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        };
+
+        metric(
+            "gitpulse_github_quota_remaining",
+            "gauge",
+            "Requests left in the current GitHub rate-limit window.",
+            per_resource(|status| status.remaining.into()),
+        );
+        metric(
+            "gitpulse_github_quota_limit",
+            "gauge",
+            "Requests allowed per GitHub rate-limit window.",
+            per_resource(|status| status.limit.into()),
+        );
+        metric(
+            "gitpulse_github_quota_reset_seconds",
+            "gauge",
+            "Seconds until the GitHub rate-limit window resets.",
+            per_resource(|status| status.resets_in_secs),
+        );
+        metric(
+            "gitpulse_github_retries_total",
+            "counter",
+            "GitHub requests retried after being rate limited.",
+            vec![(String::new(), self.retries)],
+        );
+        metric(
+            "gitpulse_github_delayed_total",
+            "counter",
+            "GitHub requests held back until the quota reset.",
+            vec![(String::new(), self.delayed)],
+        );
+        metric(
+            "gitpulse_github_not_modified_total",
+            "counter",
+            "GitHub searches revalidated with a 304 Not Modified.",
+            vec![(String::new(), self.not_modified)],
+        );
+
+        let per_token = |value: fn(&TokenUsage) -> u64| {
+            self.tokens
+                .iter()
+                .map(|usage| (format!("{{token=\"{}\"}}", usage.token), value(usage)))
+                .collect::<Vec<_>>()
+        };
+
+        metric(
+            "gitpulse_github_token_requests_total",
+            "counter",
+            "GitHub requests sent with each token.",
+            per_token(|usage| usage.requests),
+        );
+        metric(
+            "gitpulse_github_token_revoked",
+            "gauge",
+            "1 when GitHub rejected the token and it left the rotation.",
+            per_token(|usage| usage.revoked.into()),
+        );
+        metric(
+            "gitpulse_github_token_quota_remaining",
+            "gauge",
+            "Requests left for each token in the current window.",
+            self.tokens
+                .iter()
+                .flat_map(|usage| {
+                    usage.resources.iter().map(|status| {
+                        (
+                            format!(
+                                "{{token=\"{}\",resource=\"{}\"}}",
+                                usage.token, status.resource
+                            ),
+                            status.remaining.into(),
+                        )
+                    })
+                })
+                .collect(),
+        );
+
+        metrics
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    limit: u32,
+    remaining: u32,
+    reset_at: DateTime<Utc>,
+}
+
+/// Tracks the quota from GitHub's `X-RateLimit-*` headers. Clones share
+/// state, so the scheduler and user requests draw on the same quota.
+#[derive(Debug, Clone)]
+pub struct QuotaTracker {
+    quotas: Arc<Mutex<BTreeMap<RateLimitResource, Quota>>>,
+    retries: Arc<AtomicU64>,
+    delayed: Arc<AtomicU64>,
+    not_modified: Arc<AtomicU64>,
+    reserve: u32,
+}
+
+impl Default for QuotaTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUOTA_RESERVE)
+    }
+}
+
+impl QuotaTracker {
+    pub fn new(reserve: u32) -> Self {
+        Self {
+            quotas: Arc::new(Mutex::new(BTreeMap::new())),
+            retries: Arc::new(AtomicU64::new(0)),
+            delayed: Arc::new(AtomicU64::new(0)),
+            not_modified: Arc::new(AtomicU64::new(0)),
+            reserve,
+        }
+    }
+
+    /// Takes one call from the quota, or returns how long to wait for the
+    /// reset when no more than the reserve is left.
+    pub fn acquire(&self, resource: RateLimitResource) -> Option<Duration> {
+        let mut quotas = self.quotas.lock().unwrap_or_else(|e| e.into_inner());
+
+        let quota = quotas.get_mut(&resource)?;
+        let now = Utc::now();
+
+        if quota.reset_at <= now {
+            return None;
+        }
+
+        if quota.remaining > self.reserve {
+            quota.remaining -= 1;
+            return None;
+        }
+
+        Some((quota.reset_at - now).to_std().unwrap_or_default())
+    }
+
+    /// Calls left above the reserve, or how long until the reset when
+    /// none are. Unknown or already reset quotas count as full.
+    pub fn headroom(&self, resource: RateLimitResource) -> Result<u32, Duration> {
+        let quotas = self.quotas.lock().unwrap_or_else(|e| e.into_inner());
+
+        let Some(quota) = quotas.get(&resource) else {
+            return Ok(u32::MAX);
+        };
+
+        let now = Utc::now();
+
+        if quota.reset_at <= now {
+            return Ok(u32::MAX);
+        }
+
+        match quota.remaining.checked_sub(self.reserve) {
+            Some(calls) if calls > 0 => Ok(calls),
+            _ => Err((quota.reset_at - now).to_std().unwrap_or_default()),
+        }
+    }
+
+    /// Records the quota a GitHub response reports.
+    pub fn update(&self, resource: RateLimitResource, headers: &HeaderMap) {
+        let (Some(limit), Some(remaining), Some(reset)) = (
+            header_number(headers, "x-ratelimit-limit"),
+            header_number(headers, "x-ratelimit-remaining"),
+            header_number(headers, "x-ratelimit-reset"),
+        ) else {
+            return;
+        };
+
+        let Some(reset_at) = DateTime::from_timestamp(reset, 0) else {
+            return;
+        };
+
+        if remaining <= i64::from(self.reserve) {
+            tracing::warn!(
+                "GitHub {} quota nearly used up: {} of {} left until {}",
+                resource.name(),
+                remaining,
+                limit,
+                reset_at
+            );
+        }
+
+        self.quotas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                resource,
+                Quota {
+                    limit: limit.clamp(0, u32::MAX.into()) as u32,
+                    remaining: remaining.clamp(0, u32::MAX.into()) as u32,
+                    reset_at,
+                },
+            );
+    }
+
+    /// Treats `resource` as used up for `wait`, after GitHub rate-limited a
+    /// request without the headers saying so.
+    pub fn hold(&self, resource: RateLimitResource, wait: Duration) {
+        let until = TimeDelta::from_std(wait)
+            .ok()
+            .and_then(|wait| Utc::now().checked_add_signed(wait))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        let mut quotas = self.quotas.lock().unwrap_or_else(|e| e.into_inner());
+
+        let quota = quotas.entry(resource).or_insert(Quota {
+            limit: 0,
+            remaining: 0,
+            reset_at: until,
+        });
+
+        if quota.remaining > self.reserve || quota.reset_at < until {
+            quota.reset_at = until;
+        }
+
+        quota.remaining = 0;
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_delay(&self) {
+        self.delayed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_not_modified(&self) {
+        self.not_modified.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> GitHubQuota {
+        let now = Utc::now();
+
+        let resources = self
+            .quotas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(resource, quota)| QuotaStatus {
+                resource: resource.name().to_string(),
+                limit: quota.limit,
+                remaining: quota.remaining,
+                reset_at: quota.reset_at.to_rfc3339(),
+                resets_in_secs: (quota.reset_at - now).num_seconds().max(0) as u64,
+            })
+            .collect();
+
+        GitHubQuota {
+            resources,
+            retries: self.retries.load(Ordering::Relaxed),
+            delayed: self.delayed.load(Ordering::Relaxed),
+            not_modified: self.not_modified.load(Ordering::Relaxed),
+            tokens: vec![],
+        }
+    }
+}
+
+/// How long to wait before retrying a response GitHub rate-limited, or
+/// `None` when the response isn't a rate limit. Uses `Retry-After`, then
+/// the quota reset time, then exponential backoff.
+pub fn rate_limit_delay(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &str,
+    attempt: u32,
+    policy: &RetryPolicy,
+) -> Option<Duration> {
+    let retry_after = header_number(headers, RETRY_AFTER.as_str());
+    let exhausted = header_number(headers, "x-ratelimit-remaining") == Some(0);
+
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && (retry_after.is_some() || exhausted || body.to_lowercase().contains("rate limit")));
+
+    if !rate_limited {
+        return None;
+    }
+
+    if let Some(seconds) = retry_after {
+        return Some(Duration::from_secs(seconds.max(0) as u64));
+    }
+
+    if exhausted
+        && let Some(reset_at) = header_number(headers, "x-ratelimit-reset")
+            .and_then(|reset| DateTime::from_timestamp(reset, 0))
+    {
+        return Some((reset_at - Utc::now()).to_std().unwrap_or_default());
+    }
+
+    Some(policy.backoff(attempt))
+}
+
+fn header_number(headers: &HeaderMap, name: &str) -> Option<i64> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
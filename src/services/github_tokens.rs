@@ -0,0 +1,287 @@
+use std::{
+    cmp::{Ordering as CmpOrdering, Reverse},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use chrono::{TimeDelta, Utc};
+use reqwest::header::HeaderValue;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::services::{
+    github_app::GitHubAppAuth,
+    github_quota::{
+        DEFAULT_QUOTA_RESERVE, GitHubQuota, QuotaStatus, QuotaTracker, RateLimitResource,
+    },
+};
+
+/// How long a token GitHub rejected stays out of rotation, in case the
+/// rejection was a blip.
+pub const REVOKE_COOLDOWN: TimeDelta = TimeDelta::minutes(15);
+
+/// How much one token has been used, as reported on `/health`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TokenUsage {
+    /// `token-1`, `token-2`, ... in configuration order, `app-<installation
+    /// id>` or `anonymous`.
+    pub token: String,
+    pub revoked: bool,
+    pub requests: u64,
+    pub retries: u64,
+    pub resources: Vec<QuotaStatus>,
+}
+
+#[derive(Debug)]
+enum Credential {
+    Anonymous,
+    Token(HeaderValue),
+    App(GitHubAppAuth),
+}
+
+/// One credential and the quota GitHub reported for it.
+#[derive(Debug)]
+pub struct PooledToken {
+    label: String,
+    credential: Credential,
+    quota: QuotaTracker,
+    /// Unix time the token rejoins the rotation, 0 while it's in it.
+    revoked_until: AtomicI64,
+    requests: AtomicU64,
+}
+
+impl PooledToken {
+    fn new(label: String, credential: Credential) -> Self {
+        Self {
+            label,
+            credential,
+            quota: QuotaTracker::new(DEFAULT_QUOTA_RESERVE),
+            revoked_until: AtomicI64::new(0),
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The `Authorization` header value, or `None` for anonymous requests.
+    pub async fn authorization(&self) -> Result<Option<HeaderValue>> {
+        match &self.credential {
+            Credential::Anonymous => Ok(None),
+            Credential::Token(authorization) => Ok(Some(authorization.clone())),
+            Credential::App(app) => app.authorization().await.map(Some),
+        }
+    }
+
+    pub fn quota(&self) -> &QuotaTracker {
+        &self.quota
+    }
+
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drops a cached GitHub App installation token so the next request
+    /// exchanges a new one. Returns `false` for other credentials.
+    pub async fn refresh(&self) -> bool {
+        match &self.credential {
+            Credential::App(app) => {
+                app.invalidate().await;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.revoked_until.load(Ordering::Relaxed) <= Utc::now().timestamp()
+    }
+
+    fn usage(&self) -> TokenUsage {
+        let quota = self.quota.status();
+
+        TokenUsage {
+            token: self.label.clone(),
+            revoked: !self.is_live(),
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: quota.retries,
+            resources: quota.resources,
+        }
+    }
+}
+
+/// The GitHub tokens requests are spread across. Each request goes out with
+/// the live token that has the most quota left, so a scheduler digest and a
+/// user search don't drain the same token. Without tokens every request is
+/// anonymous.
+#[derive(Debug, Clone)]
+pub struct TokenPool {
+    tokens: Arc<Vec<PooledToken>>,
+    /// Serializes revocations so two can't take out the last live tokens.
+    revocations: Arc<Mutex<()>>,
+}
+
+impl TokenPool {
+    pub fn new<T: AsRef<str>>(tokens: &[T]) -> Result<Self> {
+        let mut pooled = Vec::new();
+
+        for token in tokens.iter().map(|token| token.as_ref().trim()) {
+            if token.is_empty() {
+                continue;
+            }
+
+            let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token))
+                .context("Invalid GitHub access token format")?;
+            authorization.set_sensitive(true);
+
+            pooled.push(PooledToken::new(
+                format!("token-{}", pooled.len() + 1),
+                Credential::Token(authorization),
+            ));
+        }
+
+        if pooled.is_empty() {
+            pooled.push(PooledToken::new(
+                "anonymous".to_string(),
+                Credential::Anonymous,
+            ));
+        }
+
+        Ok(Self {
+            tokens: Arc::new(pooled),
+            revocations: Arc::default(),
+        })
+    }
+
+    /// A pool holding a single GitHub App installation.
+    pub fn app(app: GitHubAppAuth) -> Self {
+        let label = format!("app-{}", app.installation_id());
+
+        Self {
+            tokens: Arc::new(vec![PooledToken::new(label, Credential::App(app))]),
+            revocations: Arc::default(),
+        }
+    }
+
+    /// The live token with the most `resource` calls left. Tokens GitHub
+    /// hasn't reported on yet count as full, ties go to the least used and
+    /// then the first configured token, and when every token is held back
+    /// the one that resets first wins.
+    pub fn pick(&self, resource: RateLimitResource) -> Result<&PooledToken> {
+        self.tokens
+            .iter()
+            .rev()
+            .filter(|token| token.is_live())
+            .max_by(|a, b| {
+                compare_headroom(a.quota.headroom(resource), b.quota.headroom(resource)).then_with(
+                    || {
+                        Reverse(a.requests.load(Ordering::Relaxed))
+                            .cmp(&Reverse(b.requests.load(Ordering::Relaxed)))
+                    },
+                )
+            })
+            .map_or_else(|| bail!("Every GitHub token has been revoked"), Ok)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Whether a live token other than `token` has `resource` calls left.
+    pub fn has_other_headroom(&self, token: &PooledToken, resource: RateLimitResource) -> bool {
+        self.tokens.iter().any(|other| {
+            !std::ptr::eq(other, token) && other.is_live() && other.quota.headroom(resource).is_ok()
+        })
+    }
+
+    /// Takes `token` out of rotation for `REVOKE_COOLDOWN` after GitHub
+    /// rejected it. Anonymous access and the last live token are never
+    /// revoked, so one bad response can't cut off GitHub entirely.
+    pub fn revoke(&self, token: &PooledToken) -> bool {
+        if matches!(token.credential, Credential::Anonymous) {
+            return false;
+        }
+
+        let _guard = self.revocations.lock().unwrap_or_else(|e| e.into_inner());
+
+        let others_live = self
+            .tokens
+            .iter()
+            .any(|other| !std::ptr::eq(other, token) && other.is_live());
+
+        if !others_live {
+            tracing::error!("GitHub rejected {}, the last live token", token.label);
+            return false;
+        }
+
+        tracing::error!(
+            "GitHub rejected {}, taking it out of rotation for {} minutes",
+            token.label,
+            REVOKE_COOLDOWN.num_minutes()
+        );
+
+        token.revoked_until.store(
+            (Utc::now() + REVOKE_COOLDOWN).timestamp(),
+            Ordering::Relaxed,
+        );
+        true
+    }
+
+    /// The quota summed over live tokens, with a breakdown per token.
+    pub fn status(&self) -> GitHubQuota {
+        let mut total = GitHubQuota::default();
+
+        for token in self.tokens.iter() {
+            let quota = token.quota.status();
+
+            total.retries += quota.retries;
+            total.delayed += quota.delayed;
+            total.not_modified += quota.not_modified;
+
+            if token.is_live() {
+                for status in quota.resources {
+                    match total
+                        .resources
+                        .iter_mut()
+                        .find(|total| total.resource == status.resource)
+                    {
+                        Some(total) => {
+                            total.limit += status.limit;
+                            total.remaining += status.remaining;
+
+                            if status.resets_in_secs < total.resets_in_secs {
+                                total.reset_at = status.reset_at;
+                                total.resets_in_secs = status.resets_in_secs;
+                            }
+                        }
+                        None => total.resources.push(status),
+                    }
+                }
+            }
+
+            total.tokens.push(token.usage());
+        }
+
+        total
+    }
+}
+
+/// Orders headroom from `QuotaTracker::headroom`: any calls left beat a
+/// wait, more calls beat fewer, and a shorter wait beats a longer one.
+fn compare_headroom(a: Result<u32, Duration>, b: Result<u32, Duration>) -> CmpOrdering {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => CmpOrdering::Greater,
+        (Err(_), Ok(_)) => CmpOrdering::Less,
+        (Err(a), Err(b)) => b.cmp(&a),
+    }
+}
@@ -0,0 +1,300 @@
+use regex::Regex;
+
+use crate::{
+    models::{
+        lexicon::{LANGUAGES, TOPIC_PHRASES, TOPICS},
+        normalize::canonical_language,
+    },
+    services::subscription_store::DigestFrequency,
+};
+
+/// Most repositories a single comparison covers.
+pub const MAX_COMPARED_REPOS: usize = 4;
+
+/// Words that mark a message as a repository search even when it names no
+/// known language or topic.
+const SEARCH_WORDS: &[&str] = &[
+    "trending",
+    "popular",
+    "hot",
+    "top",
+    "best",
+    "new",
+    "newest",
+    "latest",
+    "fastest",
+    "growing",
+    "repo",
+    "repos",
+    "repository",
+    "repositories",
+    "project",
+    "projects",
+    "library",
+    "libraries",
+    "lib",
+    "libs",
+    "tool",
+    "tools",
+    "tooling",
+    "framework",
+    "frameworks",
+    "crate",
+    "crates",
+    "package",
+    "packages",
+    "app",
+    "apps",
+    "sdk",
+    "github",
+    "star",
+    "stars",
+    "starred",
+    "open-source",
+    "opensource",
+    "today",
+    "yesterday",
+    "week",
+    "month",
+    "quarter",
+    "year",
+    "daily",
+    "weekly",
+    "monthly",
+    "since",
+    "more",
+    "same",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smalltalk {
+    Greeting,
+    Thanks,
+    Farewell,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intent {
+    TrendingSearch,
+    /// Full name of the repository, e.g. `tokio-rs/axum`.
+    RepoDetails(String),
+    /// Two to `MAX_COMPARED_REPOS` repository full names.
+    Compare(Vec<String>),
+    Subscribe(DigestFrequency),
+    Unsubscribe,
+    Help,
+    Smalltalk(Smalltalk),
+    /// Nothing in the message looks like a repository search.
+    OffTopic,
+}
+
+impl Intent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TrendingSearch => "trending_search",
+            Self::RepoDetails(_) => "repo_details",
+            Self::Compare(_) => "compare",
+            Self::Subscribe(_) => "subscribe",
+            Self::Unsubscribe => "unsubscribe",
+            Self::Help => "help",
+            Self::Smalltalk(_) => "smalltalk",
+            Self::OffTopic => "off_topic",
+        }
+    }
+}
+
+/// Rule-based classifier that runs before query parsing, so requests that
+/// aren't trending searches never reach the LLM or the GitHub search API.
+#[derive(Clone)]
+pub struct IntentRouter {
+    repo_refs: Regex,
+    details: Regex,
+    compare: Regex,
+    subscribe: Regex,
+    unsubscribe: Regex,
+    weekly: Regex,
+    help: Regex,
+    greeting: Regex,
+    thanks: Regex,
+    farewell: Regex,
+}
+
+impl Default for IntentRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntentRouter {
+    pub fn new() -> Self {
+        let regex = |pattern: &str| Regex::new(pattern).expect("invalid intent router regex");
+
+        Self {
+            repo_refs: regex(
+                r"(?:https?://)?(?:www\.)?github\.com/([\w.-]+/[\w.-]+)|\b([A-Za-z0-9][A-Za-z0-9-]*/[A-Za-z0-9_.-]+)",
+            ),
+            details: regex(
+                r"\b(?:tell me about|(?:details?|info|information|stats) (?:for|on|of|about)|describe|look ?up)\b",
+            ),
+            compare: regex(r"\b(?:compare|comparison|vs|versus|difference between)\b"),
+            subscribe: regex(
+                r"\b(?:subscribe|sign me up|send me (?:the |a |your )?(?:daily|weekly)|notify me|keep me (?:posted|updated))\b",
+            ),
+            unsubscribe: regex(
+                r"\b(?:unsubscribe|stop sending|stop (?:the |my )?(?:daily |weekly )?(?:digests?|roundups?|notifications?|updates)|cancel (?:my )?subscription|opt out)\b",
+            ),
+            weekly: regex(r"\b(?:week|weekly|roundup)\b"),
+            help: regex(
+                r"\b(?:help|commands|usage|what can you do|what do you do|how do i use|how does (?:this|it) work|who are you|what are you)\b",
+            ),
+            greeting: regex(
+                r"^(?:(?:hi|hello|hey|hiya|howdy|yo|greetings|good (?:morning|afternoon|evening)|how are you(?: doing)?|how's it going|what's up|sup|there|gitpulse|all|everyone)[\s!.,?]*)+$",
+            ),
+            thanks: regex(
+                r"^(?:(?:thanks|thank you|thx|ty|cheers|great|awesome|nice|cool|perfect|ok|okay|got it|so much|a lot|gitpulse)[\s!.,?]*)+$",
+            ),
+            farewell: regex(
+                r"^(?:(?:bye|goodbye|see you|see ya|cya|later|good night|gitpulse)[\s!.,?]*)+$",
+            ),
+        }
+    }
+
+    pub fn classify(&self, user_text: &str) -> Intent {
+        let text = user_text.trim().to_lowercase();
+
+        let intent = self.classify_text(user_text, &text);
+
+        tracing::info!("Classified intent: {}", intent.name());
+
+        intent
+    }
+
+    fn classify_text(&self, user_text: &str, text: &str) -> Intent {
+        if self.unsubscribe.is_match(text) {
+            return Intent::Unsubscribe;
+        }
+
+        if self.subscribe.is_match(text) {
+            return match self.weekly.is_match(text) {
+                true => Intent::Subscribe(DigestFrequency::Weekly),
+                false => Intent::Subscribe(DigestFrequency::Daily),
+            };
+        }
+
+        let repos = self.repo_refs(user_text, text);
+
+        if repos.len() >= 2 {
+            return Intent::Compare(repos.into_iter().take(MAX_COMPARED_REPOS).collect());
+        }
+
+        if let Some(repo) = repos.into_iter().next() {
+            return Intent::RepoDetails(repo);
+        }
+
+        if self.greeting.is_match(text) {
+            return Intent::Smalltalk(Smalltalk::Greeting);
+        }
+
+        if self.thanks.is_match(text) {
+            return Intent::Smalltalk(Smalltalk::Thanks);
+        }
+
+        if self.farewell.is_match(text) {
+            return Intent::Smalltalk(Smalltalk::Farewell);
+        }
+
+        if has_search_signal(text) {
+            return Intent::TrendingSearch;
+        }
+
+        match self.help.is_match(text) {
+            true => Intent::Help,
+            false => Intent::OffTopic,
+        }
+    }
+
+    /// Repository full names mentioned in the message, in order and without
+    /// duplicates. github.com links always count; a bare `owner/name` only
+    /// counts when it is the whole message or follows a details or compare
+    /// verb, so "trending in ci/cd" stays a search.
+    fn repo_refs(&self, user_text: &str, text: &str) -> Vec<String> {
+        let mut repos: Vec<String> = vec![];
+
+        let explicit = self.details.is_match(text) || self.compare.is_match(text);
+
+        for captures in self.repo_refs.captures_iter(user_text) {
+            let found = match (captures.get(1), captures.get(2)) {
+                (Some(linked), _) => linked,
+                (None, Some(bare))
+                    if explicit
+                        || text.trim_end_matches(['?', '!', '.']).trim()
+                            == bare.as_str().to_lowercase() =>
+                {
+                    bare
+                }
+                _ => continue,
+            };
+
+            let repo = found
+                .as_str()
+                .trim_end_matches('.')
+                .trim_end_matches(".git")
+                .to_string();
+
+            let Some((owner, name)) = repo.split_once('/') else {
+                continue;
+            };
+
+            if !is_plausible_repo(owner, name) {
+                continue;
+            }
+
+            if is_lexicon_word(owner) && is_lexicon_word(name) {
+                continue;
+            }
+
+            if !repos.iter().any(|seen| seen.eq_ignore_ascii_case(&repo)) {
+                repos.push(repo);
+            }
+        }
+
+        repos
+    }
+}
+
+/// GitHub logins are at most 39 characters and don't end with a hyphen;
+/// repository names are at most 100.
+fn is_plausible_repo(owner: &str, name: &str) -> bool {
+    owner.len() <= 39
+        && !owner.ends_with('-')
+        && owner.chars().any(|c| c.is_ascii_alphabetic())
+        && !name.is_empty()
+        && name.len() <= 100
+        && name.chars().any(|c| c.is_ascii_alphanumeric())
+}
+
+fn has_search_signal(text: &str) -> bool {
+    if text.chars().any(|c| c.is_ascii_digit()) {
+        return true;
+    }
+
+    if TOPIC_PHRASES
+        .iter()
+        .any(|(phrase, _)| text.replace('-', " ").contains(phrase))
+    {
+        return true;
+    }
+
+    text.split(|c: char| c.is_whitespace() || ",;:!?()[]\"'/".contains(c))
+        .map(|token| token.trim_end_matches('.'))
+        .filter(|token| !token.is_empty())
+        .any(|token| SEARCH_WORDS.contains(&token) || is_lexicon_word(token))
+}
+
+fn is_lexicon_word(word: &str) -> bool {
+    let word = word.to_lowercase();
+
+    LANGUAGES.iter().any(|(alias, _)| *alias == word)
+        || TOPICS.iter().any(|(alias, _)| *alias == word)
+        || canonical_language(&word).is_some()
+}
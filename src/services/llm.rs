@@ -0,0 +1,514 @@
+use std::{sync::Arc, time::Duration};
+
+use anthropic_sdk::{
+    Anthropic, ContentBlock, MessageCreateBuilder,
+    types::tools::{Tool, ToolChoice, ToolInputSchema},
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use google_ai_rs::{Client, Schema, SchemaType};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::config::settings::Config;
+
+const MAX_TOKENS: u32 = 300;
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Name of the tool Claude is forced to call when a response schema is
+/// given, unless the schema has a `title`.
+const STRUCTURED_OUTPUT_TOOL: &str = "record_query";
+const STRUCTURED_OUTPUT_DESCRIPTION: &str = "Record the parameters extracted from the query";
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
+
+pub const SUPPORTED_PROVIDERS: &[&str] = &["anthropic", "gemini", "openai", "ollama"];
+
+/// Selects the offline rule-based parser instead of an LLM.
+pub const RULES_PROVIDER: &str = "rules";
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Sends one prompt and returns the raw text answer. When `schema` is
+    /// given, the provider's native structured-output mode is used so the
+    /// answer is a JSON object matching it.
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String>;
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmSettings {
+    pub provider: String,
+    pub api_key: String,
+    pub model: String,
+    pub base_url: Option<String>,
+    pub json_mode: bool,
+}
+
+impl LlmSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            provider: config.llm_provider.clone(),
+            api_key: config.llm_api_key.clone(),
+            model: config.llm_model.clone(),
+            base_url: config.llm_base_url.clone(),
+            json_mode: config.llm_json_mode.unwrap_or(true),
+        }
+    }
+
+    /// The primary provider followed by the `LLM_FALLBACKS` entries, in the
+    /// order they should be tried. Entries that can't be reached are dropped.
+    pub fn chain_from_config(config: &Config) -> Result<Vec<Self>> {
+        let mut chain = vec![Self::from_config(config)];
+
+        for entry in config
+            .llm_fallbacks
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            chain.push(Self::parse_fallback(entry, config)?);
+        }
+
+        Ok(chain
+            .into_iter()
+            .filter(|settings| {
+                let configured = settings.is_configured();
+
+                if !configured && settings.provider != RULES_PROVIDER {
+                    tracing::warn!(
+                        "Skipping LLM provider {} ({}): no API key configured",
+                        settings.provider,
+                        settings.model
+                    );
+                }
+
+                configured
+            })
+            .collect())
+    }
+
+    /// Parses a `provider:model` fallback entry, optionally followed by
+    /// `@base_url` for OpenAI-compatible servers. The API key comes from the
+    /// provider's own variable, or `LLM_API_KEY` for the primary provider.
+    pub fn parse_fallback(entry: &str, config: &Config) -> Result<Self> {
+        let (provider, rest) = entry.split_once(':').with_context(|| {
+            format!("Invalid LLM fallback '{}': expected provider:model", entry)
+        })?;
+
+        let (model, base_url) = match rest.split_once('@') {
+            Some((model, base_url)) => (model, Some(base_url.trim().to_string())),
+            None => (rest, None),
+        };
+
+        let provider = provider.trim().to_lowercase();
+
+        let api_key = match provider.as_str() {
+            "anthropic" => config.anthropic_api_key.clone(),
+            "gemini" => config.gemini_api_key.clone(),
+            "openai" => config.openai_api_key.clone(),
+            _ => None,
+        }
+        .or_else(|| {
+            (provider == config.llm_provider.to_lowercase()).then(|| config.llm_api_key.clone())
+        })
+        .unwrap_or_default();
+
+        Ok(Self {
+            provider,
+            api_key,
+            model: model.trim().to_string(),
+            base_url,
+            json_mode: config.llm_json_mode.unwrap_or(true),
+        })
+    }
+
+    /// Whether an LLM can be reached with these settings. Hosted providers
+    /// need an API key; local OpenAI-compatible servers don't.
+    pub fn is_configured(&self) -> bool {
+        match self.provider.to_lowercase().as_str() {
+            RULES_PROVIDER => false,
+            "ollama" => true,
+            "openai" if self.base_url.is_some() => true,
+            _ => !self.api_key.is_empty(),
+        }
+    }
+}
+
+pub async fn build_provider(settings: &LlmSettings) -> Result<Arc<dyn LlmProvider>> {
+    let provider: Arc<dyn LlmProvider> = match settings.provider.to_lowercase().as_str() {
+        "anthropic" => Arc::new(AnthropicProvider::new(&settings.api_key, &settings.model)?),
+        "gemini" => Arc::new(GeminiProvider::new(&settings.api_key, &settings.model).await?),
+        "openai" => Arc::new(OpenAiProvider::new(
+            settings.base_url.as_deref().unwrap_or(OPENAI_BASE_URL),
+            &settings.api_key,
+            &settings.model,
+            settings.json_mode,
+        )?),
+        "ollama" => Arc::new(
+            OpenAiProvider::new(
+                settings.base_url.as_deref().unwrap_or(OLLAMA_BASE_URL),
+                &settings.api_key,
+                &settings.model,
+                settings.json_mode,
+            )?
+            .with_name("ollama"),
+        ),
+        other => anyhow::bail!(
+            "Unknown LLM provider: '{}'. Must be one of: {}",
+            other,
+            SUPPORTED_PROVIDERS.join(", ")
+        ),
+    };
+
+    tracing::info!(
+        "LLM provider initialized: {} ({})",
+        provider.name(),
+        settings.model
+    );
+
+    Ok(provider)
+}
+
+pub async fn build_chain(chain: &[LlmSettings]) -> Result<Vec<Arc<dyn LlmProvider>>> {
+    let mut providers = vec![];
+
+    for settings in chain {
+        providers.push(build_provider(settings).await?);
+    }
+
+    Ok(providers)
+}
+
+pub struct AnthropicProvider {
+    client: Anthropic,
+    model: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: &str, model: &str) -> Result<Self> {
+        let client = Anthropic::new(api_key).context("Failed to initialize Anthropic client")?;
+
+        Ok(Self {
+            client,
+            model: model.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String> {
+        let mut builder = MessageCreateBuilder::new(&self.model, MAX_TOKENS)
+            .system(system_prompt)
+            .user(user_query);
+
+        if let Some(schema) = schema {
+            builder = builder
+                .tools(vec![structured_output_tool(schema)])
+                .tool_choice(ToolChoice::tool(output_name(schema)));
+        }
+
+        let response = self
+            .client
+            .messages()
+            .create(builder.build())
+            .await
+            .context("Failed to call Anthropic API")?;
+
+        let text = response
+            .content
+            .into_iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text),
+                ContentBlock::ToolUse { input, .. } => Some(input.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok(text)
+    }
+}
+
+pub struct GeminiProvider {
+    client: Client,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub async fn new(api_key: &str, model: &str) -> Result<Self> {
+        let client = Client::new(api_key)
+            .await
+            .context("Failed to initialize Gemini client")?;
+
+        Ok(Self {
+            client,
+            model: model.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String> {
+        let full_prompt = format!("{}\n\nQuery: \"{}\"", system_prompt, user_query);
+
+        let mut model = self.client.generative_model(&self.model);
+
+        if let Some(schema) = schema {
+            model = model.with_response_schema(gemini_schema(schema));
+        }
+
+        let response = model
+            .generate_content(full_prompt)
+            .await
+            .context("Failed to call Gemini API")?;
+
+        Ok(response.text())
+    }
+}
+
+/// Talks to any server exposing the OpenAI chat completions API
+/// (OpenAI, vLLM, Ollama, LM Studio, ...).
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    name: String,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    json_mode: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+impl OpenAiProvider {
+    pub fn new(base_url: &str, api_key: &str, model: &str, json_mode: bool) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .context("Failed to build OpenAI-compatible client")?;
+
+        Ok(Self {
+            client,
+            name: "openai".to_string(),
+            endpoint: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            api_key: Some(api_key.to_string()).filter(|key| !key.is_empty()),
+            model: model.to_string(),
+            json_mode,
+        })
+    }
+
+    /// Reports the configured provider, e.g. "ollama", in logs and
+    /// `parsed_by` instead of "openai".
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String> {
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "temperature": 0,
+            "messages": [
+                { "role": "system", "content": system_prompt },
+                { "role": "user", "content": user_query },
+            ],
+        });
+
+        if self.json_mode {
+            body["response_format"] = match schema {
+                Some(schema) => json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": output_name(schema), "schema": schema },
+                }),
+                None => json!({ "type": "json_object" }),
+            };
+        }
+
+        let mut request = self.client.post(&self.endpoint).json(&body);
+
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to call OpenAI-compatible API")?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            anyhow::bail!("OpenAI-compatible API returned {}: {}", status, error_body);
+        }
+
+        let completion: ChatCompletion = response
+            .json()
+            .await
+            .context("Failed to parse OpenAI-compatible response")?;
+
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .context("OpenAI-compatible response contained no message content")
+    }
+}
+
+fn output_name(schema: &Value) -> &str {
+    schema
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or(STRUCTURED_OUTPUT_TOOL)
+}
+
+fn structured_output_tool(schema: &Value) -> Tool {
+    let object = |key: &str| {
+        schema
+            .get(key)
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|field| field.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Tool {
+        name: output_name(schema).to_string(),
+        description: schema
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or(STRUCTURED_OUTPUT_DESCRIPTION)
+            .to_string(),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: object("properties"),
+            required,
+            additional: Map::new(),
+        },
+    }
+}
+
+/// Converts a JSON schema into Gemini's schema type. `["string", "null"]`
+/// style types become nullable, which is the only union Gemini supports.
+fn gemini_schema(schema: &Value) -> Schema {
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(kind) => vec![kind.as_str()],
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+
+    let kind = match types.iter().find(|kind| **kind != "null") {
+        Some(&"string") => SchemaType::String,
+        Some(&"number") => SchemaType::Number,
+        Some(&"integer") => SchemaType::Integer,
+        Some(&"boolean") => SchemaType::Boolean,
+        Some(&"array") => SchemaType::Array,
+        Some(&"object") => SchemaType::Object,
+        _ => SchemaType::Unspecified,
+    };
+
+    let strings = |key: &str| -> Vec<String> {
+        schema[key]
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let values = strings("enum");
+
+    Schema {
+        r#type: kind as i32,
+        format: if values.is_empty() { "" } else { "enum" }.to_string(),
+        description: schema["description"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        nullable: types.contains(&"null"),
+        r#enum: values,
+        items: schema
+            .get("items")
+            .map(|items| Box::new(gemini_schema(items))),
+        properties: schema["properties"]
+            .as_object()
+            .map(|properties| {
+                properties
+                    .iter()
+                    .map(|(name, property)| (name.clone(), gemini_schema(property)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        required: strings("required"),
+        ..Default::default()
+    }
+}
@@ -1,6 +1,21 @@
 pub mod ai;
 pub mod cache;
+pub mod circuit_breaker;
+pub mod conversation_store;
 pub mod date_parser;
+pub mod enricher;
+pub mod eval;
 pub mod github;
+pub mod github_app;
+pub mod github_quota;
+pub mod github_tokens;
+pub mod intent;
+pub mod llm;
+pub mod prompts;
+pub mod push_notifier;
 pub mod rate_limiter;
+pub mod rule_parser;
 pub mod scheduler;
+pub mod snapshot_store;
+pub mod subscription_store;
+pub mod task_store;
@@ -0,0 +1,247 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::query::MAX_COUNT;
+
+pub const DEFAULT_SYSTEM_PROMPT_PATH: &str = "system_prompt.txt";
+pub const DEFAULT_EXAMPLES_PATH: &str = "few_shot_examples.txt";
+pub const DEFAULT_PROMPT_RELOAD_SECS: u64 = 10;
+
+const BUILT_IN_SYSTEM_PROMPT: &str = include_str!("../../system_prompt.txt");
+const BUILT_IN_EXAMPLES: &str = include_str!("../../few_shot_examples.txt");
+const BUILT_IN: &str = "built-in";
+
+#[derive(Debug, Clone)]
+struct Template {
+    text: String,
+    source: String,
+}
+
+impl Template {
+    fn built_in(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            source: BUILT_IN.to_string(),
+        }
+    }
+
+    /// Reads `path`, or returns `None` when there is no such file.
+    fn read(path: &PathBuf) -> Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+
+        if text.trim().is_empty() {
+            anyhow::bail!("{} is empty", path.display());
+        }
+
+        Ok(Some(Self {
+            text,
+            source: path.display().to_string(),
+        }))
+    }
+
+    fn load(path: Option<&PathBuf>, built_in: &str) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::built_in(built_in));
+        };
+
+        match Self::read(path)? {
+            Some(template) => Ok(template),
+            None => {
+                tracing::warn!("{} not found, using the built-in prompt", path.display());
+
+                Ok(Self::built_in(built_in))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PromptStatus {
+    /// File the system prompt was loaded from, or `built-in`.
+    pub system_prompt: String,
+    /// File the few-shot examples were loaded from, or `built-in`.
+    pub examples: String,
+}
+
+/// The query parser's system prompt and few-shot examples. Both are read
+/// from files when present, fall back to copies compiled into the binary,
+/// and can be reloaded while the server runs.
+///
+/// Templates may use `{{today}}`, `{{weekday}}`, `{{year}}`, `{{max_count}}`
+/// and `{{examples}}`. Unknown variables are left as they are.
+#[derive(Clone)]
+pub struct PromptStore {
+    system_prompt_path: Option<PathBuf>,
+    examples_path: Option<PathBuf>,
+    system_prompt: Arc<RwLock<Template>>,
+    examples: Arc<RwLock<Template>>,
+    variables: Regex,
+}
+
+impl PromptStore {
+    /// Loads both files. A missing file falls back to the built-in prompt,
+    /// an unreadable or empty one is an error.
+    pub fn load(system_prompt_path: &str, examples_path: &str) -> Result<Self> {
+        let system_prompt_path = PathBuf::from(system_prompt_path);
+        let examples_path = PathBuf::from(examples_path);
+
+        let system_prompt = Template::load(Some(&system_prompt_path), BUILT_IN_SYSTEM_PROMPT)?;
+        let examples = Template::load(Some(&examples_path), BUILT_IN_EXAMPLES)?;
+
+        Ok(Self::new(
+            Some(system_prompt_path),
+            Some(examples_path),
+            system_prompt,
+            examples,
+        ))
+    }
+
+    pub fn built_in() -> Self {
+        Self::from_template(BUILT_IN_SYSTEM_PROMPT, BUILT_IN_EXAMPLES)
+    }
+
+    /// A fixed prompt that is never reloaded.
+    pub fn from_template(system_prompt: &str, examples: &str) -> Self {
+        Self::new(
+            None,
+            None,
+            Template::built_in(system_prompt),
+            Template::built_in(examples),
+        )
+    }
+
+    fn new(
+        system_prompt_path: Option<PathBuf>,
+        examples_path: Option<PathBuf>,
+        system_prompt: Template,
+        examples: Template,
+    ) -> Self {
+        Self {
+            system_prompt_path,
+            examples_path,
+            system_prompt: Arc::new(RwLock::new(system_prompt)),
+            examples: Arc::new(RwLock::new(examples)),
+            variables: Regex::new(r"\{\{\s*(\w+)\s*\}\}").expect("invalid prompt variable regex"),
+        }
+    }
+
+    /// The system prompt with examples and variables filled in for today.
+    pub fn render(&self) -> String {
+        let system_prompt = self.system_prompt.read().unwrap_or_else(|e| e.into_inner());
+        let examples = self.examples.read().unwrap_or_else(|e| e.into_inner());
+
+        let examples = examples.text.trim();
+
+        let template = match system_prompt.text.contains("{{examples}}") || examples.is_empty() {
+            true => system_prompt.text.replace("{{examples}}", examples),
+            false => format!(
+                "{}\n\nExamples:\n\n{}\n",
+                system_prompt.text.trim_end(),
+                examples
+            ),
+        };
+
+        let now = Utc::now();
+
+        self.variables
+            .replace_all(&template, |captures: &Captures| match &captures[1] {
+                "today" => now.format("%Y-%m-%d").to_string(),
+                "weekday" => now.format("%A").to_string(),
+                "year" => now.format("%Y").to_string(),
+                "max_count" => MAX_COUNT.to_string(),
+                _ => captures[0].to_string(),
+            })
+            .into_owned()
+    }
+
+    /// Re-reads both files. If either can't be read, both templates stay as
+    /// they were.
+    pub fn reload(&self) -> Result<PromptStatus> {
+        let system_prompt =
+            Template::load(self.system_prompt_path.as_ref(), BUILT_IN_SYSTEM_PROMPT)?;
+        let examples = Template::load(self.examples_path.as_ref(), BUILT_IN_EXAMPLES)?;
+
+        *self
+            .system_prompt
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = system_prompt;
+        *self.examples.write().unwrap_or_else(|e| e.into_inner()) = examples;
+
+        let status = self.status();
+
+        tracing::info!(
+            "Prompts reloaded (system prompt: {}, examples: {})",
+            status.system_prompt,
+            status.examples
+        );
+
+        Ok(status)
+    }
+
+    pub fn status(&self) -> PromptStatus {
+        PromptStatus {
+            system_prompt: self
+                .system_prompt
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .source
+                .clone(),
+            examples: self
+                .examples
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .source
+                .clone(),
+        }
+    }
+
+    /// Reloads the prompts whenever one of the files changes, checking
+    /// every `interval`.
+    pub fn watch(&self, interval: Duration) {
+        let store = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut last_seen = store.modified_times();
+
+            loop {
+                ticker.tick().await;
+
+                let seen = store.modified_times();
+
+                if seen == last_seen {
+                    continue;
+                }
+
+                last_seen = seen;
+
+                if let Err(e) = store.reload() {
+                    tracing::error!("Failed to reload prompts: {:#}", e);
+                }
+            }
+        });
+    }
+
+    fn modified_times(&self) -> [Option<SystemTime>; 2] {
+        [&self.system_prompt_path, &self.examples_path].map(|path| {
+            path.as_ref()
+                .and_then(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        })
+    }
+}
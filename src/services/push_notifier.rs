@@ -0,0 +1,252 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use reqwest::{
+    StatusCode, Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect::Policy,
+};
+
+use crate::models::a2a::{PushNotificationConfig, TaskResult};
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_DELAY_MS: u64 = 500;
+
+/// Resolves callback hosts, refusing any that resolve to a loopback,
+/// private or link-local address, so a client can't make the server post
+/// to its own network.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .collect::<Vec<SocketAddr>>();
+
+            if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+                return Err(
+                    format!("{} resolves to non-public {}", name.as_str(), addr.ip()).into(),
+                );
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PushNotifier {
+    client: reqwest::Client,
+    configs: Arc<DashMap<String, PushNotificationConfig>>,
+    allow_private_hosts: bool,
+}
+
+impl PushNotifier {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: build_client(false)?,
+            configs: Arc::new(DashMap::new()),
+            allow_private_hosts: false,
+        })
+    }
+
+    /// Lets callbacks reach loopback and private addresses, for local
+    /// development and tests.
+    pub fn with_private_hosts(mut self) -> Result<Self> {
+        self.client = build_client(true)?;
+        self.allow_private_hosts = true;
+        Ok(self)
+    }
+
+    pub fn set(&self, task_id: &str, config: PushNotificationConfig) -> Result<()> {
+        let url = Url::parse(&config.url).context("Invalid push notification url")?;
+
+        if url.scheme() != "http" && url.scheme() != "https" {
+            anyhow::bail!("Push notification url must use http or https");
+        }
+
+        if !self.allow_private_hosts {
+            check_public_host(&url)?;
+        }
+
+        self.configs.insert(task_id.to_string(), config);
+        tracing::info!("Push notification config set for task {}", task_id);
+
+        Ok(())
+    }
+
+    pub fn get(&self, task_id: &str) -> Option<PushNotificationConfig> {
+        self.configs.get(task_id).map(|config| config.clone())
+    }
+
+    /// Drops the configs of tasks `keep` rejects, e.g. evicted ones.
+    /// Returns how many were dropped.
+    pub fn retain_tasks(&self, keep: impl Fn(&str) -> bool) -> usize {
+        let before = self.configs.len();
+        self.configs.retain(|task_id, _| keep(task_id));
+        before.saturating_sub(self.configs.len())
+    }
+
+    pub async fn notify(&self, task: &TaskResult) {
+        let Some(config) = self.get(&task.id) else {
+            return;
+        };
+
+        self.deliver(&config, task).await;
+    }
+
+    /// Sends `task` to `config`, retrying server errors and failed
+    /// connections with exponential backoff. Returns the number of attempts.
+    pub async fn deliver(&self, config: &PushNotificationConfig, task: &TaskResult) -> u32 {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let error = match self.send(config, task).await {
+                Ok(status) if status.is_success() => {
+                    tracing::info!(
+                        "Push notification delivered for task {} (attempt {})",
+                        task.id,
+                        attempt
+                    );
+                    return attempt;
+                }
+                Ok(status) if !status.is_server_error() => {
+                    tracing::error!(
+                        "Push notification for task {} rejected with {}, not retrying",
+                        task.id,
+                        status
+                    );
+                    return attempt;
+                }
+                Ok(status) => anyhow::anyhow!("Callback responded with {}", status),
+                Err(e) => e,
+            };
+
+            match error {
+                e if attempt < MAX_ATTEMPTS => {
+                    let delay = Duration::from_millis(BASE_DELAY_MS * 2u64.pow(attempt - 1));
+
+                    tracing::warn!(
+                        "Push notification for task {} failed (attempt {}): {}, retrying in {}ms",
+                        task.id,
+                        attempt,
+                        e,
+                        delay.as_millis()
+                    );
+
+                    tokio::time::sleep(delay).await;
+                }
+                e => {
+                    tracing::error!(
+                        "Push notification for task {} failed after {} attempts: {}",
+                        task.id,
+                        MAX_ATTEMPTS,
+                        e
+                    );
+                }
+            }
+        }
+
+        MAX_ATTEMPTS
+    }
+
+    async fn send(&self, config: &PushNotificationConfig, task: &TaskResult) -> Result<StatusCode> {
+        let mut request = self.client.post(&config.url).json(task);
+
+        if let Some(token) = &config.token {
+            request = request.header("X-A2A-Notification-Token", token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to send push notification")?;
+
+        Ok(response.status())
+    }
+}
+
+/// Redirects aren't followed: one could point a checked host at a private
+/// address.
+fn build_client(allow_private_hosts: bool) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(Policy::none());
+
+    if !allow_private_hosts {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+
+    builder
+        .build()
+        .context("Failed to build push notification client")
+}
+
+/// Rejects IP literals that aren't public and names that only resolve
+/// locally. Other names are checked when they resolve.
+fn check_public_host(url: &Url) -> Result<()> {
+    let host = url
+        .host_str()
+        .context("Push notification url has no host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_lowercase();
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        if !is_public(ip) {
+            anyhow::bail!("Push notification url must not point to a private address");
+        }
+    } else if host == "localhost"
+        || [".localhost", ".local", ".internal"]
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+    {
+        anyhow::bail!("Push notification url must not point to a local host");
+    }
+
+    Ok(())
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT, 100.64.0.0/10.
+        || (a == 100 && (64..128).contains(&b))
+        // Reserved, 240.0.0.0/4.
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7.
+        || (first & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10.
+        || (first & 0xffc0) == 0xfe80)
+}
@@ -1,4 +1,5 @@
 use std::{
+    fmt,
     net::SocketAddr,
     sync::Arc,
     time::{Duration, Instant},
@@ -7,15 +8,31 @@ use std::{
 use axum::{
     body::Body,
     extract::{ConnectInfo, Request},
-    http::HeaderValue,
     middleware::Next,
     response::Response,
 };
 
 use dashmap::DashMap;
-use reqwest::StatusCode;
 use tokio::time::interval;
 
+#[derive(Debug)]
+pub enum RateLimitError {
+    /// The client's window is full; holds the seconds until it resets.
+    Exceeded(u64),
+    Unconfigured,
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exceeded(secs) => write!(f, "Rate limit exceeded, retry after {}s", secs),
+            Self::Unconfigured => write!(f, "Rate limiter is not configured"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
 #[derive(Clone)]
 pub struct RateLimiter {
     windows: Arc<DashMap<String, Window>>,
@@ -51,7 +68,8 @@ impl RateLimiter {
         limiter
     }
 
-    pub fn check_rate_limit(&self, client_key: &str) -> bool {
+    /// Counts a request from `client_key`, failing once its window is full.
+    pub fn check_rate_limit(&self, client_key: &str) -> Result<(), RateLimitError> {
         let now = Instant::now();
 
         let mut entry = self
@@ -68,11 +86,11 @@ impl RateLimiter {
         }
 
         if entry.request_count >= self.requests_per_window {
-            return false;
+            return Err(RateLimitError::Exceeded(self.window_duration.as_secs()));
         }
 
         entry.request_count += 1;
-        true
+        Ok(())
     }
 }
 
@@ -80,21 +98,13 @@ pub async fn rate_limit_middleware(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     req: Request<Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, RateLimitError> {
     let rate_limiter = req
         .extensions()
         .get::<RateLimiter>()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+        .ok_or(RateLimitError::Unconfigured)?;
 
-    let client_key = addr.ip().to_string();
-
-    if !rate_limiter.check_rate_limit(&client_key) {
-        let mut res = Response::new(Body::empty());
-        *res.status_mut() = StatusCode::TOO_MANY_REQUESTS;
-        res.headers_mut()
-            .insert("Retry-After", HeaderValue::from_static("60"));
-        return Ok(res);
-    }
+    rate_limiter.check_rate_limit(&addr.ip().to_string())?;
 
     Ok(next.run(req).await)
 }
@@ -0,0 +1,352 @@
+use regex::Regex;
+
+use crate::{
+    models::{
+        lexicon::{
+            AMBIGUOUS_LANGUAGES, LANGUAGE_CONTEXT, LANGUAGE_CUES, LANGUAGES, TOPIC_PHRASES, TOPICS,
+        },
+        query::{FollowUp, MAX_COUNT, ParsedQuery, QueryParams, SortBy},
+    },
+    services::date_parser::DateParser,
+};
+
+const CONFIDENT: f32 = 0.9;
+const GENERIC: f32 = 0.7;
+const UNSURE: f32 = 0.3;
+
+const MONTH: &str = r"(?:jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sept?(?:ember)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+const DAY: &str = r"\d{1,2}(?:st|nd|rd|th)?";
+const YEAR: &str = r"(?:19|20)\d{2}";
+const WEEKDAY: &str = r"(?:monday|tuesday|wednesday|thursday|friday|saturday|sunday)";
+
+const GENERIC_WORDS: &[&str] = &[
+    "trending",
+    "popular",
+    "hot",
+    "top",
+    "best",
+    "new",
+    "repos",
+    "repositories",
+    "projects",
+];
+
+/// Timeframe phrases, matched as whole words and also in the plural
+/// ("2 weeks", "past months").
+const TIMEFRAMES: &[(&str, &str)] = &[
+    ("today", "day"),
+    ("yesterday", "day"),
+    ("daily", "day"),
+    ("24 hours", "day"),
+    ("week", "week"),
+    ("weekly", "week"),
+    ("quarter", "quarter"),
+    ("3 months", "quarter"),
+    ("month", "month"),
+    ("monthly", "month"),
+    ("year", "year"),
+    ("yearly", "year"),
+    ("annual", "year"),
+];
+
+/// Fields a query mentioned explicitly. Anything left as `None` keeps its
+/// default (or, for follow-ups, its previous value).
+#[derive(Debug, Default)]
+struct Extracted {
+    language: Option<String>,
+    topics: Vec<String>,
+    timeframe: Option<String>,
+    count: Option<usize>,
+    min_stars: Option<u32>,
+    date_string: Option<String>,
+    sort: Option<SortBy>,
+}
+
+impl Extracted {
+    fn is_empty(&self) -> bool {
+        self.language.is_none()
+            && self.topics.is_empty()
+            && self.timeframe.is_none()
+            && self.count.is_none()
+            && self.min_stars.is_none()
+            && self.date_string.is_none()
+            && self.sort.is_none()
+    }
+
+    fn apply(self, mut params: QueryParams) -> QueryParams {
+        if self.language.is_some() {
+            params.language = self.language;
+        }
+
+        if !self.topics.is_empty() {
+            params.topics = self.topics;
+        }
+
+        if let Some(count) = self.count {
+            params.count = count;
+        }
+
+        if let Some(min_stars) = self.min_stars {
+            params.min_stars = min_stars;
+        }
+
+        if let Some(sort) = self.sort {
+            params.sort = sort;
+        }
+
+        if self.timeframe.is_some() || self.date_string.is_some() {
+            params.created_after = None;
+            params.pushed_after = None;
+            params.has_specific_date = self.date_string.is_some();
+            params.date_string = self.date_string;
+        }
+
+        if let Some(timeframe) = self.timeframe {
+            params.timeframe = timeframe;
+        }
+
+        params
+    }
+}
+
+/// Offline query parser built from regexes and a language/topic lexicon.
+/// Used when no LLM is configured and as a fallback when the LLM call fails.
+#[derive(Clone)]
+pub struct RuleParser {
+    stars: Regex,
+    counts: Vec<Regex>,
+    dates: Vec<Regex>,
+    timeframes: Vec<(Regex, &'static str)>,
+    more: Regex,
+    velocity: Regex,
+}
+
+impl Default for RuleParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuleParser {
+    pub fn new() -> Self {
+        let regex = |pattern: &str| Regex::new(pattern).expect("invalid rule parser regex");
+
+        Self {
+            stars: regex(r"(\d+(?:\.\d+)?)\s*(k)?\s*\+?\s*stars?\b"),
+            counts: vec![
+                regex(r"\btop\s+(\d+)\b"),
+                regex(r"\b(?:show|give|list|find|get)(?:\s+me)?\s+(\d+)\b"),
+                regex(
+                    r"\b(\d+)\s+(?:\S+\s+){0,4}?(?:repos?|repositories|projects|libraries|libs|tools|results)\b",
+                ),
+                regex(r"\b(\d+)\s+more\b"),
+                regex(r"\bnext\s+(\d+)\b"),
+            ],
+            dates: vec![
+                regex(&format!(
+                    r"\b(?:(?:after|since|from)\s+)?(?:{MONTH}\s+{DAY},?\s+{YEAR}|{DAY}\s+{MONTH},?\s+{YEAR}|\d{{4}}-\d{{2}}-\d{{2}}|\d{{1,2}}/\d{{1,2}}/\d{{4}})\b"
+                )),
+                regex(&format!(
+                    r"\b(?:(?:after|since|from)\s+)?last\s+{WEEKDAY}\b"
+                )),
+                regex(r"\b(?:last|past)\s+\d+\s+days\b"),
+            ],
+            timeframes: TIMEFRAMES
+                .iter()
+                .map(|(phrase, timeframe)| {
+                    (
+                        regex(&format!(r"\b{}s?\b", regex::escape(phrase))),
+                        *timeframe,
+                    )
+                })
+                .collect(),
+            more: regex(r"\b(?:more|next|another|additional|page)\b"),
+            velocity: regex(
+                r"\b(?:fastest[- ]growing|gaining(?: the most)? stars|star velocity|taking off|blowing up|exploding)\b",
+            ),
+        }
+    }
+
+    pub fn parse(&self, user_query: &str) -> ParsedQuery {
+        let text = user_query.to_lowercase();
+        let extracted = self.extract(&text);
+
+        let confidence = if !extracted.is_empty() {
+            CONFIDENT
+        } else if tokens(&text).any(|token| GENERIC_WORDS.contains(&token)) {
+            GENERIC
+        } else {
+            UNSURE
+        };
+
+        tracing::info!(
+            "Rule-based parse (confidence: {}): {:?}",
+            confidence,
+            extracted
+        );
+
+        ParsedQuery {
+            params: extracted.apply(QueryParams::default()),
+            confidence,
+            clarifying_question: None,
+            parsed_by: None,
+        }
+    }
+
+    pub fn parse_follow_up(&self, user_query: &str, previous: &QueryParams) -> FollowUp {
+        let text = user_query.to_lowercase().replace("more than", "over");
+        let more_results = self.more.is_match(&text);
+        let extracted = self.extract(&text);
+
+        tracing::info!(
+            "Rule-based follow-up (more results: {}): {:?}",
+            more_results,
+            extracted
+        );
+
+        FollowUp {
+            params: extracted.apply(previous.clone()),
+            more_results,
+            parsed_by: None,
+        }
+    }
+
+    fn extract(&self, text: &str) -> Extracted {
+        let mut rest = text.replace("more than", "over");
+
+        let min_stars = self.stars.captures(&rest).and_then(|captures| {
+            let value = captures[1].parse::<f64>().ok()?;
+            let multiplier = if captures.get(2).is_some() {
+                1000.0
+            } else {
+                1.0
+            };
+
+            Some((value * multiplier) as u32)
+        });
+        rest = self.stars.replace_all(&rest, " ").to_string();
+
+        let date_string = self
+            .dates
+            .iter()
+            .filter_map(|regex| regex.find(&rest))
+            .map(|found| found.as_str().trim().to_string())
+            .find(|phrase| DateParser::parse(phrase).is_ok());
+
+        if let Some(phrase) = &date_string {
+            rest = rest.replace(phrase.as_str(), " ");
+        }
+
+        let count = self
+            .counts
+            .iter()
+            .find_map(|regex| regex.captures(&rest))
+            .and_then(|captures| captures[1].parse::<usize>().ok())
+            .map(|count| count.clamp(1, MAX_COUNT));
+
+        let timeframe = self
+            .timeframes
+            .iter()
+            .find(|(regex, _)| regex.is_match(&rest))
+            .map(|(_, timeframe)| timeframe.to_string());
+
+        let language = extract_language(&rest);
+
+        let sort = self.velocity.is_match(&rest).then_some(SortBy::Velocity);
+
+        Extracted {
+            language,
+            topics: extract_topics(&rest),
+            timeframe,
+            count,
+            min_stars,
+            date_string,
+            sort,
+        }
+    }
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| c.is_whitespace() || ",;:!?()[]\"'/".contains(c))
+        .map(|token| token.trim_end_matches('.'))
+        .filter(|token| !token.is_empty())
+}
+
+fn lookup(lexicon: &[(&str, &str)], token: &str) -> Option<String> {
+    lexicon
+        .iter()
+        .find(|(alias, _)| *alias == token)
+        .map(|(_, name)| name.to_string())
+}
+
+/// Prefers an unambiguous alias; "go" and "c" only count in a language
+/// context, so "I want to go through python repos" stays python.
+fn extract_language(text: &str) -> Option<String> {
+    let tokens = tokens(text).collect::<Vec<_>>();
+
+    let names_language = |index: usize| {
+        index
+            .checked_sub(1)
+            .is_some_and(|previous| LANGUAGE_CUES.contains(&tokens[previous]))
+            || tokens
+                .get(index + 1)
+                .is_some_and(|next| LANGUAGE_CONTEXT.contains(next))
+    };
+
+    tokens
+        .iter()
+        .find_map(|token| match AMBIGUOUS_LANGUAGES.contains(token) {
+            true => None,
+            false => lookup(LANGUAGES, token),
+        })
+        .or_else(|| {
+            tokens
+                .iter()
+                .enumerate()
+                .filter(|(index, token)| {
+                    AMBIGUOUS_LANGUAGES.contains(token) && names_language(*index)
+                })
+                .find_map(|(_, token)| lookup(LANGUAGES, token))
+        })
+}
+
+fn extract_topics(text: &str) -> Vec<String> {
+    let mut spaced = text.replace('-', " ");
+    let mut found: Vec<(usize, String)> = vec![];
+
+    for (phrase, topic) in TOPIC_PHRASES {
+        if let Some(position) = spaced.find(phrase) {
+            found.push((position, topic.to_string()));
+            spaced.replace_range(position..position + phrase.len(), &" ".repeat(phrase.len()));
+        }
+    }
+
+    let mut offset = 0;
+    for token in tokens(&spaced) {
+        let position = spaced[offset..]
+            .find(token)
+            .map_or(offset, |index| offset + index);
+        offset = position + token.len();
+
+        let topic = lookup(TOPICS, token).or_else(|| {
+            token
+                .strip_suffix('s')
+                .and_then(|stem| lookup(TOPICS, stem))
+        });
+
+        if let Some(topic) = topic {
+            found.push((position, topic));
+        }
+    }
+
+    found.sort_by_key(|(position, _)| *position);
+
+    let mut topics: Vec<String> = vec![];
+    for (_, topic) in found {
+        if !topics.contains(&topic) {
+            topics.push(topic);
+        }
+    }
+
+    topics
+}
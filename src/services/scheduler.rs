@@ -5,7 +5,7 @@ use tokio_cron_scheduler::{Job, JobScheduler};
 
 use crate::{
     api::state::AppState,
-    utils::tasks::{send_daily_digest, send_weekly_roundup},
+    utils::tasks::{send_daily_digest, send_weekly_roundup, snapshot_stars},
 };
 
 pub struct AgentScheduler {
@@ -62,4 +62,67 @@ impl AgentScheduler {
         tracing::info!("Weekly roundup job scheduled (9 AM Mondays)");
         Ok(())
     }
+
+    pub async fn add_cleanup(&self) -> Result<()> {
+        let state = Arc::clone(&self.state);
+        let job = Job::new_async("0 */10 * * * *", move |_uuid, _lock| {
+            let state = Arc::clone(&state);
+            Box::pin(async move {
+                let task_store = state.task_store.clone();
+
+                if let Err(e) =
+                    tokio::task::spawn_blocking(move || task_store.evict_expired()).await
+                {
+                    tracing::error!("Task eviction failed: {}", e);
+                }
+
+                let dropped = state
+                    .push_notifier
+                    .retain_tasks(|task_id| state.task_store.contains(task_id));
+
+                if dropped > 0 {
+                    tracing::info!("Dropped {} stale push notification configs", dropped);
+                }
+
+                let expired = state.conversation_store.evict_expired()
+                    + state
+                        .conversation_store
+                        .retain_pending(|task_id| state.task_store.contains(task_id));
+
+                if expired > 0 {
+                    tracing::info!(
+                        "Dropped {} expired conversations and clarifications",
+                        expired
+                    );
+                }
+
+                let evicted = state.github_client.evict_expired_responses();
+
+                if evicted > 0 {
+                    tracing::info!("Dropped {} expired search responses", evicted);
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        tracing::info!("Cleanup job scheduled (every 10 minutes)");
+        Ok(())
+    }
+
+    pub async fn add_star_snapshots(&self) -> Result<()> {
+        let state = Arc::clone(&self.state);
+        let job = Job::new_async("0 30 */6 * * *", move |_uuid, _lock| {
+            let state = Arc::clone(&state);
+            Box::pin(async move {
+                tracing::info!("Running star snapshot job");
+                if let Err(e) = snapshot_stars(state).await {
+                    tracing::error!("Star snapshots failed: {}", e);
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        tracing::info!("Star snapshot job scheduled (every 6 hours)");
+        Ok(())
+    }
 }
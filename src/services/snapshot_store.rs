@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::{Connection, params};
+
+use crate::models::repository::{StarVelocity, TrendingRepo};
+
+/// A repository is snapshotted at most this often, so searches that
+/// return it again don't produce near-identical rows.
+pub const SNAPSHOT_INTERVAL: TimeDelta = TimeDelta::hours(1);
+
+/// Snapshots older than this are pruned.
+pub const SNAPSHOT_RETENTION: TimeDelta = TimeDelta::days(30);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS star_snapshots (
+    repo TEXT NOT NULL,
+    taken_at INTEGER NOT NULL,
+    stars INTEGER NOT NULL,
+    forks INTEGER NOT NULL,
+    PRIMARY KEY (repo, taken_at)
+) WITHOUT ROWID;
+";
+
+struct Snapshot {
+    taken_at: i64,
+    stars: i64,
+    forks: i64,
+}
+
+/// Star and fork counts of repositories over time, kept in SQLite. Every
+/// search result is recorded, and the scheduler re-snapshots the tracked
+/// repositories, so consecutive snapshots show how fast a repository is
+/// gaining stars right now. Clones share the connection.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SnapshotStore {
+    /// Opens the database at `path`, creating it when missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).context("Failed to create snapshot store directory")?;
+        }
+
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open snapshot store {:?}", path))?;
+
+        Self::init(connection)
+    }
+
+    /// A store that lives as long as the process.
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory().context("Failed to open snapshot store")?)
+    }
+
+    fn init(connection: Connection) -> Result<Self> {
+        connection
+            .execute_batch(SCHEMA)
+            .context("Failed to create snapshot store schema")?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Records the current counts of `repos`. Returns how many were
+    /// recorded.
+    pub fn record(&self, repos: &[TrendingRepo]) -> Result<usize> {
+        self.record_at(repos, Utc::now())
+    }
+
+    /// Records `repos` as seen at `taken_at`, skipping repositories
+    /// snapshotted less than `SNAPSHOT_INTERVAL` before, and prunes
+    /// snapshots past `SNAPSHOT_RETENTION`.
+    pub fn record_at(&self, repos: &[TrendingRepo], taken_at: DateTime<Utc>) -> Result<usize> {
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        let transaction = connection.transaction()?;
+
+        let taken_at = taken_at.timestamp();
+        let mut recorded = 0;
+
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO star_snapshots (repo, taken_at, stars, forks)
+                 SELECT ?1, ?2, ?3, ?4 WHERE NOT EXISTS (
+                     SELECT 1 FROM star_snapshots WHERE repo = ?1 AND taken_at > ?5
+                 )",
+            )?;
+
+            let since = taken_at - SNAPSHOT_INTERVAL.num_seconds();
+
+            for repo in repos {
+                recorded +=
+                    insert.execute(params![repo.name, taken_at, repo.stars, repo.forks, since])?;
+            }
+
+            transaction.execute(
+                "DELETE FROM star_snapshots WHERE taken_at < ?1",
+                params![taken_at - SNAPSHOT_RETENTION.num_seconds()],
+            )?;
+        }
+
+        transaction
+            .commit()
+            .context("Failed to record star snapshots")?;
+
+        Ok(recorded)
+    }
+
+    /// How fast `repo` is gaining stars, or `None` until it has two
+    /// snapshots.
+    pub fn velocity(&self, repo: &str) -> Result<Option<StarVelocity>> {
+        Ok(self.velocities(&[repo])?.remove(repo))
+    }
+
+    /// The velocity of every repository in `repos` that has two snapshots,
+    /// read with one query.
+    pub fn velocities<S: AsRef<str>>(&self, repos: &[S]) -> Result<HashMap<String, StarVelocity>> {
+        let names = repos.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut statement = connection.prepare_cached(
+            "SELECT repo, taken_at, stars, forks FROM star_snapshots
+             WHERE repo IN (SELECT value FROM json_each(?1))
+             ORDER BY repo, taken_at DESC",
+        )?;
+
+        let mut snapshots: HashMap<String, Vec<Snapshot>> = HashMap::new();
+
+        let rows = statement.query_map(params![serde_json::to_string(&names)?], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Snapshot {
+                    taken_at: row.get(1)?,
+                    stars: row.get(2)?,
+                    forks: row.get(3)?,
+                },
+            ))
+        })?;
+
+        for row in rows {
+            let (repo, snapshot) = row?;
+            snapshots.entry(repo).or_default().push(snapshot);
+        }
+
+        Ok(snapshots
+            .into_iter()
+            .filter_map(|(repo, snapshots)| Some((repo, velocity(&snapshots)?)))
+            .collect())
+    }
+
+    /// Repositories snapshotted since `since`, most recently seen first.
+    pub fn tracked(&self, since: DateTime<Utc>, limit: usize) -> Result<Vec<String>> {
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut statement = connection.prepare_cached(
+            "SELECT repo FROM star_snapshots GROUP BY repo
+             HAVING MAX(taken_at) >= ?1
+             ORDER BY MAX(taken_at) DESC, repo LIMIT ?2",
+        )?;
+
+        let repos = statement
+            .query_map(params![since.timestamp(), limit as i64], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(repos)
+    }
+
+    /// Runs `f` on the blocking thread pool, so SQLite calls don't stall the
+    /// async workers.
+    pub async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&SnapshotStore) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let store = self.clone();
+
+        tokio::task::spawn_blocking(move || f(&store))
+            .await
+            .context("Snapshot store task failed")?
+    }
+
+    /// Number of snapshots stored for `repo`.
+    pub fn snapshot_count(&self, repo: &str) -> Result<usize> {
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+
+        let count: i64 = connection.query_row(
+            "SELECT COUNT(*) FROM star_snapshots WHERE repo = ?1",
+            params![repo],
+            |row| row.get(0),
+        )?;
+
+        Ok(count as usize)
+    }
+}
+
+/// `snapshots` newest first.
+fn velocity(snapshots: &[Snapshot]) -> Option<StarVelocity> {
+    let [latest, previous, ..] = snapshots else {
+        return None;
+    };
+
+    let week_ago = latest.taken_at - TimeDelta::days(7).num_seconds();
+
+    let baseline = snapshots
+        .iter()
+        .find(|snapshot| snapshot.taken_at <= week_ago)
+        .or(snapshots.last())
+        .unwrap_or(previous);
+
+    let oldest = snapshots.last().unwrap_or(previous);
+
+    Some(StarVelocity {
+        stars_per_day: round(
+            (latest.stars - previous.stars) as f64 / days(latest.taken_at - previous.taken_at),
+        ),
+        stars_last_week: latest.stars - baseline.stars,
+        forks_last_week: latest.forks - baseline.forks,
+        tracked_days: round(days(latest.taken_at - oldest.taken_at)),
+    })
+}
+
+fn days(seconds: i64) -> f64 {
+    seconds as f64 / TimeDelta::days(1).num_seconds() as f64
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::models::a2a::PushNotificationConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily digest",
+            Self::Weekly => "weekly roundup",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub context_id: String,
+    pub frequency: DigestFrequency,
+    pub push_config: PushNotificationConfig,
+}
+
+/// Conversations that asked to receive the scheduled digests. Each context
+/// holds at most one subscription.
+#[derive(Clone, Default)]
+pub struct SubscriptionStore {
+    subscriptions: Arc<DashMap<String, Subscription>>,
+}
+
+impl SubscriptionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, subscription: Subscription) {
+        tracing::info!(
+            "Context {} subscribed to the {}",
+            subscription.context_id,
+            subscription.frequency.as_str()
+        );
+
+        self.subscriptions
+            .insert(subscription.context_id.clone(), subscription);
+    }
+
+    /// Returns the removed subscription, if the context had one.
+    pub fn unsubscribe(&self, context_id: &str) -> Option<Subscription> {
+        let (_, subscription) = self.subscriptions.remove(context_id)?;
+
+        tracing::info!("Context {} unsubscribed", context_id);
+
+        Some(subscription)
+    }
+
+    pub fn get(&self, context_id: &str) -> Option<Subscription> {
+        self.subscriptions
+            .get(context_id)
+            .map(|subscription| subscription.clone())
+    }
+
+    pub fn subscribers(&self, frequency: DigestFrequency) -> Vec<Subscription> {
+        self.subscriptions
+            .iter()
+            .filter(|subscription| subscription.frequency == frequency)
+            .map(|subscription| subscription.clone())
+            .collect()
+    }
+}
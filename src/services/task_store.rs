@@ -0,0 +1,459 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use dashmap::{DashMap, mapref::entry::Entry};
+use serde::{Deserialize, Serialize};
+
+use crate::models::a2a::{Artifact, Message, TaskResult, TaskStatus};
+
+const TERMINAL_STATES: [&str; 4] = ["completed", "canceled", "failed", "rejected"];
+
+/// How long a task is kept after its last update.
+pub const DEFAULT_TASK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub state: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TaskRecord {
+    task: TaskResult,
+    transitions: Vec<StateTransition>,
+    /// Bumped on every change, so an older write never replaces a newer one.
+    #[serde(skip)]
+    version: u64,
+}
+
+#[derive(Debug)]
+pub enum TaskStoreError {
+    NotFound(String),
+    NotCancelable(String),
+    /// The task already reached this terminal state and takes no more
+    /// messages.
+    Closed(String),
+    /// The task is still being processed in this state and only takes a
+    /// message once it asks for input.
+    Busy(String),
+}
+
+impl fmt::Display for TaskStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Task not found: {}", id),
+            Self::NotCancelable(state) => write!(f, "Task cannot be canceled in state '{}'", state),
+            Self::Closed(state) => {
+                write!(f, "Task is already {} and takes no more messages", state)
+            }
+            Self::Busy(state) => {
+                write!(
+                    f,
+                    "Task is still {} and takes no messages until it needs input",
+                    state
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaskStoreError {}
+
+/// Writes task files in order: a write older than the last one written
+/// for the same task is dropped.
+#[derive(Default)]
+struct Writer {
+    written: Mutex<HashMap<String, u64>>,
+}
+
+#[derive(Clone)]
+pub struct TaskStore {
+    tasks: Arc<DashMap<String, TaskRecord>>,
+    persist_dir: Option<PathBuf>,
+    writer: Arc<Writer>,
+    ttl: Duration,
+}
+
+impl TaskStore {
+    pub fn new(persist_dir: Option<&str>) -> Result<Self> {
+        let store = Self {
+            tasks: Arc::new(DashMap::new()),
+            persist_dir: persist_dir.map(PathBuf::from),
+            writer: Arc::default(),
+            ttl: DEFAULT_TASK_TTL,
+        };
+
+        if let Some(dir) = &store.persist_dir {
+            fs::create_dir_all(dir).context("Failed to create task store directory")?;
+
+            for entry in fs::read_dir(dir).context("Failed to read task store directory")? {
+                let path = entry?.path();
+
+                if path.extension().is_none_or(|ext| ext != "json") {
+                    continue;
+                }
+
+                match fs::read(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|bytes| Ok(serde_json::from_slice::<TaskRecord>(&bytes)?))
+                {
+                    Ok(record) => {
+                        store.tasks.insert(record.task.id.clone(), record);
+                    }
+                    Err(e) => tracing::warn!("Skipping unreadable task file {:?}: {}", path, e),
+                }
+            }
+
+            tracing::info!(
+                "Loaded {} persisted tasks from {:?}",
+                store.tasks.len(),
+                dir
+            );
+        }
+
+        Ok(store)
+    }
+
+    /// Keeps tasks for `ttl` after their last update, see `evict_expired`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Checks that a message for `task_id` from `context_id` may continue
+    /// it: the task is new, or waiting for input in the same context.
+    pub fn accepts(&self, task_id: &str, context_id: &str) -> Result<(), TaskStoreError> {
+        match self.tasks.get(task_id) {
+            Some(record) => check_open(&record, context_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Records `message` for a new task, or continues one that is waiting
+    /// for input. Tasks that are running, finished or from another context
+    /// are rejected.
+    pub fn submit(
+        &self,
+        task_id: &str,
+        context_id: &str,
+        message: &Message,
+    ) -> Result<TaskResult, TaskStoreError> {
+        let now = timestamp();
+
+        let mut record = match self.tasks.entry(task_id.to_string()) {
+            Entry::Occupied(entry) => {
+                check_open(entry.get(), context_id)?;
+                entry.into_ref()
+            }
+            Entry::Vacant(entry) => entry.insert(TaskRecord {
+                task: TaskResult {
+                    kind: "task".to_string(),
+                    id: task_id.to_string(),
+                    context_id: context_id.to_string(),
+                    status: TaskStatus {
+                        state: "submitted".to_string(),
+                        timestamp: now.clone(),
+                        message: message.clone(),
+                    },
+                    artifacts: vec![],
+                    history: vec![],
+                },
+                transitions: vec![],
+                version: 0,
+            }),
+        };
+
+        record.task.history.push(message.clone());
+        record.task.status = TaskStatus {
+            state: "submitted".to_string(),
+            timestamp: now.clone(),
+            message: message.clone(),
+        };
+        record.transitions.push(StateTransition {
+            state: "submitted".to_string(),
+            timestamp: now,
+        });
+
+        let task = record.task.clone();
+        let write = self.prepare_write(&mut record);
+        drop(record);
+        self.write(write);
+
+        Ok(task)
+    }
+
+    pub fn transition(&self, task_id: &str, state: &str, message: Message) -> Option<TaskResult> {
+        self.update(task_id, state, message, false, vec![])
+    }
+
+    pub fn complete(
+        &self,
+        task_id: &str,
+        state: &str,
+        message: Message,
+        artifacts: Vec<Artifact>,
+    ) -> Option<TaskResult> {
+        self.update(task_id, state, message, true, artifacts)
+    }
+
+    pub fn get(&self, task_id: &str, history_length: Option<usize>) -> Option<TaskResult> {
+        let mut task = self.tasks.get(task_id)?.task.clone();
+
+        if let Some(length) = history_length {
+            let skip = task.history.len().saturating_sub(length);
+            task.history.drain(..skip);
+        }
+
+        Some(task)
+    }
+
+    pub fn contains(&self, task_id: &str) -> bool {
+        self.tasks.contains_key(task_id)
+    }
+
+    pub fn transitions(&self, task_id: &str) -> Option<Vec<StateTransition>> {
+        self.tasks
+            .get(task_id)
+            .map(|record| record.transitions.clone())
+    }
+
+    pub fn is_canceled(&self, task_id: &str) -> bool {
+        self.tasks
+            .get(task_id)
+            .is_some_and(|record| record.task.status.state == "canceled")
+    }
+
+    pub fn cancel(&self, task_id: &str) -> Result<TaskResult, TaskStoreError> {
+        let mut record = self
+            .tasks
+            .get_mut(task_id)
+            .ok_or_else(|| TaskStoreError::NotFound(task_id.to_string()))?;
+
+        let state = record.task.status.state.clone();
+
+        if TERMINAL_STATES.contains(&state.as_str()) {
+            return Err(TaskStoreError::NotCancelable(state));
+        }
+
+        let now = timestamp();
+        let message = Message::agent("Task canceled".to_string(), task_id, None);
+
+        record.task.status = TaskStatus {
+            state: "canceled".to_string(),
+            timestamp: now.clone(),
+            message,
+        };
+        record.transitions.push(StateTransition {
+            state: "canceled".to_string(),
+            timestamp: now,
+        });
+
+        let task = record.task.clone();
+        let write = self.prepare_write(&mut record);
+        drop(record);
+        self.write(write);
+
+        tracing::info!("Task {} canceled (was '{}')", task_id, state);
+
+        Ok(task)
+    }
+
+    fn update(
+        &self,
+        task_id: &str,
+        state: &str,
+        message: Message,
+        record_in_history: bool,
+        artifacts: Vec<Artifact>,
+    ) -> Option<TaskResult> {
+        let mut record = self.tasks.get_mut(task_id)?;
+
+        if record.task.status.state == "canceled" {
+            tracing::info!("Ignoring '{}' for canceled task {}", state, task_id);
+            return None;
+        }
+
+        let now = timestamp();
+
+        if record_in_history {
+            record.task.history.push(message.clone());
+        }
+
+        record.task.artifacts.extend(artifacts);
+        record.task.status = TaskStatus {
+            state: state.to_string(),
+            timestamp: now.clone(),
+            message,
+        };
+        record.transitions.push(StateTransition {
+            state: state.to_string(),
+            timestamp: now,
+        });
+
+        let task = record.task.clone();
+        let write = self.prepare_write(&mut record);
+        drop(record);
+        self.write(write);
+
+        Some(task)
+    }
+
+    /// Removes tasks not updated for the TTL, with their files. Returns how
+    /// many were removed.
+    pub fn evict_expired(&self) -> usize {
+        let Some(cutoff) = TimeDelta::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| Utc::now().checked_sub_signed(ttl))
+        else {
+            return 0;
+        };
+
+        let expired = self
+            .tasks
+            .iter()
+            .filter(|record| {
+                DateTime::parse_from_rfc3339(&record.task.status.timestamp)
+                    .is_ok_and(|updated| updated < cutoff)
+            })
+            .map(|record| record.key().clone())
+            .collect::<Vec<_>>();
+
+        for task_id in &expired {
+            self.tasks.remove(task_id);
+            self.writer.forget(task_id);
+
+            if let Some(path) = self.path(task_id)
+                && let Err(e) = fs::remove_file(&path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                tracing::warn!("Failed to remove task file {:?}: {}", path, e);
+            }
+        }
+
+        if !expired.is_empty() {
+            tracing::info!("Evicted {} expired tasks", expired.len());
+        }
+
+        expired.len()
+    }
+
+    fn path(&self, task_id: &str) -> Option<PathBuf> {
+        let dir = self.persist_dir.as_ref()?;
+
+        let file_name = task_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+
+        Some(dir.join(format!("{}.json", file_name)))
+    }
+
+    /// Serializes `record` while its entry is locked. The file is written
+    /// by `write` once the lock is released.
+    fn prepare_write(&self, record: &mut TaskRecord) -> Option<PendingWrite> {
+        let path = self.path(&record.task.id)?;
+
+        record.version += 1;
+
+        match serde_json::to_vec_pretty(record) {
+            Ok(bytes) => Some(PendingWrite {
+                task_id: record.task.id.clone(),
+                version: record.version,
+                path,
+                bytes,
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to serialize task {}: {}", record.task.id, e);
+                None
+            }
+        }
+    }
+
+    /// Writes on the blocking thread pool when there is a runtime.
+    fn write(&self, write: Option<PendingWrite>) {
+        let Some(write) = write else {
+            return;
+        };
+
+        let writer = Arc::clone(&self.writer);
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || writer.write(write));
+            }
+            Err(_) => writer.write(write),
+        }
+    }
+}
+
+struct PendingWrite {
+    task_id: String,
+    version: u64,
+    path: PathBuf,
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn forget(&self, task_id: &str) {
+        self.written
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(task_id);
+    }
+
+    fn write(&self, write: PendingWrite) {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+
+        if written
+            .get(&write.task_id)
+            .is_some_and(|version| *version >= write.version)
+        {
+            return;
+        }
+
+        if let Err(e) = fs::write(&write.path, &write.bytes) {
+            tracing::warn!("Failed to persist task {}: {}", write.task_id, e);
+            return;
+        }
+
+        written.insert(write.task_id, write.version);
+    }
+}
+
+fn check_open(record: &TaskRecord, context_id: &str) -> Result<(), TaskStoreError> {
+    // Don't reveal that another context's task exists.
+    if record.task.context_id != context_id {
+        return Err(TaskStoreError::NotFound(record.task.id.clone()));
+    }
+
+    let state = &record.task.status.state;
+
+    if TERMINAL_STATES.contains(&state.as_str()) {
+        return Err(TaskStoreError::Closed(state.clone()));
+    }
+
+    // A second message while the task runs would start a second pipeline
+    // racing on the same task.
+    if state != "input-required" {
+        return Err(TaskStoreError::Busy(state.clone()));
+    }
+
+    Ok(())
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
@@ -1,14 +1,17 @@
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDate, Utc};
+use serde_json::json;
 use uuid::Uuid;
 
 use crate::models::{
     a2a::{A2ARequest, Artifact, MessagePart},
-    query::QueryParams,
-    repository::TrendingRepo,
+    normalize::language_search_name,
+    query::{QueryParams, SortBy},
+    repository::{RepoDetails, SearchResult, TrendingData, TrendingRepo},
+    search_query::{Range, SearchQuery},
 };
 
-pub fn calculate_date_filters(timeframe: &String) -> (String, String) {
-    let created_days = match timeframe.as_str() {
+pub fn calculate_date_filters(timeframe: &str) -> (NaiveDate, NaiveDate) {
+    let created_days = match timeframe {
         "day" => 1,
         "week" => 7,
         "month" => 30,
@@ -17,48 +20,60 @@ pub fn calculate_date_filters(timeframe: &String) -> (String, String) {
         _ => 7,
     };
 
-    let pushed_days = match timeframe.as_str() {
+    let pushed_days = match timeframe {
         "day" | "week" | "month" => created_days,
         "quarter" => 90,
         "year" => 180,
         _ => created_days,
     };
 
-    let now = Utc::now();
-    let created_date = (now - Duration::days(created_days))
-        .format("%Y-%m-%d")
-        .to_string();
-    let pushed_date = (now - Duration::days(pushed_days))
-        .format("%Y-%m-%d")
-        .to_string();
+    let today = Utc::now().date_naive();
 
-    (created_date, pushed_date)
+    (
+        today - Duration::days(created_days),
+        today - Duration::days(pushed_days),
+    )
 }
 
-pub fn build_base_query_parts(params: &QueryParams) -> Vec<String> {
-    let (created_date, pushed_date) = if params.uses_specific_dates() {
-        (
-            params.created_after.clone().unwrap(),
-            params.pushed_after.clone().unwrap(),
-        )
-    } else {
-        calculate_date_filters(&params.timeframe)
+/// The date, language and star qualifiers every search for `params` shares.
+pub fn build_base_query(params: &QueryParams) -> SearchQuery {
+    let specific_dates = match (&params.created_after, &params.pushed_after) {
+        (Some(created), Some(pushed)) => parse_date(created).zip(parse_date(pushed)),
+        _ => None,
     };
 
-    let mut query_parts = vec![
-        format!("created:>{}", created_date),
-        format!("pushed:>{}", pushed_date),
-    ];
+    let (created_date, pushed_date) =
+        specific_dates.unwrap_or_else(|| calculate_date_filters(&params.timeframe));
+
+    let mut query = SearchQuery::new();
+
+    // A velocity ranking should surface older repositories that are taking
+    // off, so only an explicit date limits the creation date.
+    if params.sort == SortBy::Stars || specific_dates.is_some() {
+        query = query.created(Range::Greater(created_date));
+    }
+
+    query = query.pushed(Range::Greater(pushed_date));
 
     if let Some(ref language) = params.language {
-        query_parts.push(format!("language:{}", language));
+        query = query.language(&language_search_name(language));
     }
 
     if params.min_stars > 0 {
-        query_parts.push(format!("stars:>={}", params.min_stars));
+        query = query.stars(Range::AtLeast(params.min_stars));
     }
 
-    query_parts
+    query
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+        Ok(date) => Some(date),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid date '{}', using the timeframe", date);
+            None
+        }
+    }
 }
 
 pub fn format_trending_message(repos: &[TrendingRepo], params: QueryParams) -> String {
@@ -84,11 +99,33 @@ pub fn format_trending_message(repos: &[TrendingRepo], params: QueryParams) -> S
 
         message.push_str(&format!(">> {}\n", repo.description));
 
+        if let Some(blurb) = &repo.blurb {
+            message.push_str(&format!("**SUMMARY:** {}\n", blurb.summary));
+            message.push_str(&format!("**WHY IT'S NOTABLE:** {}\n", blurb.why_notable));
+        }
+
         message.push_str(&format!(
-            "**STARS:** {} | **LANGUAGE:** {}\n",
+            "**STARS:** {} | **LANGUAGE:** {}",
             stars, repo.language
         ));
 
+        if let Some(details) = &repo.details
+            && details.stars_last_week > 0
+        {
+            message.push_str(&format!(
+                " | **THIS WEEK:** {}",
+                format_stars_last_week(details)
+            ));
+        }
+
+        if let Some(velocity) = &repo.velocity
+            && velocity.stars_per_day > 0.0
+        {
+            message.push_str(&format!(" | **STARS/DAY:** +{}", velocity.stars_per_day));
+        }
+
+        message.push('\n');
+
         if i < repos.len() - 1 {
             message.push_str("---\n");
         }
@@ -102,6 +139,140 @@ pub fn format_trending_message(repos: &[TrendingRepo], params: QueryParams) -> S
     message
 }
 
+pub fn format_repo_details(repo: &TrendingRepo) -> String {
+    let mut message = format!("### [{}]({})\n", repo.name, repo.url);
+
+    message.push_str(&format!(">> {}\n", repo.description));
+
+    if let Some(blurb) = &repo.blurb {
+        message.push_str(&format!("**SUMMARY:** {}\n", blurb.summary));
+        message.push_str(&format!("**WHY IT'S NOTABLE:** {}\n", blurb.why_notable));
+    }
+
+    message.push_str(&format!(
+        "**STARS:** {} | **FORKS:** {} | **LANGUAGE:** {}\n",
+        format_number(repo.stars),
+        format_number(repo.forks),
+        repo.language
+    ));
+    message.push_str(&format!("**CREATED:** {}\n", format_date(&repo.created_at)));
+
+    if !repo.topics.is_empty() {
+        message.push_str(&format!("**TOPICS:** {}\n", repo.topics.join(", ")));
+    }
+
+    if let Some(details) = &repo.details {
+        message.push_str(&format_details(details));
+    }
+
+    if let Some(velocity) = &repo.velocity {
+        message.push_str(&format!(
+            "**STAR VELOCITY:** {:+} a day, {:+} this week\n",
+            velocity.stars_per_day, velocity.stars_last_week
+        ));
+    }
+
+    message
+}
+
+pub fn format_comparison(repos: &[TrendingRepo]) -> String {
+    let row = |label: &str, value: &dyn Fn(&TrendingRepo) -> String| {
+        let cells = repos.iter().map(value).collect::<Vec<_>>().join(" | ");
+        format!("| **{}** | {} |\n", label, cells)
+    };
+
+    let mut message = String::new();
+
+    message.push_str("**REPOSITORY COMPARISON**\n\n");
+
+    let header = repos
+        .iter()
+        .map(|repo| format!("[{}]({})", repo.name, repo.url))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    message.push_str(&format!("| | {} |\n", header));
+    message.push_str(&format!("|---|{}\n", "---|".repeat(repos.len())));
+
+    message.push_str(&row("Stars", &|repo| format_number(repo.stars)));
+    message.push_str(&row("Forks", &|repo| format_number(repo.forks)));
+    message.push_str(&row("Language", &|repo| repo.language.clone()));
+    message.push_str(&row("Created", &|repo| format_date(&repo.created_at)));
+    message.push_str(&row("Topics", &|repo| match repo.topics.is_empty() {
+        true => "-".to_string(),
+        false => repo
+            .topics
+            .iter()
+            .take(3)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", "),
+    }));
+
+    if let Some(top) = repos.iter().max_by_key(|repo| repo.stars) {
+        message.push_str(&format!("\n**MOST STARRED:** {}\n", top.name));
+    }
+
+    message
+}
+
+pub fn format_help_message() -> String {
+    [
+        "**GITPULSE**",
+        "",
+        "I find trending GitHub repositories. Try:",
+        "- **Trending search:** \"Top 5 Rust repos this week\", \"AI projects since October 1st 2025\"",
+        "- **Follow-ups:** \"same but Python\", \"show 5 more\"",
+        "- **Repository details:** \"Tell me about tokio-rs/axum\"",
+        "- **Comparisons:** \"Compare tokio-rs/axum vs actix/actix-web\"",
+        "- **Digests:** \"Subscribe to the daily digest\", \"Send me the weekly roundup\", \"Unsubscribe\"",
+    ]
+    .join("\n")
+}
+
+fn format_details(details: &RepoDetails) -> String {
+    let mut message = format!(
+        "**LICENSE:** {} | **OPEN ISSUES:** {}\n",
+        details.license.as_deref().unwrap_or("None"),
+        format_number(details.open_issues)
+    );
+
+    if let Some(release) = &details.latest_release {
+        match &release.published_at {
+            Some(published_at) => message.push_str(&format!(
+                "**LATEST RELEASE:** {} ({})\n",
+                release.tag,
+                format_date(published_at)
+            )),
+            None => message.push_str(&format!("**LATEST RELEASE:** {}\n", release.tag)),
+        }
+    }
+
+    if let Some(share) = details.language_share {
+        message.push_str(&format!("**PRIMARY LANGUAGE SHARE:** {}%\n", share));
+    }
+
+    if details.stars_last_week > 0 {
+        message.push_str(&format!(
+            "**STARS THIS WEEK:** {}\n",
+            format_stars_last_week(details)
+        ));
+    }
+
+    message
+}
+
+fn format_date(timestamp: &str) -> String {
+    timestamp.split('T').next().unwrap_or(timestamp).to_string()
+}
+
+/// A saturated count is a lower bound and shows as "100+".
+fn format_stars_last_week(details: &RepoDetails) -> String {
+    match details.stars_last_week_saturated {
+        true => format!("{}+", details.stars_last_week),
+        false => format!("+{}", format_number(details.stars_last_week)),
+    }
+}
+
 fn format_number(num: u32) -> String {
     if num >= 1_000_000 {
         format!("{:.1}M", num as f64 / 1_000_000.0)
@@ -125,7 +296,7 @@ pub fn extract_user_query(request: &A2ARequest) -> Option<String> {
             if let Some(text) = entry.get("text").and_then(|v| v.as_str()) {
                 let trimmed = text.trim();
 
-                let is_user_query = trimmed.starts_with("<p>") || trimmed.len() > 0;
+                let is_user_query = trimmed.starts_with("<p>") || !trimmed.is_empty();
 
                 if is_user_query {
                     let cleaned = trimmed
@@ -156,16 +327,44 @@ pub fn extract_user_query(request: &A2ARequest) -> Option<String> {
 }
 
 pub fn create_artifacts(response_text: String) -> Vec<Artifact> {
-    let mut artifacts = Vec::new();
-
-    artifacts.push(Artifact {
+    vec![Artifact {
         artifact_id: Uuid::new_v4().to_string(),
         name: "gitpulseAgentResponse".to_string(),
         parts: vec![MessagePart::Text {
             kind: "text".to_string(),
             text: response_text,
         }],
-    });
+    }]
+}
 
-    artifacts
+pub fn create_data_artifact(
+    params: &QueryParams,
+    result: &SearchResult,
+    parsed_by: Option<&str>,
+) -> Artifact {
+    let data = TrendingData {
+        params: params.clone(),
+        parsed_by: parsed_by.map(str::to_string),
+        result: result.clone(),
+    };
+
+    Artifact {
+        artifact_id: Uuid::new_v4().to_string(),
+        name: "gitpulseTrendingData".to_string(),
+        parts: vec![MessagePart::Data {
+            kind: "data".to_string(),
+            data: vec![json!(data)],
+        }],
+    }
+}
+
+pub fn create_repository_artifact(repos: &[TrendingRepo]) -> Artifact {
+    Artifact {
+        artifact_id: Uuid::new_v4().to_string(),
+        name: "gitpulseRepositoryData".to_string(),
+        parts: vec![MessagePart::Data {
+            kind: "data".to_string(),
+            data: repos.iter().map(|repo| json!(repo)).collect(),
+        }],
+    }
 }
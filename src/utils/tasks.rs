@@ -1,17 +1,22 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use futures::future::join_all;
 use uuid::Uuid;
 
 use crate::{
     api::state::AppState,
     models::{
         a2a::{A2AResponse, Artifact, Message, MessagePart},
-        query::QueryParams,
+        query::{MAX_COUNT, QueryParams, SortBy},
     },
-    utils::helpers::{create_artifacts, format_trending_message},
+    services::subscription_store::{DigestFrequency, Subscription},
+    utils::helpers::{create_artifacts, create_data_artifact, format_trending_message},
 };
 
+/// Most tracked repositories one snapshot run fetches individually.
+const MAX_SNAPSHOT_REFRESH: usize = 300;
+
 pub async fn send_daily_digest(state: Arc<AppState>) -> Result<()> {
     let params = QueryParams {
         language: None,
@@ -23,12 +28,18 @@ pub async fn send_daily_digest(state: Arc<AppState>) -> Result<()> {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
-    let repos = state.github_client.search_with_params(&params).await?;
-    let message = format_trending_message(&repos, params);
+    let result = state.github_client.search_with_params(&params).await?;
+    let data_artifact = create_data_artifact(&params, &result, None);
+
+    let message = format_trending_message(&result.repos, params);
+
+    let mut artifacts = create_artifacts(message.clone());
+    artifacts.push(data_artifact);
 
-    let artifacts = create_artifacts(message.clone());
+    notify_subscribers(&state, DigestFrequency::Daily, &message, &artifacts).await;
 
     call_external_webhook(
         &state.config.external_webhook_url,
@@ -52,12 +63,18 @@ pub async fn send_weekly_roundup(state: Arc<AppState>) -> Result<()> {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
-    let repos = state.github_client.search_with_params(&params).await?;
-    let message = format_trending_message(&repos, params);
+    let result = state.github_client.search_with_params(&params).await?;
+    let data_artifact = create_data_artifact(&params, &result, None);
 
-    let artifacts = create_artifacts(message.clone());
+    let message = format_trending_message(&result.repos, params);
+
+    let mut artifacts = create_artifacts(message.clone());
+    artifacts.push(data_artifact);
+
+    notify_subscribers(&state, DigestFrequency::Weekly, &message, &artifacts).await;
 
     call_external_webhook(
         &state.config.external_webhook_url,
@@ -70,6 +87,94 @@ pub async fn send_weekly_roundup(state: Arc<AppState>) -> Result<()> {
     Ok(())
 }
 
+/// Snapshots the current trending candidates, new and established, then
+/// the other repositories tracked during the last week.
+pub async fn snapshot_stars(state: Arc<AppState>) -> Result<()> {
+    for sort in [SortBy::Stars, SortBy::Velocity] {
+        let params = QueryParams {
+            timeframe: "week".to_string(),
+            count: MAX_COUNT,
+            min_stars: 50,
+            sort,
+            ..QueryParams::default()
+        };
+
+        if let Err(e) = state.github_client.search_with_params(&params).await {
+            tracing::warn!("Failed to search snapshot candidates: {}", e);
+        }
+    }
+
+    let recorded = state
+        .github_client
+        .refresh_snapshots(MAX_SNAPSHOT_REFRESH)
+        .await?;
+
+    tracing::info!("Star snapshots taken ({} refreshed)", recorded);
+    Ok(())
+}
+
+async fn notify_subscribers(
+    state: &AppState,
+    frequency: DigestFrequency,
+    message: &str,
+    artifacts: &[Artifact],
+) {
+    let subscribers = state.subscription_store.subscribers(frequency);
+
+    if subscribers.is_empty() {
+        return;
+    }
+
+    join_all(
+        subscribers
+            .iter()
+            .map(|subscription| notify_subscriber(state, subscription, message, artifacts)),
+    )
+    .await;
+
+    tracing::info!(
+        "Sent the {} to {} subscribers",
+        frequency.as_str(),
+        subscribers.len()
+    );
+}
+
+async fn notify_subscriber(
+    state: &AppState,
+    subscription: &Subscription,
+    message: &str,
+    artifacts: &[Artifact],
+) {
+    let task_id = Uuid::new_v4().to_string();
+    let request_message = Message::agent(
+        format!("Scheduled {}", subscription.frequency.as_str()),
+        &task_id,
+        None,
+    );
+
+    if let Err(e) = state
+        .task_store
+        .submit(&task_id, &subscription.context_id, &request_message)
+    {
+        tracing::error!("Failed to record scheduled task {}: {}", task_id, e);
+        return;
+    }
+
+    let Some(task) = state.task_store.complete(
+        &task_id,
+        "completed",
+        Message::agent(message.to_string(), &task_id, None),
+        artifacts.to_vec(),
+    ) else {
+        return;
+    };
+
+    state
+        .push_notifier
+        .deliver(&subscription.push_config, &task)
+        .await;
+}
+
 async fn call_external_webhook(
     webhook_url: &str,
     message: String,
@@ -86,6 +191,7 @@ async fn call_external_webhook(
         }],
         message_id: Uuid::new_v4().to_string(),
         task_id: Some(Uuid::new_v4().to_string()),
+        context_id: None,
         telex_metadata: None,
     };
 
@@ -0,0 +1,56 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+};
+use gitpulse::models::repository::TrendingRepo;
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// What a mock server saw of every request, by default the query string.
+pub type Captured<T = HashMap<String, String>> = Arc<Mutex<Vec<T>>>;
+
+/// A repository fixture. Tests that need other values override them with
+/// struct update syntax.
+pub fn repo(name: &str, stars: u32) -> TrendingRepo {
+    TrendingRepo {
+        id: 1,
+        name: name.to_string(),
+        description: "No description".to_string(),
+        url: format!("https://github.com/{}", name),
+        language: "Rust".to_string(),
+        stars,
+        forks: 10,
+        topics: vec![],
+        created_at: "2025-10-30T10:30:00Z".to_string(),
+        blurb: None,
+        details: None,
+        velocity: None,
+    }
+}
+
+/// Serves `router` on a free local port and returns its base URL.
+pub async fn spawn_mock(router: Router) -> (String, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    (format!("http://{}", addr), server)
+}
+
+/// A repository search that records its query and finds nothing.
+pub async fn search(
+    State(captured): State<Captured>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    captured.lock().unwrap().push(query);
+
+    Json(json!({ "total_count": 0, "items": [] }))
+}
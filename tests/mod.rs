@@ -1,3 +1,26 @@
+pub mod common;
 pub mod test_a2a;
+pub mod test_agent_card;
+pub mod test_circuit_breaker;
 pub mod test_client_search;
+pub mod test_conditional_requests;
+pub mod test_config;
+pub mod test_conversation_store;
+pub mod test_enricher;
+pub mod test_eval;
+pub mod test_github_app;
+pub mod test_github_quota;
+pub mod test_github_tokens;
+pub mod test_graphql;
+pub mod test_intent;
+pub mod test_llm_provider;
+pub mod test_normalize;
+pub mod test_parsed_query;
+pub mod test_prompts;
+pub mod test_push_notifier;
 pub mod test_query_parser;
+pub mod test_rpc;
+pub mod test_rule_parser;
+pub mod test_search_query;
+pub mod test_snapshots;
+pub mod test_task_store;
@@ -1,8 +1,15 @@
 use gitpulse::{
-    models::a2a::{A2ARequest, A2AResponse, Message, MessagePart},
-    utils::helpers::create_artifacts,
+    api::rpc::RpcError,
+    models::{
+        a2a::{A2ARequest, A2AResponse, Message, MessagePart, RequestId},
+        query::QueryParams,
+        repository::{SearchResult, TrendingData, TrendingRepo},
+    },
+    utils::helpers::{create_artifacts, create_data_artifact},
 };
 
+use crate::common::repo;
+
 #[test]
 fn test_default_configuration() {
     let json = r#"{
@@ -35,6 +42,7 @@ fn test_success_response() {
         parts: vec![],
         message_id: "test-123".to_string(),
         task_id: Some("test-123".to_string()),
+        context_id: None,
         telex_metadata: None,
     };
     let response_text = "Here are trending repos...".to_string();
@@ -61,6 +69,7 @@ fn test_success_response_without_task_id() {
         parts: vec![],
         message_id: "test-123".to_string(),
         task_id: Some("test-123".to_string()),
+        context_id: None,
         telex_metadata: None,
     };
     let response_text = "Here are trending repos...".to_string();
@@ -84,7 +93,10 @@ fn test_success_response_without_task_id() {
 
 #[test]
 fn test_serialize_error_response() {
-    let response = A2AResponse::error(-32601, "Method not found".to_string());
+    let response = A2AResponse::<serde_json::Value>::error(
+        Some(RequestId::from("req-789")),
+        RpcError::MethodNotFound("tasks/unknown".to_string()),
+    );
 
     let json = serde_json::to_string_pretty(&response).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
@@ -104,6 +116,7 @@ fn test_response_round_trip() {
         parts: vec![],
         message_id: "test-123".to_string(),
         task_id: Some("test-123".to_string()),
+        context_id: None,
         telex_metadata: None,
     };
     let response_text = "Round-trip test".to_string();
@@ -134,3 +147,104 @@ fn test_response_round_trip() {
         }
     }
 }
+
+#[test]
+fn test_stream_status_update() {
+    let response = A2AResponse::status_update(
+        &RequestId::from("req-stream"),
+        "task-stream",
+        "context-stream",
+        "working",
+        "Parsing query".to_string(),
+        false,
+    );
+
+    let parsed = serde_json::to_value(&response).unwrap();
+
+    assert_eq!(parsed["id"], "req-stream");
+    assert_eq!(parsed["result"]["kind"], "status-update");
+    assert_eq!(parsed["result"]["taskId"], "task-stream");
+    assert_eq!(parsed["result"]["contextId"], "context-stream");
+    assert_eq!(parsed["result"]["status"]["state"], "working");
+    assert_eq!(parsed["result"]["final"], false);
+}
+
+#[test]
+fn test_stream_artifact_update() {
+    let artifact = create_artifacts("chunk".to_string()).remove(0);
+
+    let response = A2AResponse::artifact_update(
+        &RequestId::from("req-stream"),
+        "task-stream",
+        "context-stream",
+        artifact,
+        true,
+        true,
+    );
+
+    let parsed = serde_json::to_value(&response).unwrap();
+
+    assert_eq!(parsed["result"]["kind"], "artifact-update");
+    assert_eq!(parsed["result"]["append"], true);
+    assert_eq!(parsed["result"]["lastChunk"], true);
+    assert_eq!(parsed["result"]["artifact"]["parts"][0]["text"], "chunk");
+}
+
+#[test]
+fn test_success_response_echoes_context_id() {
+    let message = Message {
+        kind: "message".to_string(),
+        role: "user".to_string(),
+        parts: vec![],
+        message_id: "message-222".to_string(),
+        task_id: None,
+        context_id: Some("context-222".to_string()),
+        telex_metadata: None,
+    };
+    let response_text = "Here are trending repos...".to_string();
+
+    let response = A2AResponse::success(
+        "req-222".to_string(),
+        None,
+        response_text.clone(),
+        create_artifacts(response_text),
+        &message,
+    );
+
+    let result = response.result.unwrap();
+    assert_eq!(result.context_id, "context-222");
+    assert_eq!(result.history[0].context_id.as_deref(), Some("context-222"));
+}
+
+#[test]
+fn test_data_artifact() {
+    let params = QueryParams {
+        language: Some("rust".to_string()),
+        ..QueryParams::default()
+    };
+    let result = SearchResult {
+        queries: vec!["created:>2025-10-23+language:rust".to_string()],
+        total_count: 42,
+        repos: vec![TrendingRepo {
+            forks: 7,
+            topics: vec!["cli".to_string()],
+            ..repo("test/repo", 100)
+        }],
+    };
+
+    let artifact = create_data_artifact(&params, &result, Some("gemini"));
+    assert_eq!(artifact.name, "gitpulseTrendingData");
+
+    let MessagePart::Data { kind, data } = &artifact.parts[0] else {
+        panic!("Expected a data part");
+    };
+    assert_eq!(kind, "data");
+    assert_eq!(data[0]["total_count"], 42);
+    assert_eq!(data[0]["params"]["language"], "rust");
+    assert_eq!(data[0]["repos"][0]["forks"], 7);
+    assert_eq!(data[0]["parsed_by"], "gemini");
+
+    let parsed = serde_json::from_value::<TrendingData>(data[0].clone()).unwrap();
+    assert_eq!(parsed.result.queries, result.queries);
+    assert_eq!(parsed.result.repos[0].topics, vec!["cli"]);
+}
@@ -0,0 +1,108 @@
+use gitpulse::{
+    config::settings::Config,
+    models::{a2a::SUPPORTED_METHODS, agent_card::AgentCard},
+};
+
+fn test_config(public_url: Option<&str>) -> Config {
+    Config {
+        llm_provider: "gemini".to_string(),
+        github_search_url: "https://api.github.com/search/repositories".to_string(),
+        github_access_token: Some("token".to_string()),
+        github_access_tokens: None,
+        github_app_id: None,
+        github_app_installation_id: None,
+        github_app_private_key_path: None,
+        github_search_backend: None,
+        external_webhook_url: "https://example.com/webhook".to_string(),
+        llm_api_key: "key".to_string(),
+        llm_model: "gemini-2.5-flash".to_string(),
+        llm_base_url: None,
+        llm_json_mode: None,
+        llm_fallbacks: None,
+        llm_timeout_secs: None,
+        anthropic_api_key: None,
+        gemini_api_key: None,
+        openai_api_key: None,
+        system_prompt_path: None,
+        few_shot_examples_path: None,
+        prompt_reload_secs: None,
+        admin_token: None,
+        cache_ttl: 60,
+        host: "0.0.0.0".to_string(),
+        port: 8000,
+        public_url: public_url.map(|url| url.to_string()),
+        cors_allowed_origins: "http://localhost".to_string(),
+        rate_limit_ms: 60,
+        task_store_dir: None,
+        task_ttl_secs: None,
+        snapshot_db_path: None,
+        enrich_results: None,
+        topic_synonyms_path: None,
+        topic_match: None,
+        search_fanout_budget: None,
+    }
+}
+
+#[test]
+fn test_agent_card_from_config() {
+    let card = AgentCard::from_config(&test_config(None));
+
+    assert_eq!(card.name, "GitPulse");
+    assert_eq!(card.url, "http://0.0.0.0:8000/trending");
+    assert_eq!(card.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(card.supported_methods, SUPPORTED_METHODS);
+    assert!(card.capabilities.streaming);
+    assert!(card.capabilities.push_notifications);
+
+    let skills = card
+        .skills
+        .iter()
+        .map(|skill| skill.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        skills,
+        vec!["trending-search", "repo-details", "scheduled-digests"]
+    );
+}
+
+#[test]
+fn test_agent_card_public_url() {
+    let card = AgentCard::from_config(&test_config(Some("https://gitpulse.example.com/")));
+    assert_eq!(card.url, "https://gitpulse.example.com/trending");
+
+    let json = serde_json::to_value(&card).unwrap();
+    assert_eq!(json["protocolVersion"], "0.3.0");
+    assert_eq!(json["capabilities"]["pushNotifications"], true);
+    assert_eq!(json["defaultInputModes"][0], "text/plain");
+    assert_eq!(
+        json["defaultOutputModes"],
+        serde_json::json!(["text/plain", "application/json"])
+    );
+    assert!(json.get("securitySchemes").is_none());
+}
+
+#[test]
+fn test_agent_card_declares_admin_scheme() {
+    let config = Config {
+        admin_token: Some("secret".to_string()),
+        ..test_config(None)
+    };
+
+    let json = serde_json::to_value(AgentCard::from_config(&config)).unwrap();
+
+    let scheme = &json["securitySchemes"]["adminToken"];
+    assert_eq!(scheme["type"], "http");
+    assert_eq!(scheme["scheme"], "bearer");
+    assert!(json["security"].as_array().unwrap().is_empty());
+    assert!(!json.to_string().contains("secret\""));
+
+    for admin_token in ["", "  "] {
+        let config = Config {
+            admin_token: Some(admin_token.to_string()),
+            ..test_config(None)
+        };
+
+        let json = serde_json::to_value(AgentCard::from_config(&config)).unwrap();
+        assert!(json.get("securitySchemes").is_none());
+    }
+}
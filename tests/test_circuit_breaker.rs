@@ -0,0 +1,71 @@
+use std::{thread::sleep, time::Duration};
+
+use gitpulse::services::circuit_breaker::CircuitBreaker;
+
+#[test]
+fn test_opens_after_threshold() {
+    let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+    assert!(!breaker.record_failure());
+    assert!(!breaker.record_failure());
+    assert!(breaker.allow());
+
+    assert!(breaker.record_failure());
+    assert!(breaker.is_open());
+}
+
+#[test]
+fn test_success_resets_failures() {
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+    breaker.record_failure();
+    breaker.record_success();
+    breaker.record_failure();
+
+    assert!(breaker.allow());
+}
+
+#[test]
+fn test_half_open_after_cooldown() {
+    let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+
+    assert!(breaker.record_failure());
+    assert!(breaker.is_open());
+
+    sleep(Duration::from_millis(30));
+    assert!(breaker.allow());
+
+    // A failed trial re-opens the breaker straight away
+    assert!(breaker.record_failure());
+    assert!(breaker.is_open());
+
+    sleep(Duration::from_millis(30));
+    breaker.record_success();
+    assert!(breaker.allow());
+}
+
+#[test]
+fn test_half_open_lets_one_trial_through() {
+    let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+
+    breaker.record_failure();
+    sleep(Duration::from_millis(30));
+    assert!(!breaker.is_open());
+
+    assert!(breaker.allow());
+    assert!(!breaker.allow());
+    assert!(breaker.is_open());
+
+    breaker.record_success();
+    assert!(breaker.allow());
+    assert!(breaker.allow());
+
+    breaker.record_failure();
+    sleep(Duration::from_millis(30));
+
+    assert!(breaker.allow());
+    assert!(!breaker.allow());
+
+    assert!(breaker.record_failure());
+    assert!(!breaker.allow());
+}
@@ -3,11 +3,13 @@ use std::env;
 use anyhow::{Ok, Result};
 use dotenvy::dotenv;
 use gitpulse::{
-    models::{query::QueryParams, repository::TrendingRepo},
+    models::query::{QueryParams, SortBy},
     services::github::GitHubClient,
     utils::helpers::format_trending_message,
 };
 
+use crate::common::repo;
+
 fn create_test_client() -> Result<GitHubClient> {
     dotenv().ok();
 
@@ -17,13 +19,7 @@ fn create_test_client() -> Result<GitHubClient> {
 
 #[test]
 fn test_format_message() {
-    let repos = vec![TrendingRepo {
-        name: "test/repo".to_string(),
-        description: "A test repo".to_string(),
-        url: "https://github.com/test/repo".to_string(),
-        language: "Rust".to_string(),
-        stars: 100,
-    }];
+    let repos = vec![repo("test/repo", 100)];
 
     let params = QueryParams {
         language: None,
@@ -35,6 +31,7 @@ fn test_format_message() {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
     let message = format_trending_message(&repos, params);
@@ -52,7 +49,8 @@ async fn search_and_verify(
     let repos = client
         .search_with_params(&params)
         .await
-        .expect("Search with params failed");
+        .expect("Search with params failed")
+        .repos;
 
     assert!(
         repos.len() >= expected_min_count,
@@ -82,6 +80,7 @@ async fn test_search_with_params() -> Result<()> {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
     search_and_verify(params, 1, "Rust repos").await
@@ -99,6 +98,7 @@ async fn test_search_with_topics() -> Result<()> {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
     search_and_verify(params, 1, "Python AI/ML repos").await
@@ -116,6 +116,7 @@ async fn test_search_with_invalid_topics() -> Result<()> {
         created_after: None,
         pushed_after: None,
         has_specific_date: false,
+        sort: SortBy::Stars,
     };
 
     search_and_verify(params, 1, "Python repos (with invalid topic)").await
@@ -0,0 +1,112 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use gitpulse::{
+    models::{query::QueryParams, repository::SearchResult},
+    services::{cache::ConditionalCache, github::GitHubClient},
+};
+use serde_json::json;
+
+use crate::common::{Captured, spawn_mock};
+
+const ETAG: &str = "\"v1\"";
+const URL: &str = "https://api.github.com/search/repositories?q=rust";
+
+async fn search(State(captured): State<Captured<Option<String>>>, headers: HeaderMap) -> Response {
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    captured.lock().unwrap().push(if_none_match.clone());
+
+    if if_none_match.as_deref() == Some(ETAG) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, ETAG)]).into_response();
+    }
+
+    (
+        [(header::ETAG, ETAG)],
+        Json(json!({
+            "total_count": 1,
+            "items": [{
+                "id": 1,
+                "name": "owner/repo",
+                "full_name": "owner/repo",
+                "owner": { "login": "owner" },
+                "html_url": "https://github.com/owner/repo",
+                "description": null,
+                "stargazers_count": 42,
+                "forks_count": 0,
+                "language": "Rust",
+                "topics": [],
+                "created_at": "2025-10-30T10:30:00Z",
+                "updated_at": "2025-10-30T10:30:00Z"
+            }]
+        })),
+    )
+        .into_response()
+}
+
+#[tokio::test]
+async fn test_search_revalidates_with_etag() -> Result<()> {
+    let captured: Captured<Option<String>> = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let client = GitHubClient::new(None, &format!("{}/search/repositories", base_url))?;
+
+    let first = client.search_with_params(&QueryParams::default()).await?;
+    let second = client.search_with_params(&QueryParams::default()).await?;
+
+    assert_eq!(
+        captured.lock().unwrap().clone(),
+        vec![None, Some(ETAG.to_string())]
+    );
+
+    assert_eq!(second.repos.len(), 1);
+    assert_eq!(second.repos[0].name, first.repos[0].name);
+    assert_eq!(second.repos[0].stars, 42);
+
+    assert_eq!(client.quota().not_modified, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_conditional_cache_keeps_validated_results() {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(reqwest::header::ETAG, ETAG.parse().unwrap());
+
+    let result = SearchResult {
+        queries: vec![],
+        total_count: 0,
+        repos: vec![],
+    };
+
+    let cache = ConditionalCache::default();
+
+    cache.set(URL, &reqwest::header::HeaderMap::new(), &result);
+    assert!(cache.get(URL).is_none());
+
+    cache.set(URL, &headers, &result);
+    assert_eq!(cache.get(URL).unwrap().etag.as_deref(), Some(ETAG));
+
+    assert_eq!(cache.evict_expired(), 0);
+
+    let cache = ConditionalCache::new(0);
+
+    cache.set(URL, &headers, &result);
+    assert_eq!(cache.evict_expired(), 1);
+    assert!(cache.get(URL).is_none());
+}
@@ -0,0 +1,53 @@
+use gitpulse::config::settings::Config;
+
+fn env(extra: &[(&str, &str)]) -> Vec<(String, String)> {
+    [
+        ("LLM_PROVIDER", "rules"),
+        (
+            "GITHUB_SEARCH_URL",
+            "https://api.github.com/search/repositories",
+        ),
+        ("EXTERNAL_WEBHOOK_URL", "https://example.com/webhook"),
+        ("LLM_API_KEY", ""),
+        ("LLM_MODEL", ""),
+        ("CACHE_TTL", "60"),
+        ("HOST", "0.0.0.0"),
+        ("PORT", "8000"),
+        ("CORS_ALLOWED_ORIGINS", "http://localhost"),
+        ("RATE_LIMIT_MS", "60"),
+    ]
+    .iter()
+    .chain(extra)
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect()
+}
+
+#[test]
+fn test_app_only_config_without_access_token() {
+    let config = envy::from_iter::<_, Config>(env(&[
+        ("GITHUB_APP_ID", "1234"),
+        ("GITHUB_APP_INSTALLATION_ID", "42"),
+    ]))
+    .unwrap();
+
+    assert!(config.github_access_token.is_none());
+    assert_eq!(config.github_app_installation_id, Some(42));
+}
+
+#[test]
+fn test_empty_values_are_unset() {
+    let config = envy::from_iter::<_, Config>(env(&[
+        ("GITHUB_ACCESS_TOKEN", "ghp_token"),
+        ("GITHUB_APP_INSTALLATION_ID", ""),
+        ("LLM_TIMEOUT_SECS", " "),
+        ("ENRICH_RESULTS", ""),
+    ]))
+    .unwrap();
+
+    assert_eq!(config.github_access_token.as_deref(), Some("ghp_token"));
+    assert_eq!(config.github_app_installation_id, None);
+    assert_eq!(config.llm_timeout_secs, None);
+    assert_eq!(config.enrich_results, None);
+
+    assert!(envy::from_iter::<_, Config>(env(&[("GITHUB_APP_INSTALLATION_ID", "abc")])).is_err());
+}
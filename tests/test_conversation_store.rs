@@ -0,0 +1,132 @@
+use gitpulse::{
+    models::query::{FollowUp, QueryParams},
+    services::conversation_store::ConversationStore,
+};
+
+use crate::common::repo;
+
+#[test]
+fn test_record_and_append_shown_repos() {
+    let store = ConversationStore::new(60);
+
+    assert!(store.get("context-1").is_none());
+
+    let params = QueryParams {
+        language: Some("rust".to_string()),
+        ..QueryParams::default()
+    };
+
+    store.record(
+        "context-1",
+        &params,
+        &[repo("a/one", 100), repo("b/two", 100)],
+        false,
+    );
+    store.record("context-1", &params, &[repo("c/three", 100)], true);
+
+    let conversation = store.get("context-1").unwrap();
+    assert_eq!(conversation.params.language.as_deref(), Some("rust"));
+    assert_eq!(conversation.shown, vec!["a/one", "b/two", "c/three"]);
+
+    let refined = QueryParams {
+        timeframe: "month".to_string(),
+        ..params
+    };
+
+    store.record("context-1", &refined, &[repo("d/four", 100)], false);
+
+    let conversation = store.get("context-1").unwrap();
+    assert_eq!(conversation.params.timeframe, "month");
+    assert_eq!(conversation.shown, vec!["d/four"]);
+}
+
+#[test]
+fn test_expired_conversation() {
+    let store = ConversationStore::new(0);
+
+    store.record(
+        "context-2",
+        &QueryParams::default(),
+        &[repo("a/one", 100)],
+        false,
+    );
+
+    assert!(store.get("context-2").is_none());
+}
+
+#[test]
+fn test_expired_conversations_are_evicted() {
+    let store = ConversationStore::new(0);
+
+    store.record(
+        "context-3",
+        &QueryParams::default(),
+        &[repo("a/one", 100)],
+        false,
+    );
+    store.record("context-4", &QueryParams::default(), &[], false);
+
+    assert_eq!(store.evict_expired(), 2);
+    assert_eq!(store.evict_expired(), 0);
+
+    let store = ConversationStore::new(60);
+    store.record("context-5", &QueryParams::default(), &[], false);
+
+    assert_eq!(store.evict_expired(), 0);
+    assert!(store.get("context-5").is_some());
+}
+
+#[test]
+fn test_follow_up_deserialization() {
+    let json = r#"{
+        "language": "rust",
+        "topics": [],
+        "timeframe": "week",
+        "count": 5,
+        "min_stars": 10,
+        "date_string": null,
+        "has_specific_date": false,
+        "more_results": true
+    }"#;
+
+    let follow_up = serde_json::from_str::<FollowUp>(json).unwrap();
+    assert!(follow_up.more_results);
+    assert_eq!(follow_up.params.language.as_deref(), Some("rust"));
+
+    let params_only = r#"{"language": null, "timeframe": "month"}"#;
+
+    let follow_up = serde_json::from_str::<FollowUp>(params_only).unwrap();
+    assert!(!follow_up.more_results);
+    assert_eq!(follow_up.params.timeframe, "month");
+    assert_eq!(follow_up.params.count, 5);
+}
+
+#[test]
+fn test_pending_clarification() {
+    let store = ConversationStore::new(60);
+
+    assert!(store.take_pending_query("task-1").is_none());
+
+    store.await_clarification("task-1", "trending stuff from around that conference");
+
+    assert_eq!(
+        store.take_pending_query("task-1").as_deref(),
+        Some("trending stuff from around that conference")
+    );
+    assert!(store.take_pending_query("task-1").is_none());
+}
+
+#[test]
+fn test_pending_queries_of_gone_tasks_are_dropped() {
+    let store = ConversationStore::new(60);
+
+    store.await_clarification("task-2", "that thing");
+    store.await_clarification("task-3", "the other thing");
+
+    assert_eq!(store.retain_pending(|task_id| task_id == "task-3"), 1);
+    assert!(store.take_pending_query("task-2").is_none());
+    assert_eq!(
+        store.take_pending_query("task-3").as_deref(),
+        Some("the other thing")
+    );
+}
@@ -0,0 +1,213 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Router, extract::Path, routing::get};
+use gitpulse::{
+    models::{
+        query::QueryParams,
+        repository::{RepoBlurb, TrendingRepo},
+    },
+    services::{
+        ai::QueryParser,
+        enricher::{RepoEnricher, describe_repo},
+        github::GitHubClient,
+        llm::LlmProvider,
+    },
+    utils::helpers::format_trending_message,
+};
+use serde_json::Value;
+
+use crate::common::{repo, spawn_mock};
+
+/// A repository with the metadata the blurb prompt describes.
+fn tool(id: u64, name: &str) -> TrendingRepo {
+    TrendingRepo {
+        id,
+        topics: vec!["cli".to_string(), "terminal".to_string()],
+        ..repo(name, 1200)
+    }
+}
+
+/// Answers every prompt with the same blurb and keeps the prompts and
+/// output names it saw.
+struct BlurbProvider {
+    prompts: Mutex<Vec<String>>,
+    titles: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl LlmProvider for BlurbProvider {
+    fn name(&self) -> &str {
+        "blurbs"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        user_query: &str,
+        schema: Option<&Value>,
+    ) -> Result<String> {
+        self.prompts.lock().unwrap().push(user_query.to_string());
+        self.titles.lock().unwrap().extend(
+            schema
+                .and_then(|schema| schema["title"].as_str())
+                .map(str::to_string),
+        );
+
+        Ok(r#"{"summary": "A fast terminal file manager.", "why_notable": "It just shipped plugin support."}"#.to_string())
+    }
+}
+
+async fn spawn_readme_server() -> String {
+    let app = Router::new().route(
+        "/repos/{owner}/{repo}/readme",
+        get(|Path((owner, repo)): Path<(String, String)>| async move {
+            format!("# {}/{}\n\nBrowse files from your terminal.", owner, repo)
+        }),
+    );
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    format!("{}/search/repositories", base_url)
+}
+
+#[test]
+fn test_describe_repo() {
+    let long_readme = "x".repeat(5000);
+
+    let prompt = describe_repo(&tool(1, "owner/tool"), Some(&long_readme));
+
+    assert!(prompt.contains("Repository: owner/tool"));
+    assert!(prompt.contains("Topics: cli, terminal"));
+    assert!(prompt.contains("Stars: 1200"));
+    assert!(prompt.contains("days"));
+    assert!(prompt.len() < 2000);
+
+    let prompt = describe_repo(&tool(1, "owner/tool"), None);
+    assert!(!prompt.contains("README"));
+}
+
+#[tokio::test]
+async fn test_enrich_adds_and_caches_blurbs() -> Result<()> {
+    let search_url = spawn_readme_server().await;
+
+    let provider = Arc::new(BlurbProvider {
+        prompts: Mutex::new(vec![]),
+        titles: Mutex::new(vec![]),
+    });
+
+    let enricher = RepoEnricher::new(
+        QueryParser::new(provider.clone(), "system").chain().fork(),
+        GitHubClient::new(None, &search_url)?,
+        60,
+    );
+
+    let mut repos = vec![tool(1, "owner/one"), tool(2, "owner/two")];
+    enricher.enrich(&mut repos).await;
+
+    assert_eq!(
+        repos[0].blurb,
+        Some(RepoBlurb {
+            summary: "A fast terminal file manager.".to_string(),
+            why_notable: "It just shipped plugin support.".to_string(),
+        })
+    );
+    assert!(repos[1].blurb.is_some());
+    assert!(provider.prompts.lock().unwrap()[0].contains("Browse files from your terminal."));
+    assert_eq!(provider.titles.lock().unwrap()[0], "record_blurb");
+
+    let mut repos = vec![tool(1, "owner/one")];
+    enricher.enrich(&mut repos).await;
+
+    assert!(repos[0].blurb.is_some());
+    assert_eq!(provider.prompts.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_enrich_without_llm_leaves_repos_alone() -> Result<()> {
+    let enricher = RepoEnricher::new(
+        QueryParser::rule_based().chain().fork(),
+        GitHubClient::new(None, "http://127.0.0.1:9/search/repositories")?,
+        60,
+    );
+
+    let mut repos = vec![tool(1, "owner/one")];
+    enricher.enrich(&mut repos).await;
+
+    assert!(repos[0].blurb.is_none());
+
+    Ok(())
+}
+
+/// Fails every call and counts them.
+struct FailingProvider {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl LlmProvider for FailingProvider {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        _user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        anyhow::bail!("unavailable")
+    }
+}
+
+#[tokio::test]
+async fn test_failing_blurbs_leave_parser_circuits_closed() -> Result<()> {
+    let provider = Arc::new(FailingProvider {
+        calls: AtomicUsize::new(0),
+    });
+
+    let parser = QueryParser::new(provider.clone(), "system");
+    let enricher = RepoEnricher::new(
+        parser.chain().fork(),
+        GitHubClient::new(None, "http://127.0.0.1:9/search/repositories")?,
+        60,
+    );
+
+    let mut repos = (1..=8)
+        .map(|id| tool(id, &format!("owner/repo-{}", id)))
+        .collect::<Vec<_>>();
+    enricher.enrich(&mut repos).await;
+
+    assert!(repos.iter().all(|repo| repo.blurb.is_none()));
+    let calls = provider.calls.load(Ordering::SeqCst);
+    assert!(calls < repos.len(), "{} calls", calls);
+
+    parser.parse("trending rust").await?;
+    assert_eq!(provider.calls.load(Ordering::SeqCst), calls + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_format_message_renders_blurb() {
+    let mut enriched = tool(1, "owner/one");
+    enriched.blurb = Some(RepoBlurb {
+        summary: "A fast terminal file manager.".to_string(),
+        why_notable: "It just shipped plugin support.".to_string(),
+    });
+
+    let message =
+        format_trending_message(&[enriched, tool(2, "owner/two")], QueryParams::default());
+
+    assert!(message.contains("**SUMMARY:** A fast terminal file manager."));
+    assert!(message.contains("**WHY IT'S NOTABLE:** It just shipped plugin support."));
+    assert_eq!(message.matches("**SUMMARY:**").count(), 1);
+}
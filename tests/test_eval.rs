@@ -0,0 +1,109 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use gitpulse::services::{
+    ai::QueryParser,
+    eval::{
+        DEFAULT_CORPUS_PATH, EvalBackend, EvalOptions, RecordedProvider, Recording, load_corpus,
+        run_eval,
+    },
+    prompts::PromptStore,
+};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_rule_parser_on_shipped_corpus() -> Result<()> {
+    let cases = load_corpus(DEFAULT_CORPUS_PATH)?;
+    assert!(cases.iter().any(|case| case.ambiguous));
+
+    let report = run_eval(&QueryParser::rule_based(), &cases).await;
+
+    assert_eq!(report.cases, cases.len());
+    assert_eq!(report.parsed_by.get("rules"), Some(&cases.len()));
+    assert_eq!(report.fields.len(), 7);
+    assert!(report.render().contains("exact match"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recorded_provider_scores_fields() -> Result<()> {
+    let cases = load_corpus(DEFAULT_CORPUS_PATH)?
+        .into_iter()
+        .filter(|case| {
+            case.query == "What's trending in Rust?"
+                || case.query == "Top 10 Python web frameworks from this month"
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(cases.len(), 2);
+
+    let provider = RecordedProvider::new(vec![
+        Recording {
+            query: "What's trending in Rust?".to_string(),
+            response: r#"{"language": "rust", "topics": [], "timeframe": "week", "count": 5, "min_stars": 10}"#.to_string(),
+        },
+        Recording {
+            query: "Top 10 Python web frameworks from this month".to_string(),
+            response: r#"{"language": "python", "topics": ["web-framework"], "timeframe": "week", "count": 10, "min_stars": 10}"#.to_string(),
+        },
+    ]);
+
+    let parser = QueryParser::with_chain(
+        vec![Arc::new(provider)],
+        PromptStore::built_in(),
+        Duration::from_secs(5),
+    );
+
+    let report = run_eval(&parser, &cases).await;
+
+    assert_eq!(report.exact_matches, 1);
+    assert_eq!(report.exact_match_rate(), 0.5);
+    assert_eq!(report.parsed_by.get("recorded"), Some(&2));
+
+    let timeframe = report.field("timeframe").unwrap();
+    assert_eq!((timeframe.correct, timeframe.total), (1, 2));
+    assert_eq!(report.field("language").unwrap().accuracy(), 1.0);
+
+    let failure = &report.failures[0];
+    assert_eq!(
+        failure.query,
+        "Top 10 Python web frameworks from this month"
+    );
+    assert_eq!(failure.mismatches.len(), 1);
+    assert_eq!(failure.mismatches[0].expected, "month");
+    assert_eq!(failure.mismatches[0].actual, "week");
+
+    Ok(())
+}
+
+#[test]
+fn test_eval_options() -> Result<()> {
+    let options = EvalOptions::from_args(&args(&[]))?;
+    assert_eq!(options.backend, EvalBackend::Llm);
+    assert_eq!(options.corpus, DEFAULT_CORPUS_PATH);
+
+    let options = EvalOptions::from_args(&args(&[
+        "--backend",
+        "recorded",
+        "--recordings",
+        "eval/recordings.jsonl",
+        "--min-accuracy",
+        "0.9",
+        "--json",
+    ]))?;
+    assert_eq!(options.backend, EvalBackend::Recorded);
+    assert_eq!(options.min_accuracy, Some(0.9));
+    assert!(options.json);
+
+    assert!(EvalOptions::from_args(&args(&["--backend", "recorded"])).is_err());
+    assert!(
+        EvalOptions::from_args(&args(&["--backend", "rules", "--record", "out.jsonl"])).is_err()
+    );
+    assert!(EvalOptions::from_args(&args(&["--corpus"])).is_err());
+    assert!(EvalOptions::from_args(&args(&["--verbose"])).is_err());
+
+    Ok(())
+}
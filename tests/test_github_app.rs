@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{TimeDelta, Utc};
+use gitpulse::{
+    models::query::QueryParams,
+    services::{github::GitHubClient, github_app::GitHubAppAuth},
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::spawn_mock;
+
+const PRIVATE_KEY: &[u8] = include_bytes!("fixtures/github_app_key.pem");
+const PUBLIC_KEY: &[u8] = include_bytes!("fixtures/github_app_key.pub.pem");
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Default)]
+struct Mock {
+    /// Installation tokens handed out so far.
+    issued: usize,
+    /// The `Authorization` header of every search.
+    searches: Vec<String>,
+}
+
+type Shared = Arc<Mutex<Mock>>;
+
+fn bearer(headers: &HeaderMap) -> String {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .trim_start_matches("Bearer ")
+        .to_string()
+}
+
+/// Checks the app JWT and issues `inst-1`, `inst-2`, ... The first token
+/// is already inside the refresh margin, later ones last an hour.
+async fn access_tokens(
+    State(mock): State<Shared>,
+    Path(installation_id): Path<u64>,
+    headers: HeaderMap,
+) -> Response {
+    let key = DecodingKey::from_rsa_pem(PUBLIC_KEY).unwrap();
+
+    let Ok(jwt) =
+        jsonwebtoken::decode::<Claims>(&bearer(&headers), &key, &Validation::new(Algorithm::RS256))
+    else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    assert_eq!(installation_id, 42);
+    assert_eq!(jwt.claims.iss, "1234");
+    assert!(jwt.claims.exp - jwt.claims.iat <= 600);
+
+    let mut mock = mock.lock().unwrap();
+    mock.issued += 1;
+
+    let lifetime = match mock.issued {
+        1 => TimeDelta::minutes(2),
+        _ => TimeDelta::hours(1),
+    };
+
+    (
+        StatusCode::CREATED,
+        Json(json!({
+            "token": format!("inst-{}", mock.issued),
+            "expires_at": (Utc::now() + lifetime).format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        })),
+    )
+        .into_response()
+}
+
+async fn search(State(mock): State<Shared>, headers: HeaderMap) -> Json<serde_json::Value> {
+    mock.lock().unwrap().searches.push(bearer(&headers));
+
+    Json(json!({ "total_count": 0, "items": [] }))
+}
+
+#[tokio::test]
+async fn test_app_installation_token_is_cached_and_refreshed() -> Result<()> {
+    let mock: Shared = Arc::new(Mutex::new(Mock::default()));
+
+    let app = Router::new()
+        .route(
+            "/app/installations/{installation_id}/access_tokens",
+            post(access_tokens),
+        )
+        .route("/search/repositories", get(search))
+        .with_state(mock.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let client = GitHubClient::new(None, &format!("{}/search/repositories", base_url))?
+        .with_app_auth(GitHubAppAuth::new("1234", 42, PRIVATE_KEY)?);
+
+    for _ in 0..3 {
+        client.search_with_params(&QueryParams::default()).await?;
+    }
+
+    let mock = mock.lock().unwrap();
+
+    assert_eq!(mock.issued, 2);
+    assert_eq!(mock.searches, vec!["inst-1", "inst-2", "inst-2"]);
+
+    assert_eq!(client.quota().tokens[0].token, "app-42");
+
+    Ok(())
+}
+
+#[test]
+fn test_app_rejects_invalid_private_key() {
+    assert!(GitHubAppAuth::new("1234", 42, b"not a key").is_err());
+    assert!(GitHubAppAuth::from_key_file("1234", 42, "missing.pem").is_err());
+}
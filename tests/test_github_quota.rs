@@ -0,0 +1,211 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap as AxumHeaderMap, StatusCode as AxumStatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::Utc;
+use gitpulse::{
+    models::query::QueryParams,
+    services::{
+        github::GitHubClient,
+        github_quota::{QuotaTracker, RateLimitResource, RetryPolicy, rate_limit_delay},
+    },
+};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderValue},
+};
+use serde_json::json;
+
+use crate::common::spawn_mock;
+
+fn quota_headers(remaining: u32, reset_in_secs: i64) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    headers.insert("x-ratelimit-limit", HeaderValue::from(30));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+    headers.insert(
+        "x-ratelimit-reset",
+        HeaderValue::from(Utc::now().timestamp() + reset_in_secs),
+    );
+
+    headers
+}
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 3,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn test_tracker_holds_back_calls_near_zero() {
+    let tracker = QuotaTracker::new(1);
+
+    assert_eq!(tracker.acquire(RateLimitResource::Search), None);
+
+    tracker.update(RateLimitResource::Search, &quota_headers(3, 30));
+
+    assert_eq!(tracker.acquire(RateLimitResource::Search), None);
+    assert_eq!(tracker.acquire(RateLimitResource::Search), None);
+
+    let wait = tracker.acquire(RateLimitResource::Search).unwrap();
+    assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+    assert_eq!(tracker.acquire(RateLimitResource::Core), None);
+
+    tracker.update(RateLimitResource::Search, &quota_headers(0, -1));
+    assert_eq!(tracker.acquire(RateLimitResource::Search), None);
+
+    let status = tracker.status();
+    assert_eq!(status.resources.len(), 1);
+    assert_eq!(status.resources[0].resource, "search");
+    assert_eq!(status.resources[0].limit, 30);
+}
+
+#[test]
+fn test_rate_limit_delay() {
+    let policy = fast_retries();
+
+    let mut retry_after = HeaderMap::new();
+    retry_after.insert("retry-after", HeaderValue::from(2));
+
+    assert_eq!(
+        rate_limit_delay(StatusCode::FORBIDDEN, &retry_after, "", 0, &policy),
+        Some(Duration::from_secs(2))
+    );
+
+    let delay = rate_limit_delay(
+        StatusCode::FORBIDDEN,
+        &HeaderMap::new(),
+        r#"{"message": "You have exceeded a secondary rate limit."}"#,
+        2,
+        &policy,
+    )
+    .unwrap();
+    assert!(delay >= Duration::from_millis(20) && delay <= Duration::from_millis(40));
+
+    let delay = rate_limit_delay(
+        StatusCode::FORBIDDEN,
+        &quota_headers(0, 10),
+        "API rate limit exceeded",
+        0,
+        &policy,
+    )
+    .unwrap();
+    assert!(delay > Duration::from_secs(8) && delay <= Duration::from_secs(10));
+
+    assert!(
+        rate_limit_delay(
+            StatusCode::TOO_MANY_REQUESTS,
+            &HeaderMap::new(),
+            "",
+            0,
+            &policy
+        )
+        .is_some()
+    );
+    assert_eq!(
+        rate_limit_delay(
+            StatusCode::FORBIDDEN,
+            &HeaderMap::new(),
+            "Resource not accessible",
+            0,
+            &policy
+        ),
+        None
+    );
+    assert_eq!(
+        rate_limit_delay(StatusCode::NOT_FOUND, &HeaderMap::new(), "", 0, &policy),
+        None
+    );
+}
+
+/// Rate-limits the first `limited` requests, then answers normally.
+async fn flaky_search(State((calls, limited)): State<(Arc<AtomicUsize>, usize)>) -> Response {
+    let call = calls.fetch_add(1, Ordering::SeqCst);
+
+    let mut headers = AxumHeaderMap::new();
+    headers.insert("x-ratelimit-limit", 30.into());
+    headers.insert("x-ratelimit-reset", (Utc::now().timestamp() + 60).into());
+
+    if call < limited {
+        headers.insert("x-ratelimit-remaining", 12.into());
+        headers.insert("retry-after", 0.into());
+
+        return (
+            AxumStatusCode::FORBIDDEN,
+            headers,
+            Json(json!({"message": "You have exceeded a secondary rate limit."})),
+        )
+            .into_response();
+    }
+
+    headers.insert("x-ratelimit-remaining", 11.into());
+
+    (headers, Json(json!({ "total_count": 0, "items": [] }))).into_response()
+}
+
+async fn spawn_flaky_server(limited: usize) -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let app = Router::new()
+        .route("/search/repositories", get(flaky_search))
+        .with_state((calls.clone(), limited));
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    (format!("{}/search/repositories", base_url), calls)
+}
+
+#[tokio::test]
+async fn test_search_retries_after_rate_limit() -> Result<()> {
+    let (search_url, calls) = spawn_flaky_server(2).await;
+
+    let client = GitHubClient::new(None, &search_url)?.with_retry_policy(fast_retries());
+
+    client.search_with_params(&QueryParams::default()).await?;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let quota = client.quota();
+    assert_eq!(quota.retries, 2);
+    assert_eq!(quota.resources[0].resource, "search");
+    assert_eq!(quota.resources[0].remaining, 11);
+
+    let metrics = quota.prometheus();
+    assert!(metrics.contains("gitpulse_github_quota_remaining{resource=\"search\"} 11\n"));
+    assert!(metrics.contains("gitpulse_github_retries_total 2\n"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_gives_up_after_max_retries() -> Result<()> {
+    let (search_url, calls) = spawn_flaky_server(usize::MAX).await;
+
+    let client = GitHubClient::new(None, &search_url)?.with_retry_policy(fast_retries());
+
+    let error = client
+        .search_with_params(&QueryParams::default())
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("403"));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::Utc;
+use gitpulse::{
+    models::query::QueryParams,
+    services::{github::GitHubClient, github_quota::RateLimitResource, github_tokens::TokenPool},
+};
+use serde_json::json;
+
+use crate::common::{Captured, spawn_mock};
+
+#[tokio::test]
+async fn test_pool_picks_token_with_most_quota() -> Result<()> {
+    let pool = TokenPool::new(&["first", "second", " "])?;
+
+    assert_eq!(pool.pick(RateLimitResource::Search)?.label(), "token-1");
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-ratelimit-limit", 30.into());
+    headers.insert("x-ratelimit-remaining", 3.into());
+    headers.insert("x-ratelimit-reset", (Utc::now().timestamp() + 60).into());
+
+    let first = pool.pick(RateLimitResource::Search)?;
+    first.quota().update(RateLimitResource::Search, &headers);
+
+    assert_eq!(pool.pick(RateLimitResource::Search)?.label(), "token-2");
+    assert_eq!(pool.pick(RateLimitResource::Core)?.label(), "token-1");
+
+    let anonymous = TokenPool::new::<&str>(&[])?;
+    let token = anonymous.pick(RateLimitResource::Search)?;
+
+    assert_eq!(token.label(), "anonymous");
+    assert!(token.authorization().await?.is_none());
+    assert!(!anonymous.revoke(token));
+
+    Ok(())
+}
+
+/// Rejects the `revoked` token, rate-limits the `limited` one and reports a
+/// different quota per token.
+async fn search(State(captured): State<Captured<String>>, headers: HeaderMap) -> Response {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .trim_start_matches("Bearer ")
+        .to_string();
+
+    captured.lock().unwrap().push(token.clone());
+
+    let remaining = match token.as_str() {
+        "revoked" => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"message": "Bad credentials"})),
+            )
+                .into_response();
+        }
+        "limited" => {
+            return (
+                StatusCode::FORBIDDEN,
+                [("retry-after", "30")],
+                Json(json!({"message": "You have exceeded a secondary rate limit"})),
+            )
+                .into_response();
+        }
+        "busy" => 5,
+        _ => 20,
+    };
+
+    (
+        [
+            ("x-ratelimit-limit", "30".to_string()),
+            ("x-ratelimit-remaining", remaining.to_string()),
+            (
+                "x-ratelimit-reset",
+                (Utc::now().timestamp() + 60).to_string(),
+            ),
+        ],
+        Json(json!({ "total_count": 0, "items": [] })),
+    )
+        .into_response()
+}
+
+async fn spawn_server() -> Result<(String, Captured<String>)> {
+    let captured: Captured<String> = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    Ok((format!("{}/search/repositories", base_url), captured))
+}
+
+#[tokio::test]
+async fn test_requests_rotate_across_tokens() -> Result<()> {
+    let (search_url, captured) = spawn_server().await?;
+
+    let client = GitHubClient::new(None, &search_url)?.with_tokens(&["revoked", "busy", "idle"])?;
+
+    for _ in 0..3 {
+        client.search_with_params(&QueryParams::default()).await?;
+    }
+
+    assert_eq!(
+        captured.lock().unwrap().clone(),
+        vec!["revoked", "busy", "idle", "idle"]
+    );
+
+    let quota = client.quota();
+
+    let usage = quota
+        .tokens
+        .iter()
+        .map(|usage| (usage.token.as_str(), usage.revoked, usage.requests))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        usage,
+        vec![
+            ("token-1", true, 1),
+            ("token-2", false, 1),
+            ("token-3", false, 2)
+        ]
+    );
+
+    assert_eq!(quota.resources[0].limit, 60);
+    assert_eq!(quota.resources[0].remaining, 25);
+
+    let metrics = quota.prometheus();
+    assert!(metrics.contains("gitpulse_github_token_revoked{token=\"token-1\"} 1\n"));
+    assert!(metrics.contains(
+        "gitpulse_github_token_quota_remaining{token=\"token-3\",resource=\"search\"} 20\n"
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limited_token_switches_to_another() -> Result<()> {
+    let (search_url, captured) = spawn_server().await?;
+
+    let client = GitHubClient::new(None, &search_url)?.with_tokens(&["limited", "idle"])?;
+
+    let started = std::time::Instant::now();
+
+    client.search_with_params(&QueryParams::default()).await?;
+    client.search_with_params(&QueryParams::default()).await?;
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(
+        captured.lock().unwrap().clone(),
+        vec!["limited", "idle", "idle"]
+    );
+
+    let quota = client.quota();
+    assert_eq!(quota.retries, 1);
+    assert!(!quota.tokens[0].revoked);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_last_live_token_is_never_revoked() -> Result<()> {
+    let (search_url, captured) = spawn_server().await?;
+
+    let client = GitHubClient::new(Some("revoked"), &search_url)?;
+
+    for _ in 0..2 {
+        let error = client
+            .search_with_params(&QueryParams::default())
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("401"));
+    }
+
+    assert_eq!(captured.lock().unwrap().len(), 2);
+    assert!(!client.quota().tokens[0].revoked);
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{Json, Router, extract::State, routing::post};
+use chrono::{TimeDelta, Utc};
+use gitpulse::{
+    models::{
+        query::QueryParams,
+        repository::{GraphQlRepository, Release, RepoDetails, STARGAZERS_FETCHED, TrendingRepo},
+    },
+    services::github::{GitHubClient, SearchBackend},
+    utils::helpers::format_repo_details,
+};
+use serde_json::{Value, json};
+
+use crate::common::{Captured, spawn_mock};
+
+fn starred(days_ago: i64) -> Value {
+    json!({ "starredAt": (Utc::now() - TimeDelta::days(days_ago)).to_rfc3339() })
+}
+
+fn node(stargazers: Vec<Value>) -> Value {
+    json!({
+        "databaseId": 7,
+        "nameWithOwner": "owner/repo",
+        "url": "https://github.com/owner/repo",
+        "description": null,
+        "createdAt": "2025-10-30T10:30:00Z",
+        "stargazerCount": 1200,
+        "forkCount": 30,
+        "primaryLanguage": { "name": "Rust" },
+        "repositoryTopics": { "nodes": [{ "topic": { "name": "cli" } }] },
+        "licenseInfo": { "spdxId": "MIT", "name": "MIT License" },
+        "latestRelease": { "tagName": "v1.2.0", "publishedAt": "2025-10-31T08:00:00Z" },
+        "issues": { "totalCount": 12 },
+        "languages": { "totalSize": 3000, "edges": [{ "size": 2000 }] },
+        "stargazers": { "edges": stargazers }
+    })
+}
+
+async fn graphql(State(captured): State<Captured<Value>>, Json(body): Json<Value>) -> Json<Value> {
+    captured.lock().unwrap().push(body);
+
+    Json(json!({
+        "data": {
+            "search": {
+                "repositoryCount": 1,
+                "nodes": [node(vec![starred(30), starred(3), starred(1)])]
+            }
+        }
+    }))
+}
+
+#[tokio::test]
+async fn test_graphql_backend_returns_details() -> Result<()> {
+    let captured: Captured<Value> = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/graphql", post(graphql))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let client = GitHubClient::new(Some("token"), &format!("{}/search/repositories", base_url))?
+        .with_search_backend(SearchBackend::GraphQl);
+
+    let params = QueryParams {
+        language: Some("rust".to_string()),
+        count: 5,
+        ..QueryParams::default()
+    };
+
+    let result = client.search_with_params(&params).await?;
+
+    let requests = captured.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+
+    let variables = &requests[0]["variables"];
+    assert_eq!(variables["first"], 5);
+    let query = variables["query"].as_str().unwrap();
+    assert!(query.contains("language:rust "));
+    assert!(query.ends_with(" sort:stars-desc"));
+
+    assert_eq!(result.total_count, 1);
+
+    let repo = &result.repos[0];
+    assert_eq!(repo.name, "owner/repo");
+    assert_eq!(repo.language, "Rust");
+    assert_eq!(repo.topics, vec!["cli"]);
+    assert_eq!(
+        repo.details,
+        Some(RepoDetails {
+            license: Some("MIT".to_string()),
+            open_issues: 12,
+            latest_release: Some(Release {
+                tag: "v1.2.0".to_string(),
+                published_at: Some("2025-10-31T08:00:00Z".to_string()),
+            }),
+            language_share: Some(66.7),
+            stars_last_week: 2,
+            stars_last_week_saturated: false,
+        })
+    );
+
+    let message = format_repo_details(repo);
+    assert!(message.contains("**LATEST RELEASE:** v1.2.0 (2025-10-31)"));
+    assert!(message.contains("**STARS THIS WEEK:** +2"));
+
+    assert_eq!(client.quota().tokens[0].requests, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_saturated_stars_last_week() {
+    let stargazers = (0..STARGAZERS_FETCHED).map(|_| starred(1)).collect();
+    let repo =
+        TrendingRepo::from(serde_json::from_value::<GraphQlRepository>(node(stargazers)).unwrap());
+
+    let details = repo.details.as_ref().unwrap();
+    assert_eq!(details.stars_last_week, 100);
+    assert!(details.stars_last_week_saturated);
+
+    assert!(format_repo_details(&repo).contains("**STARS THIS WEEK:** 100+"));
+}
+
+#[test]
+fn test_search_backend_from_str() {
+    assert_eq!(
+        "GraphQL".parse::<SearchBackend>().unwrap(),
+        SearchBackend::GraphQl
+    );
+    assert_eq!(
+        "rest".parse::<SearchBackend>().unwrap(),
+        SearchBackend::Rest
+    );
+    assert!("soap".parse::<SearchBackend>().is_err());
+}
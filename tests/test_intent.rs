@@ -0,0 +1,143 @@
+use gitpulse::{
+    models::{a2a::PushNotificationConfig, repository::TrendingRepo},
+    services::{
+        intent::{Intent, IntentRouter, Smalltalk},
+        subscription_store::{DigestFrequency, Subscription, SubscriptionStore},
+    },
+    utils::helpers::{format_comparison, format_repo_details},
+};
+
+use crate::common::repo;
+
+#[test]
+fn test_trending_searches() {
+    let router = IntentRouter::new();
+
+    let queries = [
+        "What's trending in Rust?",
+        "Top 5 AI/ML repos from last month",
+        "help me find python web frameworks",
+        "machine learning stuff",
+        "show 5 more",
+        "What's trending in ci/cd this week?",
+        "show me client/server rust repos",
+        "what's new in rust/wasm?",
+    ];
+
+    for query in queries {
+        assert_eq!(router.classify(query), Intent::TrendingSearch, "{}", query);
+    }
+}
+
+#[test]
+fn test_help_smalltalk_and_off_topic() {
+    let router = IntentRouter::new();
+
+    assert_eq!(router.classify("help"), Intent::Help);
+    assert_eq!(router.classify("What can you do?"), Intent::Help);
+    assert_eq!(
+        router.classify("Hi there!"),
+        Intent::Smalltalk(Smalltalk::Greeting)
+    );
+    assert_eq!(
+        router.classify("thanks a lot"),
+        Intent::Smalltalk(Smalltalk::Thanks)
+    );
+    assert_eq!(
+        router.classify("bye"),
+        Intent::Smalltalk(Smalltalk::Farewell)
+    );
+    assert_eq!(
+        router.classify("what's the weather like in Paris?"),
+        Intent::OffTopic
+    );
+}
+
+#[test]
+fn test_repo_details_and_compare() {
+    let router = IntentRouter::new();
+
+    assert_eq!(
+        router.classify("tell me about tokio-rs/axum"),
+        Intent::RepoDetails("tokio-rs/axum".to_string())
+    );
+    assert_eq!(
+        router.classify("https://github.com/tokio-rs/axum.git"),
+        Intent::RepoDetails("tokio-rs/axum".to_string())
+    );
+    assert_eq!(
+        router.classify("rust-lang/rust"),
+        Intent::RepoDetails("rust-lang/rust".to_string())
+    );
+    assert_eq!(
+        router.classify("is https://github.com/tokio-rs/axum any good for ci/cd?"),
+        Intent::RepoDetails("tokio-rs/axum".to_string())
+    );
+    assert_eq!(
+        router.classify("Compare tokio-rs/axum vs actix/actix-web"),
+        Intent::Compare(vec![
+            "tokio-rs/axum".to_string(),
+            "actix/actix-web".to_string()
+        ])
+    );
+}
+
+#[test]
+fn test_subscriptions() {
+    let router = IntentRouter::new();
+
+    assert_eq!(
+        router.classify("subscribe me to the daily digest"),
+        Intent::Subscribe(DigestFrequency::Daily)
+    );
+    assert_eq!(
+        router.classify("send me the weekly roundup"),
+        Intent::Subscribe(DigestFrequency::Weekly)
+    );
+    assert_eq!(router.classify("unsubscribe"), Intent::Unsubscribe);
+    assert_eq!(
+        router.classify("please stop the digests"),
+        Intent::Unsubscribe
+    );
+}
+
+#[test]
+fn test_subscription_store() {
+    let store = SubscriptionStore::new();
+
+    store.subscribe(Subscription {
+        context_id: "context-1".to_string(),
+        frequency: DigestFrequency::Weekly,
+        push_config: PushNotificationConfig {
+            id: None,
+            url: "https://example.com/callback".to_string(),
+            token: None,
+        },
+    });
+
+    assert_eq!(store.subscribers(DigestFrequency::Weekly).len(), 1);
+    assert!(store.subscribers(DigestFrequency::Daily).is_empty());
+
+    assert!(store.unsubscribe("context-1").is_some());
+    assert!(store.unsubscribe("context-1").is_none());
+    assert!(store.get("context-1").is_none());
+}
+
+#[test]
+fn test_format_details_and_comparison() {
+    let details = format_repo_details(&TrendingRepo {
+        topics: vec!["http".to_string()],
+        ..repo("tokio-rs/axum", 21000)
+    });
+
+    assert!(details.contains("### [tokio-rs/axum](https://github.com/tokio-rs/axum)"));
+    assert!(details.contains("**STARS:** 21.0k"));
+    assert!(details.contains("**CREATED:** 2025-10-30"));
+    assert!(details.contains("**TOPICS:** http"));
+
+    let comparison =
+        format_comparison(&[repo("tokio-rs/axum", 21000), repo("actix/actix-web", 23000)]);
+
+    assert!(comparison.contains("| **Stars** | 21.0k | 23.0k |"));
+    assert!(comparison.contains("**MOST STARRED:** actix/actix-web"));
+}
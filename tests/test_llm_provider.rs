@@ -0,0 +1,466 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+use gitpulse::{
+    config::settings::Config,
+    services::{
+        ai::{QueryParser, query_schema},
+        llm::{LlmProvider, LlmSettings, OpenAiProvider, build_provider},
+        prompts::PromptStore,
+    },
+};
+use serde_json::{Value, json};
+
+use crate::common::{Captured, spawn_mock};
+
+async fn chat_completions(
+    State(captured): State<Captured<(Option<String>, Value)>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let auth = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    captured.lock().unwrap().push((auth, body));
+
+    Json(json!({
+        "choices": [
+            { "message": { "role": "assistant", "content": "{\"language\": \"rust\"}" } }
+        ]
+    }))
+}
+
+async fn spawn_mock_server() -> (String, Captured<(Option<String>, Value)>) {
+    let captured: Captured<(Option<String>, Value)> = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    (format!("{}/v1", base_url), captured)
+}
+
+fn settings(provider: &str, base_url: Option<String>) -> LlmSettings {
+    LlmSettings {
+        provider: provider.to_string(),
+        api_key: String::new(),
+        model: "llama3.1".to_string(),
+        base_url,
+        json_mode: true,
+    }
+}
+
+#[tokio::test]
+async fn test_openai_provider_sends_chat_completion() -> Result<()> {
+    let (base_url, captured) = spawn_mock_server().await;
+
+    let provider = OpenAiProvider::new(&base_url, "sk-test", "gpt-4o-mini", true)?;
+
+    assert_eq!(provider.name(), "openai");
+
+    let text = provider.complete("system", "trending rust", None).await?;
+
+    assert_eq!(text, "{\"language\": \"rust\"}");
+
+    let requests = captured.lock().unwrap();
+    let (auth, body) = &requests[0];
+
+    assert_eq!(auth.as_deref(), Some("Bearer sk-test"));
+    assert_eq!(body["model"], "gpt-4o-mini");
+    assert_eq!(body["messages"][0]["role"], "system");
+    assert_eq!(body["messages"][1]["content"], "trending rust");
+    assert_eq!(body["response_format"]["type"], "json_object");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_openai_provider_uses_json_schema() -> Result<()> {
+    let (base_url, captured) = spawn_mock_server().await;
+
+    let provider = OpenAiProvider::new(&base_url, "", "llama3.1", true)?;
+
+    provider
+        .complete("system", "trending rust", Some(&query_schema()))
+        .await?;
+
+    let requests = captured.lock().unwrap();
+    let response_format = &requests[0].1["response_format"];
+
+    assert_eq!(response_format["type"], "json_schema");
+    assert_eq!(
+        response_format["json_schema"]["schema"]["properties"]["timeframe"]["enum"],
+        json!(["day", "week", "month", "quarter", "year"])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ollama_provider_without_key_or_json_mode() -> Result<()> {
+    let (base_url, captured) = spawn_mock_server().await;
+
+    let provider = build_provider(&LlmSettings {
+        json_mode: false,
+        ..settings("ollama", Some(format!("{}/", base_url)))
+    })
+    .await?;
+
+    assert_eq!(provider.name(), "ollama");
+
+    provider.complete("system", "trending go", None).await?;
+
+    let requests = captured.lock().unwrap();
+    let (auth, body) = &requests[0];
+
+    assert!(auth.is_none());
+    assert_eq!(body["model"], "llama3.1");
+    assert!(body.get("response_format").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_provider_lists_supported_providers() {
+    let error = build_provider(&settings("claude", None))
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+
+    assert!(error.contains("'claude'"));
+    assert!(error.contains("anthropic, gemini, openai, ollama"));
+}
+
+struct StaticProvider(&'static str);
+
+#[async_trait]
+impl LlmProvider for StaticProvider {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        _user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_query_parser_uses_provider() -> Result<()> {
+    let provider = Arc::new(StaticProvider(
+        r#"```json
+        {"language": "go", "topics": ["cli"], "timeframe": "week", "count": 5, "min_stars": 50}
+        ```"#,
+    ));
+
+    let parser = QueryParser::new(provider, "system");
+
+    let parsed = parser.parse("go cli tools").await?;
+
+    assert!(parsed.clarification().is_none());
+    assert_eq!(parsed.params.language.as_deref(), Some("go"));
+    assert_eq!(parsed.params.topics, vec!["cli".to_string()]);
+    assert_eq!(parsed.params.count, 5);
+
+    Ok(())
+}
+
+struct FailingProvider;
+
+#[async_trait]
+impl LlmProvider for FailingProvider {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        _user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        anyhow::bail!("connection refused")
+    }
+}
+
+#[tokio::test]
+async fn test_query_parser_falls_back_to_rules() -> Result<()> {
+    let parser = QueryParser::new(Arc::new(FailingProvider), "system");
+
+    let parsed = parser.parse("top 10 golang cli tools").await?;
+
+    assert!(parsed.clarification().is_none());
+    assert_eq!(parsed.params.language.as_deref(), Some("go"));
+    assert_eq!(parsed.params.topics, vec!["cli".to_string()]);
+    assert_eq!(parsed.params.count, 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rule_based_query_parser() -> Result<()> {
+    let parser = QueryParser::rule_based();
+
+    let parsed = parser
+        .parse("rust repos created after January 23, 2013")
+        .await?;
+
+    assert!(parsed.params.has_specific_date);
+    assert_eq!(parsed.params.created_after.as_deref(), Some("2013-01-23"));
+    assert_eq!(parsed.params.timeframe, "year");
+
+    Ok(())
+}
+
+#[test]
+fn test_llm_settings_is_configured() {
+    assert!(!settings("gemini", None).is_configured());
+    assert!(!settings("rules", None).is_configured());
+    assert!(settings("ollama", None).is_configured());
+    assert!(settings("openai", Some("http://localhost:8000/v1".to_string())).is_configured());
+    assert!(
+        LlmSettings {
+            api_key: "key".to_string(),
+            ..settings("anthropic", None)
+        }
+        .is_configured()
+    );
+}
+
+/// Returns the queued responses in order and records every prompt it saw.
+struct ScriptedProvider {
+    responses: Mutex<Vec<&'static str>>,
+    prompts: Mutex<Vec<String>>,
+}
+
+impl ScriptedProvider {
+    fn new(responses: &[&'static str]) -> Arc<Self> {
+        Arc::new(Self {
+            responses: Mutex::new(responses.iter().rev().copied().collect()),
+            prompts: Mutex::new(vec![]),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        self.prompts.lock().unwrap().push(user_query.to_string());
+
+        Ok(self
+            .responses
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or("{}")
+            .to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_response_is_repaired() -> Result<()> {
+    let provider = ScriptedProvider::new(&[
+        r#"{"language": "rust", "timeframe": "fortnight", "count": 99}"#,
+        r#"{"language": "rust", "timeframe": "week", "count": 99}"#,
+    ]);
+
+    let parser = QueryParser::new(provider.clone(), "system");
+
+    let parsed = parser.parse("top rust repos").await?;
+
+    assert_eq!(parsed.params.timeframe, "week");
+    assert_eq!(parsed.params.count, 20);
+
+    let prompts = provider.prompts.lock().unwrap();
+    assert_eq!(prompts.len(), 2);
+    assert!(prompts[1].contains("timeframe 'fortnight'"));
+    assert!(prompts[1].contains("\"fortnight\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_response_still_invalid_after_retry_is_ambiguous() -> Result<()> {
+    let provider = ScriptedProvider::new(&["not json", r#"{"language": "klingon"}"#]);
+
+    let parser = QueryParser::new(provider.clone(), "system");
+
+    let parsed = parser.parse("klingon repos").await?;
+
+    assert!(parsed.clarification().is_some());
+    assert_eq!(provider.prompts.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+/// Fails or stalls on every call and counts how often it was tried.
+struct BrokenProvider {
+    name: &'static str,
+    stall: bool,
+    calls: Mutex<u32>,
+}
+
+impl BrokenProvider {
+    fn new(name: &'static str, stall: bool) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            stall,
+            calls: Mutex::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for BrokenProvider {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn complete(
+        &self,
+        _system_prompt: &str,
+        _user_query: &str,
+        _schema: Option<&Value>,
+    ) -> Result<String> {
+        *self.calls.lock().unwrap() += 1;
+
+        if self.stall {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+
+        anyhow::bail!("{} is down", self.name)
+    }
+}
+
+const RUST_QUERY: &str = r#"{"language": "rust", "topics": [], "timeframe": "week", "count": 5, "min_stars": 10, "has_specific_date": false}"#;
+
+#[tokio::test]
+async fn test_chain_falls_over_to_next_provider() -> Result<()> {
+    let slow = BrokenProvider::new("gemini", true);
+    let backup = ScriptedProvider::new(&[RUST_QUERY]);
+
+    let parser = QueryParser::with_chain(
+        vec![slow.clone(), backup],
+        PromptStore::from_template("system", ""),
+        Duration::from_millis(50),
+    );
+
+    let parsed = parser.parse("trending rust").await?;
+
+    assert_eq!(parsed.params.language.as_deref(), Some("rust"));
+    assert_eq!(parsed.parsed_by.as_deref(), Some("scripted"));
+    assert_eq!(*slow.calls.lock().unwrap(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_skips_failing_provider() -> Result<()> {
+    let broken = BrokenProvider::new("gemini", false);
+    let backup = ScriptedProvider::new(&[RUST_QUERY; 5]);
+
+    let parser = QueryParser::with_chain(
+        vec![broken.clone(), backup],
+        PromptStore::from_template("system", ""),
+        Duration::from_secs(1),
+    );
+
+    for _ in 0..5 {
+        parser.parse("trending rust").await?;
+    }
+
+    assert_eq!(*broken.calls.lock().unwrap(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rules_record_parsed_by() -> Result<()> {
+    let parser = QueryParser::with_chain(
+        vec![BrokenProvider::new("gemini", false)],
+        PromptStore::from_template("system", ""),
+        Duration::from_secs(1),
+    );
+
+    let parsed = parser.parse("trending rust").await?;
+    assert_eq!(parsed.parsed_by.as_deref(), Some("rules"));
+
+    let follow_up = parser
+        .parse_follow_up("now only go", &parsed.params)
+        .await?;
+    assert_eq!(follow_up.parsed_by.as_deref(), Some("rules"));
+    assert_eq!(follow_up.params.language.as_deref(), Some("go"));
+
+    Ok(())
+}
+
+#[test]
+fn test_chain_from_config() -> Result<()> {
+    let env = [
+        ("LLM_PROVIDER", "gemini"),
+        ("LLM_API_KEY", "key"),
+        ("LLM_MODEL", "gemini-2.5-flash"),
+        (
+            "LLM_FALLBACKS",
+            "anthropic:claude-haiku-4-5, ollama:llama3.1:8b@http://gpu-box:11434/v1, openai:gpt-4o-mini",
+        ),
+        ("ANTHROPIC_API_KEY", "sk-ant"),
+        (
+            "GITHUB_SEARCH_URL",
+            "https://api.github.com/search/repositories",
+        ),
+        ("GITHUB_ACCESS_TOKEN", "token"),
+        ("EXTERNAL_WEBHOOK_URL", "https://example.com/webhook"),
+        ("CACHE_TTL", "60"),
+        ("HOST", "0.0.0.0"),
+        ("PORT", "8000"),
+        ("CORS_ALLOWED_ORIGINS", "http://localhost"),
+        ("RATE_LIMIT_MS", "60"),
+    ];
+
+    let config = envy::from_iter::<_, Config>(
+        env.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+    )?;
+
+    let chain = LlmSettings::chain_from_config(&config)?;
+
+    // openai has no key configured and is dropped
+    assert_eq!(chain.len(), 3);
+    assert_eq!(chain[0].provider, "gemini");
+    assert_eq!(chain[0].api_key, "key");
+    assert_eq!(chain[1].provider, "anthropic");
+    assert_eq!(chain[1].api_key, "sk-ant");
+    assert_eq!(chain[2].model, "llama3.1:8b");
+    assert_eq!(
+        chain[2].base_url.as_deref(),
+        Some("http://gpu-box:11434/v1")
+    );
+
+    assert!(LlmSettings::parse_fallback("anthropic", &config).is_err());
+
+    Ok(())
+}
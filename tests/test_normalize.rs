@@ -0,0 +1,173 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{Router, routing::get};
+use gitpulse::{
+    models::{
+        lexicon::LANGUAGES,
+        normalize::{TopicMap, canonical_language, find_language, language_search_name},
+        query::QueryParams,
+    },
+    services::github::GitHubClient,
+    utils::helpers::build_base_query,
+};
+use serde_json::Value;
+
+use crate::common::{Captured, search, spawn_mock};
+
+#[test]
+fn test_language_aliases_resolve_to_github_names() {
+    let cases = [
+        ("c++", "C++", "cpp"),
+        ("CPP", "C++", "cpp"),
+        ("c#", "C#", "csharp"),
+        ("golang", "Go", "go"),
+        ("js", "JavaScript", "javascript"),
+        ("Node.js", "JavaScript", "javascript"),
+        ("objective c", "Objective-C", "objective-c"),
+        ("jupyter_notebook", "Jupyter Notebook", "jupyter-notebook"),
+    ];
+
+    for (alias, name, search_name) in cases {
+        let language = find_language(alias).unwrap();
+
+        assert_eq!(language.name, name, "{}", alias);
+        assert_eq!(language.search_name, search_name, "{}", alias);
+        assert_eq!(language_search_name(alias), search_name, "{}", alias);
+    }
+
+    assert_eq!(
+        canonical_language("Objective C").as_deref(),
+        Some("objective-c")
+    );
+    assert_eq!(canonical_language("klingon"), None);
+    assert_eq!(canonical_language("  "), None);
+}
+
+#[test]
+fn test_rule_parser_languages_are_canonical() {
+    for (alias, language) in LANGUAGES {
+        assert_eq!(
+            canonical_language(alias).as_deref(),
+            Some(*language),
+            "{}",
+            alias
+        );
+    }
+}
+
+#[test]
+fn test_query_uses_url_safe_language() {
+    let params = QueryParams {
+        language: Some("c++".to_string()),
+        ..QueryParams::default()
+    };
+
+    let query = build_base_query(&params).to_string();
+
+    assert!(query.contains("language:cpp"));
+}
+
+#[test]
+fn test_topic_synonyms_and_expansions() -> Result<()> {
+    let topics = TopicMap::from_json(
+        r#"{
+            "synonyms": {"ML": "machine learning", "k8s": "kubernetes"},
+            "expansions": {"ai": ["artificial-intelligence", "llm", "machine-learning"]}
+        }"#,
+    )?;
+
+    assert_eq!(topics.canonical("ml"), "machine-learning");
+    assert_eq!(topics.canonical("Web Framework"), "web-framework");
+
+    assert_eq!(
+        topics.normalize(&["k8s".to_string(), "kubernetes".to_string()]),
+        vec!["kubernetes"]
+    );
+    assert_eq!(
+        topics.expand(&["ai".to_string(), "ml".to_string()]),
+        vec!["ai", "machine-learning", "artificial-intelligence", "llm"]
+    );
+
+    assert!(TopicMap::from_json(r#"{"synonyms": ["ml"]}"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_expansions_name_canonical_topics() -> Result<()> {
+    let file: Value = serde_json::from_str(include_str!("../topic_synonyms.json"))?;
+    let topics = TopicMap::built_in();
+
+    for (topic, related) in file["expansions"].as_object().unwrap() {
+        for related in related.as_array().unwrap() {
+            let related = related.as_str().unwrap();
+
+            assert_eq!(
+                topics.canonical(related),
+                related,
+                "{} expands to the alias {}",
+                topic,
+                related
+            );
+        }
+    }
+
+    let topics = TopicMap::from_json(
+        r#"{"synonyms": {"k8s": "kubernetes"}, "expansions": {"devops": ["k8s"], "kubernetes": ["k8s"]}}"#,
+    )?;
+
+    assert_eq!(
+        topics.expand(&["devops".to_string(), "k8s".to_string()]),
+        vec!["devops", "kubernetes"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_expands_topics() -> Result<()> {
+    let captured: Captured = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let topics = TopicMap::from_json(
+        r#"{"synonyms": {"ml": "machine-learning"}, "expansions": {"machine-learning": ["deep-learning"]}}"#,
+    )?;
+
+    let client = GitHubClient::new(None, &format!("{}/search/repositories", base_url))?
+        .with_topic_map(topics);
+
+    let params = QueryParams {
+        language: Some("c#".to_string()),
+        topics: vec!["ml".to_string()],
+        ..QueryParams::default()
+    };
+
+    client.search_with_params(&params).await?;
+
+    let queries = captured
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|request| request["q"].clone())
+        .collect::<Vec<_>>();
+
+    assert!(
+        queries
+            .iter()
+            .all(|query| query.contains("language:csharp"))
+    );
+    assert!(
+        queries
+            .iter()
+            .any(|query| query.starts_with("topic:machine-learning OR topic:deep-learning "))
+    );
+    assert!(!queries.iter().any(|query| query.contains("topic:ml ")));
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use gitpulse::models::query::ParsedQuery;
+
+#[test]
+fn test_confident_query() {
+    let json = r#"{
+        "language": "rust",
+        "topics": [],
+        "timeframe": "week",
+        "count": 5,
+        "min_stars": 10,
+        "date_string": null,
+        "has_specific_date": false,
+        "confidence": 0.95,
+        "clarifying_question": null
+    }"#;
+
+    let parsed = serde_json::from_str::<ParsedQuery>(json).unwrap();
+    assert_eq!(parsed.params.language.as_deref(), Some("rust"));
+    assert!(parsed.clarification().is_none());
+}
+
+#[test]
+fn test_missing_confidence_defaults_to_confident() {
+    let json = r#"{"language": "go", "timeframe": "month"}"#;
+
+    let parsed = serde_json::from_str::<ParsedQuery>(json).unwrap();
+    assert_eq!(parsed.confidence, 1.0);
+    assert!(parsed.clarification().is_none());
+}
+
+#[test]
+fn test_ambiguous_query_asks_question() {
+    let json = r#"{
+        "language": null,
+        "topics": [],
+        "confidence": 0.2,
+        "clarifying_question": "Which conference do you mean?"
+    }"#;
+
+    let parsed = serde_json::from_str::<ParsedQuery>(json).unwrap();
+    assert_eq!(
+        parsed.clarification().as_deref(),
+        Some("Which conference do you mean?")
+    );
+
+    let fallback = ParsedQuery::ambiguous();
+    assert!(fallback.clarification().is_some());
+}
+
+#[test]
+fn test_validate_clamps_and_normalizes() {
+    let json = r#"{
+        "language": "Golang",
+        "topics": ["Machine Learning", "ai", "AI", " "],
+        "timeframe": "Month",
+        "count": 50,
+        "date_string": "",
+        "has_specific_date": true,
+        "confidence": 1.7
+    }"#;
+
+    let mut parsed = serde_json::from_str::<ParsedQuery>(json).unwrap();
+
+    assert!(parsed.validate().is_ok());
+    assert_eq!(parsed.params.language.as_deref(), Some("go"));
+    assert_eq!(parsed.params.topics, vec!["machine-learning", "ai"]);
+    assert_eq!(parsed.params.timeframe, "month");
+    assert_eq!(parsed.params.count, 20);
+    assert_eq!(parsed.params.date_string, None);
+    assert!(!parsed.params.has_specific_date);
+    assert_eq!(parsed.confidence, 1.0);
+}
+
+#[test]
+fn test_validate_rejects_unknown_language_and_timeframe() {
+    let json = r#"{"language": "klingon", "timeframe": "decade"}"#;
+
+    let mut parsed = serde_json::from_str::<ParsedQuery>(json).unwrap();
+
+    let errors = parsed.validate().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("klingon"));
+    assert!(errors[1].contains("decade"));
+}
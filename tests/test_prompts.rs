@@ -0,0 +1,93 @@
+use std::{env, fs};
+
+use anyhow::Result;
+use chrono::Utc;
+use gitpulse::services::prompts::PromptStore;
+use uuid::Uuid;
+
+#[test]
+fn test_render_fills_in_variables() {
+    let prompts = PromptStore::from_template(
+        "Today is {{today}}. At most {{ max_count }} results. {{unknown}}\n\n{{examples}}",
+        "Query: \"rust\"",
+    );
+
+    let rendered = prompts.render();
+
+    assert!(rendered.contains(&format!("Today is {}.", Utc::now().format("%Y-%m-%d"))));
+    assert!(rendered.contains("At most 20 results."));
+    assert!(rendered.contains("{{unknown}}"));
+    assert!(rendered.ends_with("Query: \"rust\""));
+}
+
+#[test]
+fn test_examples_are_appended_without_placeholder() {
+    let prompts = PromptStore::from_template("Parse the query.", "Query: \"rust\"");
+
+    assert_eq!(
+        prompts.render(),
+        "Parse the query.\n\nExamples:\n\nQuery: \"rust\"\n"
+    );
+}
+
+#[test]
+fn test_built_in_prompt_has_examples() {
+    let rendered = PromptStore::built_in().render();
+
+    assert!(rendered.contains("Query: \"What's trending in Rust?\""));
+    assert!(!rendered.contains("{{"));
+}
+
+#[test]
+fn test_missing_files_fall_back_to_built_in() -> Result<()> {
+    let dir = env::temp_dir().join(format!("gitpulse-prompts-{}", Uuid::new_v4()));
+
+    let prompts = PromptStore::load(
+        dir.join("system_prompt.txt").to_str().unwrap(),
+        dir.join("examples.txt").to_str().unwrap(),
+    )?;
+
+    let status = prompts.status();
+    assert_eq!(status.system_prompt, "built-in");
+    assert_eq!(status.examples, "built-in");
+    assert_eq!(prompts.render(), PromptStore::built_in().render());
+
+    Ok(())
+}
+
+#[test]
+fn test_reload_picks_up_file_changes() -> Result<()> {
+    let dir = env::temp_dir().join(format!("gitpulse-prompts-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+
+    let system_prompt_path = dir.join("system_prompt.txt");
+    let examples_path = dir.join("examples.txt");
+
+    fs::write(&system_prompt_path, "Version one.\n{{examples}}")?;
+    fs::write(&examples_path, "Example one.")?;
+
+    let prompts = PromptStore::load(
+        system_prompt_path.to_str().unwrap(),
+        examples_path.to_str().unwrap(),
+    )?;
+
+    assert_eq!(prompts.render(), "Version one.\nExample one.");
+
+    fs::write(&system_prompt_path, "Version two.\n{{examples}}")?;
+
+    let status = prompts.reload()?;
+    assert_eq!(
+        status.system_prompt,
+        system_prompt_path.display().to_string()
+    );
+    assert_eq!(prompts.render(), "Version two.\nExample one.");
+
+    fs::write(&examples_path, "  ")?;
+
+    assert!(prompts.reload().is_err());
+    assert_eq!(prompts.render(), "Version two.\nExample one.");
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
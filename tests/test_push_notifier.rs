@@ -0,0 +1,195 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use axum::{Router, http::StatusCode, routing::post};
+use gitpulse::{
+    models::a2a::{
+        A2ARequest, Configuration, Message, PushNotificationConfig, TaskResult, TaskStatus,
+    },
+    services::push_notifier::PushNotifier,
+};
+
+use crate::common::spawn_mock;
+
+fn push_config(url: &str) -> PushNotificationConfig {
+    PushNotificationConfig {
+        id: None,
+        url: url.to_string(),
+        token: Some("secret".to_string()),
+    }
+}
+
+#[test]
+fn test_non_blocking_configuration() {
+    let json = r#"{
+        "jsonrpc": "2.0",
+        "id": "test-789",
+        "method": "message/send",
+        "params": {
+            "message": {
+                "kind": "message",
+                "role": "user",
+                "parts": [
+                    {"kind": "text", "text": "trending rust"}
+                ],
+                "messageId": "message-789"
+            },
+            "configuration": {
+                "blocking": false,
+                "pushNotificationConfig": {
+                    "url": "https://example.com/callback",
+                    "token": "secret"
+                }
+            }
+        }
+    }"#;
+
+    let request = serde_json::from_str::<A2ARequest>(json).unwrap();
+    let configuration = request.params.configuration.unwrap();
+    assert!(!configuration.blocking);
+
+    let push_config = configuration.push_notification_config.unwrap();
+    assert_eq!(push_config.url, "https://example.com/callback");
+    assert_eq!(push_config.token.as_deref(), Some("secret"));
+}
+
+#[test]
+fn test_blocking_defaults_to_true() {
+    let json = r#"{"pushNotificationConfig": {"url": "https://example.com/callback"}}"#;
+
+    let configuration = serde_json::from_str::<Configuration>(json).unwrap();
+    assert!(configuration.blocking);
+}
+
+#[test]
+fn test_set_and_get_config() {
+    let notifier = PushNotifier::new().unwrap();
+
+    assert!(notifier.get("task-1").is_none());
+
+    notifier
+        .set("task-1", push_config("https://example.com/callback"))
+        .unwrap();
+
+    let config = notifier.get("task-1").unwrap();
+    assert_eq!(config.url, "https://example.com/callback");
+    assert_eq!(config.token.as_deref(), Some("secret"));
+}
+
+#[test]
+fn test_rejects_invalid_url() {
+    let notifier = PushNotifier::new().unwrap();
+
+    assert!(notifier.set("task-2", push_config("not a url")).is_err());
+    assert!(
+        notifier
+            .set("task-2", push_config("ftp://example.com/callback"))
+            .is_err()
+    );
+    assert!(notifier.get("task-2").is_none());
+}
+
+#[test]
+fn test_rejects_private_hosts() {
+    let notifier = PushNotifier::new().unwrap();
+
+    for url in [
+        "http://127.0.0.1/callback",
+        "http://169.254.169.254/latest/meta-data",
+        "http://10.0.0.5/callback",
+        "http://192.168.1.1/callback",
+        "http://[::1]/callback",
+        "http://[::ffff:127.0.0.1]/callback",
+        "http://localhost:8080/callback",
+        "http://metadata.google.internal/callback",
+    ] {
+        assert!(notifier.set("task-3", push_config(url)).is_err(), "{url}");
+    }
+
+    assert!(notifier.get("task-3").is_none());
+
+    let notifier = notifier.with_private_hosts().unwrap();
+    assert!(
+        notifier
+            .set("task-3", push_config("http://127.0.0.1/callback"))
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_retain_tasks_drops_stale_configs() {
+    let notifier = PushNotifier::new().unwrap();
+
+    for task_id in ["task-4", "task-5"] {
+        notifier
+            .set(task_id, push_config("https://example.com/callback"))
+            .unwrap();
+    }
+
+    assert_eq!(notifier.retain_tasks(|task_id| task_id == "task-4"), 1);
+    assert!(notifier.get("task-4").is_some());
+    assert!(notifier.get("task-5").is_none());
+}
+
+async fn spawn_callback(statuses: Vec<StatusCode>) -> Result<(String, Arc<AtomicUsize>)> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let statuses = Arc::new(statuses);
+
+    let app = Router::new().route(
+        "/callback",
+        post({
+            let calls = calls.clone();
+            move || async move {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                statuses[call.min(statuses.len() - 1)]
+            }
+        }),
+    );
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    Ok((format!("{}/callback", base_url), calls))
+}
+
+fn task() -> TaskResult {
+    let message = Message::agent("done".to_string(), "task-6", None);
+
+    TaskResult {
+        kind: "task".to_string(),
+        id: "task-6".to_string(),
+        context_id: "context-6".to_string(),
+        status: TaskStatus {
+            state: "completed".to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            message: message.clone(),
+        },
+        artifacts: vec![],
+        history: vec![message],
+    }
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() -> Result<()> {
+    let (url, calls) = spawn_callback(vec![StatusCode::BAD_REQUEST]).await?;
+    let notifier = PushNotifier::new()?.with_private_hosts()?;
+
+    assert_eq!(notifier.deliver(&push_config(&url), &task()).await, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried() -> Result<()> {
+    let (url, calls) =
+        spawn_callback(vec![StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK]).await?;
+    let notifier = PushNotifier::new()?.with_private_hosts()?;
+
+    assert_eq!(notifier.deliver(&push_config(&url), &task()).await, 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 use anyhow::Result;
 use dotenvy::dotenv;
-use gitpulse::services::ai::QueryParser;
-use std::{env, fs};
+use gitpulse::services::{
+    ai::QueryParser,
+    llm::{LlmSettings, build_provider},
+    prompts::PromptStore,
+};
+use std::env;
 
 #[tokio::test]
 async fn test_parse_query_with_real_gemini_api() -> Result<()> {
@@ -10,25 +14,31 @@ async fn test_parse_query_with_real_gemini_api() -> Result<()> {
     let llm_provider = env::var("LLM_PROVIDER")?;
     let api_key = env::var("LLM_API_KEY")?;
     let model = env::var("LLM_MODEL")?;
-    let system_prompt =
-        fs::read_to_string("system_prompt.txt").expect("Failed to load system prompt");
-
-    let parser = QueryParser::new(
-        llm_provider.as_str(),
-        api_key.as_str(),
-        model.as_str(),
-        system_prompt.as_str(),
-    )
+    let system_prompt = PromptStore::load("system_prompt.txt", "few_shot_examples.txt")?.render();
+
+    let provider = build_provider(&LlmSettings {
+        provider: llm_provider,
+        api_key,
+        model,
+        base_url: env::var("LLM_BASE_URL").ok(),
+        json_mode: true,
+    })
     .await?;
 
+    let parser = QueryParser::new(provider, system_prompt.as_str());
+
     let user_query = "Get trending AI and Biotech repositories written in Rust created after October 1st 2025. add natural lang too";
 
-    let params = parser.parse(user_query).await?;
+    let parsed = parser.parse(user_query).await?;
+
+    println!("Parsed query: {:?}", parsed);
+
+    assert!(parsed.clarification().is_none());
 
-    println!("Parsed parameters: {:?}", params);
+    let params = parsed.params;
 
     assert!(params.language.unwrap().to_lowercase().contains("rust"));
-    assert_eq!(params.topics.iter().count(), 3);
+    assert_eq!(params.topics.len(), 3);
     assert_eq!(params.timeframe, "quarter".to_string());
     assert_eq!(params.min_stars, 10u32);
 
@@ -0,0 +1,122 @@
+use axum::{
+    http::{StatusCode, header::RETRY_AFTER},
+    response::IntoResponse,
+};
+use gitpulse::{
+    api::rpc::{MAX_BATCH_SIZE, RpcError, check_batch, parse_call},
+    models::a2a::{A2AResponse, ErrorDetail, RequestId},
+    services::rate_limiter::RateLimitError,
+};
+use serde_json::{Value, json};
+
+#[test]
+fn test_parse_call_request_ids() {
+    let request = parse_call(json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "tasks/get",
+        "params": {"id": "task-1"}
+    }))
+    .unwrap();
+    assert_eq!(request.id, Some(RequestId::Number(7)));
+
+    let request = parse_call(json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "method": "tasks/get",
+        "params": {"id": "task-1"}
+    }))
+    .unwrap();
+    assert_eq!(request.id, Some(RequestId::Null));
+
+    let notification = parse_call(json!({
+        "jsonrpc": "2.0",
+        "method": "tasks/cancel",
+        "params": {"id": "task-1"}
+    }))
+    .unwrap();
+    assert!(notification.id.is_none());
+}
+
+#[test]
+fn test_parse_call_invalid_requests() {
+    let (request_id, error) = parse_call(json!({
+        "jsonrpc": "1.0",
+        "id": "req-1",
+        "method": "tasks/get",
+        "params": {}
+    }))
+    .unwrap_err();
+    assert_eq!(request_id, Some(RequestId::from("req-1")));
+    assert_eq!(error.code(), -32600);
+
+    let (request_id, error) = parse_call(json!({"jsonrpc": "2.0", "id": "req-2"})).unwrap_err();
+    assert_eq!(request_id, Some(RequestId::from("req-2")));
+    assert_eq!(error.code(), -32600);
+
+    let (request_id, error) = parse_call(json!(42)).unwrap_err();
+    assert!(request_id.is_none());
+    assert_eq!(error.code(), -32600);
+
+    let (_, error) = parse_call(json!({})).unwrap_err();
+    assert_eq!(error.code(), -32600);
+}
+
+#[test]
+fn test_error_retry_hints() {
+    let detail = ErrorDetail::from(RpcError::GitHubUnavailable);
+    assert_eq!(detail.code, -32051);
+
+    let data = detail.data.unwrap();
+    assert_eq!(data["retryable"], true);
+    assert_eq!(data["retryAfterSeconds"], 60);
+
+    let detail = ErrorDetail::from(RpcError::TaskNotFound("task-1".to_string()));
+    assert_eq!(detail.code, -32001);
+
+    let data = detail.data.unwrap();
+    assert_eq!(data["retryable"], false);
+    assert_eq!(data["taskId"], "task-1");
+    assert!(data.get("retryAfterSeconds").is_none());
+}
+
+#[test]
+fn test_error_response_echoes_id() {
+    let response =
+        A2AResponse::<Value>::error(Some(RequestId::Number(3)), RpcError::QueryParseFailed);
+
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json["id"], 3);
+    assert_eq!(json["error"]["code"], -32050);
+    assert_eq!(json["error"]["data"]["retryAfterSeconds"], 5);
+
+    let response = A2AResponse::<Value>::error(None, RpcError::ParseError("eof".to_string()));
+
+    let json = serde_json::to_value(&response).unwrap();
+    assert!(json["id"].is_null());
+    assert_eq!(json["error"]["code"], -32700);
+}
+
+#[test]
+fn test_check_batch_limits_size() {
+    assert_eq!(check_batch(&[]).unwrap_err().code(), -32600);
+    assert!(check_batch(&vec![json!({}); MAX_BATCH_SIZE]).is_ok());
+
+    let error = check_batch(&vec![json!({}); MAX_BATCH_SIZE + 1]).unwrap_err();
+    assert_eq!(error.code(), -32600);
+    assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_rate_limit_errors_map_to_rpc_errors() {
+    let error = RpcError::from(RateLimitError::Exceeded(60));
+    assert_eq!(error.code(), -32053);
+    assert_eq!(error.retry_after(), Some(60));
+
+    let error = RpcError::from(RateLimitError::Unconfigured);
+    assert_eq!(error.code(), -32603);
+
+    let response = RateLimitError::Exceeded(60).into_response();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[RETRY_AFTER], "60");
+}
@@ -0,0 +1,169 @@
+use gitpulse::{
+    models::query::{QueryParams, SortBy},
+    services::rule_parser::RuleParser,
+};
+
+#[test]
+fn test_extracts_count_language_topics_and_stars() {
+    let parsed =
+        RuleParser::new().parse("Top 15 Rust AI and machine-learning repos with over 1k stars");
+
+    assert!(parsed.clarification().is_none());
+    assert_eq!(parsed.params.language.as_deref(), Some("rust"));
+    assert_eq!(parsed.params.topics, vec!["ai", "machine-learning"]);
+    assert_eq!(parsed.params.count, 15);
+    assert_eq!(parsed.params.min_stars, 1000);
+}
+
+#[test]
+fn test_language_aliases() {
+    let parser = RuleParser::new();
+
+    let cases = [
+        ("trending golang tools", "go"),
+        ("what's new in c++?", "c++"),
+        ("popular C# libraries", "c#"),
+        ("show me ts projects", "typescript"),
+    ];
+
+    for (query, language) in cases {
+        assert_eq!(
+            parser.parse(query).params.language.as_deref(),
+            Some(language),
+            "{}",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_timeframe_and_count() {
+    let parsed = RuleParser::new().parse("Show me 10 Python web frameworks from this month");
+
+    assert_eq!(parsed.params.language.as_deref(), Some("python"));
+    assert_eq!(parsed.params.topics, vec!["web-framework"]);
+    assert_eq!(parsed.params.timeframe, "month");
+    assert_eq!(parsed.params.count, 10);
+    assert!(!parsed.params.has_specific_date);
+}
+
+#[test]
+fn test_count_is_capped() {
+    let parsed = RuleParser::new().parse("top 500 rust repos");
+
+    assert_eq!(parsed.params.count, 20);
+}
+
+#[test]
+fn test_date_phrases() {
+    let parser = RuleParser::new();
+
+    let parsed = parser.parse("Web3 and blockchain projects since October 1st 2025");
+    assert_eq!(parsed.params.topics, vec!["web3", "blockchain"]);
+    assert_eq!(
+        parsed.params.date_string.as_deref(),
+        Some("since october 1st 2025")
+    );
+    assert!(parsed.params.has_specific_date);
+
+    let parsed = parser.parse("AI repositories created last Tuesday");
+    assert_eq!(parsed.params.date_string.as_deref(), Some("last tuesday"));
+    assert!(parsed.params.has_specific_date);
+
+    let parsed = parser.parse("rust repos from the last 30 days");
+    assert_eq!(parsed.params.date_string.as_deref(), Some("last 30 days"));
+    assert_eq!(parsed.params.count, 5);
+}
+
+#[test]
+fn test_generic_and_vague_queries() {
+    let parser = RuleParser::new();
+
+    let parsed = parser.parse("What's trending?");
+    assert!(parsed.clarification().is_none());
+    assert_eq!(parsed.params.language, None);
+
+    let parsed = parser.parse("stuff from around that conference");
+    assert!(parsed.clarification().is_some());
+}
+
+#[test]
+fn test_follow_up_keeps_previous_params() {
+    let previous = QueryParams {
+        language: Some("rust".to_string()),
+        topics: vec!["ai".to_string()],
+        ..QueryParams::default()
+    };
+
+    let parser = RuleParser::new();
+
+    let follow_up = parser.parse_follow_up("same but last month", &previous);
+    assert!(!follow_up.more_results);
+    assert_eq!(follow_up.params.language.as_deref(), Some("rust"));
+    assert_eq!(follow_up.params.topics, vec!["ai"]);
+    assert_eq!(follow_up.params.timeframe, "month");
+
+    let follow_up = parser.parse_follow_up("now only python with more than 500 stars", &previous);
+    assert!(!follow_up.more_results);
+    assert_eq!(follow_up.params.language.as_deref(), Some("python"));
+    assert_eq!(follow_up.params.min_stars, 500);
+
+    let follow_up = parser.parse_follow_up("show 10 more", &previous);
+    assert!(follow_up.more_results);
+    assert_eq!(follow_up.params.count, 10);
+    assert_eq!(follow_up.params.language.as_deref(), Some("rust"));
+}
+
+#[test]
+fn test_velocity_phrases_sort_by_velocity() {
+    let parser = RuleParser::new();
+
+    let parsed = parser.parse("fastest growing rust projects");
+    assert_eq!(parsed.params.sort, SortBy::Velocity);
+    assert_eq!(parsed.params.language.as_deref(), Some("rust"));
+
+    assert_eq!(parser.parse("trending rust").params.sort, SortBy::Stars);
+
+    let previous = parser.parse("trending rust").params;
+    let follow_up = parser.parse_follow_up("which ones are blowing up?", &previous);
+    assert_eq!(follow_up.params.sort, SortBy::Velocity);
+    assert_eq!(follow_up.params.language.as_deref(), Some("rust"));
+}
+
+#[test]
+fn test_ambiguous_languages_need_context() {
+    let parser = RuleParser::new();
+
+    let cases = [
+        ("I want to go through trending python repos", Some("python")),
+        ("I want to go through trending repos", None),
+        ("trending go repos", Some("go")),
+        ("new cli tools written in go", Some("go")),
+        ("c projects with over 100 stars", Some("c")),
+        ("plan a, plan b, plan c", None),
+    ];
+
+    for (query, language) in cases {
+        assert_eq!(
+            parser.parse(query).params.language.as_deref(),
+            language,
+            "{}",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_plural_timeframes() {
+    let parser = RuleParser::new();
+
+    let cases = [
+        ("rust repos from the last 2 weeks", "week"),
+        ("best python projects of the past months", "month"),
+        ("top ai repos in recent years", "year"),
+    ];
+
+    for (query, timeframe) in cases {
+        assert_eq!(parser.parse(query).params.timeframe, timeframe, "{}", query);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::get,
+};
+use chrono::NaiveDate;
+use gitpulse::{
+    models::{
+        normalize::TopicMap,
+        query::QueryParams,
+        search_query::{ForkFilter, Range, SearchQuery, SearchTerm, TopicMatch},
+    },
+    services::github::GitHubClient,
+    utils::helpers::build_base_query,
+};
+use serde_json::{Value, json};
+
+use crate::common::{Captured, search, spawn_mock};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_builds_qualifiers() {
+    let query = SearchQuery::new()
+        .keyword("web-framework")
+        .language("rust")
+        .topic("cli")
+        .created(Range::Greater(date("2025-10-01")))
+        .pushed(Range::Between(date("2025-10-01"), date("2025-10-31")))
+        .stars(Range::AtLeast(100))
+        .org("tokio-rs")
+        .user("dtolnay")
+        .archived(false)
+        .fork(ForkFilter::Include)
+        .license("mit");
+
+    assert_eq!(
+        query.to_string(),
+        "web-framework language:rust topic:cli created:>2025-10-01 pushed:2025-10-01..2025-10-31 \
+         stars:>=100 org:tokio-rs user:dtolnay archived:false fork:true license:mit"
+    );
+}
+
+#[test]
+fn test_quotes_untrusted_values() {
+    let query = SearchQuery::new()
+        .keyword("rust language:go")
+        .keyword("OR")
+        .keyword("-archived")
+        .keyword("  ")
+        .topic("machine \"learning\"")
+        .language("jupyter notebook")
+        .topic("");
+
+    assert_eq!(
+        query.to_string(),
+        r#""rust language:go" "OR" "-archived" topic:"machine learning" language:"jupyter notebook""#
+    );
+    assert_eq!(query.keywords().len(), 3);
+    assert_eq!(query.qualifiers().len(), 2);
+}
+
+#[test]
+fn test_or_group() {
+    let terms = (1..=8)
+        .map(|i| SearchTerm::Keyword(format!("term{}", i)))
+        .collect::<Vec<_>>();
+
+    let query = SearchQuery::new().any_of(terms).stars(Range::AtLeast(10));
+
+    assert_eq!(query.alternatives().len(), 6);
+    assert!(query.to_string().starts_with("term1 OR term2 OR "));
+    assert!(query.to_string().ends_with("term6 stars:>=10"));
+
+    let query = SearchQuery::new().any_of(TopicMatch::Both.terms("ai"));
+    assert_eq!(query.to_string(), "topic:ai OR ai");
+}
+
+#[test]
+fn test_topic_match() {
+    assert_eq!(
+        "Keyword".parse::<TopicMatch>().unwrap(),
+        TopicMatch::Keyword
+    );
+    assert_eq!("both".parse::<TopicMatch>().unwrap(), TopicMatch::Both);
+    assert!("fuzzy".parse::<TopicMatch>().is_err());
+
+    assert_eq!(
+        TopicMatch::Topic
+            .require(SearchQuery::new(), "ai")
+            .to_string(),
+        "topic:ai"
+    );
+    assert_eq!(
+        TopicMatch::Both
+            .require(SearchQuery::new(), "ai")
+            .to_string(),
+        "ai"
+    );
+}
+
+#[test]
+fn test_base_query_from_params() {
+    let params = QueryParams {
+        language: Some("c#".to_string()),
+        min_stars: 50,
+        created_after: Some("2025-10-01".to_string()),
+        pushed_after: Some("2025-10-01".to_string()),
+        has_specific_date: true,
+        ..QueryParams::default()
+    };
+
+    assert_eq!(
+        build_base_query(&params).to_string(),
+        "created:>2025-10-01 pushed:>2025-10-01 language:csharp stars:>=50"
+    );
+
+    let params = QueryParams {
+        created_after: Some("last tuesday".to_string()),
+        pushed_after: Some("last tuesday".to_string()),
+        min_stars: 0,
+        ..QueryParams::default()
+    };
+
+    let query = build_base_query(&params).to_string();
+    assert!(query.starts_with("created:>2"));
+    assert!(!query.contains("stars:"));
+}
+
+fn repository(name: &str, stars: u32) -> Value {
+    json!({
+        "id": stars,
+        "name": name,
+        "full_name": name,
+        "owner": { "login": "owner" },
+        "html_url": format!("https://github.com/{}", name),
+        "description": null,
+        "stargazers_count": stars,
+        "forks_count": 0,
+        "language": "Rust",
+        "topics": [],
+        "created_at": "2025-10-30T10:30:00Z",
+        "updated_at": "2025-10-30T10:30:00Z"
+    })
+}
+
+/// Repositories matching every topic only come back from queries without
+/// an `OR` group.
+async fn search_topics(
+    State(captured): State<Captured>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let any_topic = query["q"].contains(" OR ");
+
+    captured.lock().unwrap().push(query);
+
+    match any_topic {
+        true => Json(json!({
+            "total_count": 2,
+            "items": [repository("owner/popular", 900), repository("owner/tagged", 10)]
+        })),
+        false => Json(json!({ "total_count": 1, "items": [repository("owner/tagged", 10)] })),
+    }
+}
+
+#[tokio::test]
+async fn test_topic_search_stays_within_budget() -> Result<()> {
+    let captured: Captured = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search_topics))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let client = GitHubClient::new(None, &format!("{}/search/repositories", base_url))?
+        .with_topic_map(TopicMap::default())
+        .with_topic_match(TopicMatch::Both)
+        .with_fanout_budget(2);
+
+    let params = QueryParams {
+        topics: ["ai", "llm", "rag", "agents", "cli", "web3"]
+            .map(String::from)
+            .to_vec(),
+        ..QueryParams::default()
+    };
+
+    let result = client.search_with_params(&params).await?;
+
+    assert_eq!(captured.lock().unwrap().len(), 2);
+    assert_eq!(result.queries.len(), 2);
+    assert_eq!(result.total_count, 2);
+
+    let names = result
+        .repos
+        .iter()
+        .map(|repo| repo.name.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["owner/tagged", "owner/popular"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_request_is_url_encoded() -> Result<()> {
+    let captured: Captured = Arc::new(Mutex::new(vec![]));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search))
+        .with_state(captured.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    let client = GitHubClient::new(None, &format!("{}/search/repositories", base_url))?;
+
+    let params = QueryParams {
+        topics: vec!["c# & f#".to_string(), "日本語".to_string()],
+        count: 7,
+        ..QueryParams::default()
+    };
+
+    client.search_with_params(&params).await?;
+
+    let requests = captured.lock().unwrap().clone();
+
+    for request in &requests {
+        let mut keys = request.keys().map(String::as_str).collect::<Vec<_>>();
+        keys.sort();
+
+        assert_eq!(keys, vec!["order", "per_page", "q", "sort"]);
+        assert_eq!(request["per_page"], "7");
+    }
+
+    let queries = requests
+        .iter()
+        .map(|request| request["q"].as_str())
+        .collect::<Vec<_>>();
+
+    assert!(
+        queries
+            .iter()
+            .any(|query| query.ends_with(r#"topic:"c#-&-f#" topic:日本語"#))
+    );
+    assert!(
+        queries
+            .iter()
+            .any(|query| query.starts_with(r#"topic:"c#-&-f#" OR topic:日本語 created:>"#))
+    );
+
+    Ok(())
+}
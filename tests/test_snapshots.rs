@@ -0,0 +1,308 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{TimeDelta, Utc};
+use gitpulse::{
+    models::{
+        query::{QueryParams, SortBy},
+        repository::{SearchResult, StarVelocity, TrendingRepo},
+    },
+    services::{cache::Cache, github::GitHubClient, snapshot_store::SnapshotStore},
+};
+use serde_json::{Value, json};
+
+use crate::common::{repo, spawn_mock};
+
+fn snapshot(name: &str, stars: u32, forks: u32) -> TrendingRepo {
+    TrendingRepo {
+        forks,
+        ..repo(name, stars)
+    }
+}
+
+fn item(name: &str, stars: u32) -> Value {
+    json!({
+        "id": 1,
+        "name": name,
+        "full_name": name,
+        "owner": { "login": name.split('/').next().unwrap() },
+        "html_url": format!("https://github.com/{}", name),
+        "description": null,
+        "stargazers_count": stars,
+        "forks_count": 0,
+        "language": "Rust",
+        "topics": [],
+        "created_at": "2020-01-01T00:00:00Z",
+        "updated_at": "2025-10-30T10:30:00Z"
+    })
+}
+
+#[test]
+fn test_velocity_from_consecutive_snapshots() -> Result<()> {
+    let store = SnapshotStore::in_memory()?;
+    let start = Utc::now() - TimeDelta::days(20);
+
+    assert_eq!(store.record_at(&[snapshot("a/b", 100, 10)], start)?, 1);
+    assert_eq!(
+        store.record_at(&[snapshot("a/b", 101, 10)], start + TimeDelta::minutes(30))?,
+        0
+    );
+    assert_eq!(store.velocity("a/b")?, None);
+
+    store.record_at(&[snapshot("a/b", 150, 12)], start + TimeDelta::days(1))?;
+    store.record_at(&[snapshot("a/b", 500, 30)], start + TimeDelta::days(8))?;
+    store.record_at(&[snapshot("a/b", 560, 31)], start + TimeDelta::days(9))?;
+
+    assert_eq!(store.snapshot_count("a/b")?, 4);
+    assert_eq!(
+        store.velocity("a/b")?,
+        Some(StarVelocity {
+            stars_per_day: 60.0,
+            stars_last_week: 410,
+            forks_last_week: 19,
+            tracked_days: 9.0,
+        })
+    );
+
+    assert_eq!(store.tracked(start + TimeDelta::days(9), 10)?, vec!["a/b"]);
+    assert!(store.tracked(start + TimeDelta::days(10), 10)?.is_empty());
+
+    store.record_at(&[snapshot("c/d", 1, 0)], start + TimeDelta::days(40))?;
+    assert_eq!(store.snapshot_count("a/b")?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_store_persists_to_disk() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("gitpulse-snapshots-{}.db", fastrand::u64(..)));
+
+    SnapshotStore::open(&path)?.record(&[snapshot("a/b", 100, 10)])?;
+    assert_eq!(SnapshotStore::open(&path)?.snapshot_count("a/b")?, 1);
+
+    std::fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Mock {
+    /// The `q` and `per_page` of every search.
+    searches: Vec<(String, String)>,
+    /// Every repository fetched individually.
+    fetched: Vec<String>,
+}
+
+type Shared = Arc<Mutex<Mock>>;
+
+/// An established repository growing slowly and a smaller one taking off.
+async fn search(
+    State(mock): State<Shared>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    mock.lock().unwrap().searches.push((
+        query.get("q").cloned().unwrap_or_default(),
+        query.get("per_page").cloned().unwrap_or_default(),
+    ));
+
+    Json(json!({
+        "total_count": 2,
+        "items": [item("old/giant", 50_000), item("new/rocket", 900)]
+    }))
+}
+
+async fn fetch(
+    State(mock): State<Shared>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Response {
+    let name = format!("{}/{}", owner, name);
+    mock.lock().unwrap().fetched.push(name.clone());
+
+    match name.as_str() {
+        "new/rocket" => Json(item(&name, 1_300)).into_response(),
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn spawn_server() -> Result<(String, Shared)> {
+    let mock: Shared = Arc::new(Mutex::new(Mock::default()));
+
+    let app = Router::new()
+        .route("/search/repositories", get(search))
+        .route("/repos/{owner}/{name}", get(fetch))
+        .with_state(mock.clone());
+
+    let (base_url, _) = spawn_mock(app).await;
+
+    Ok((format!("{}/search/repositories", base_url), mock))
+}
+
+#[tokio::test]
+async fn test_search_ranks_by_velocity() -> Result<()> {
+    let (search_url, mock) = spawn_server().await?;
+
+    let store = SnapshotStore::in_memory()?;
+    store.record_at(
+        &[
+            snapshot("old/giant", 49_990, 0),
+            snapshot("new/rocket", 500, 0),
+        ],
+        Utc::now() - TimeDelta::days(2),
+    )?;
+
+    let client = GitHubClient::new(None, &search_url)?.with_snapshot_store(store.clone());
+
+    let params = QueryParams {
+        count: 1,
+        sort: SortBy::Velocity,
+        ..QueryParams::default()
+    };
+
+    let result = client.search_with_params(&params).await?;
+
+    assert_eq!(result.repos.len(), 1);
+    assert_eq!(result.repos[0].name, "new/rocket");
+    assert_eq!(
+        result.repos[0]
+            .velocity
+            .as_ref()
+            .map(|velocity| velocity.stars_per_day),
+        Some(200.0)
+    );
+
+    let result = client.search_with_params(&QueryParams::default()).await?;
+
+    assert_eq!(result.repos[0].name, "old/giant");
+    assert_eq!(
+        result.repos[0]
+            .velocity
+            .as_ref()
+            .map(|velocity| velocity.stars_last_week),
+        Some(10)
+    );
+
+    let searches = mock.lock().unwrap().searches.clone();
+
+    assert!(!searches[0].0.contains("created:"));
+    assert_eq!(searches[0].1, "100");
+    assert!(searches[1].0.contains("created:"));
+    assert_eq!(searches[1].1, "5");
+
+    assert_eq!(store.snapshot_count("new/rocket")?, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_velocity_without_store_ranks_by_stars() -> Result<()> {
+    let (search_url, mock) = spawn_server().await?;
+
+    let client = GitHubClient::new(None, &search_url)?;
+
+    let params = QueryParams {
+        count: 1,
+        sort: SortBy::Velocity,
+        ..QueryParams::default()
+    };
+
+    let result = client.search_with_params(&params).await?;
+
+    assert_eq!(result.repos[0].name, "old/giant");
+    assert!(result.repos[0].velocity.is_none());
+    assert!(mock.lock().unwrap().searches[0].0.contains("created:"));
+    assert_eq!(client.refresh_snapshots(10).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refresh_snapshots_fetches_tracked_repos() -> Result<()> {
+    let (search_url, mock) = spawn_server().await?;
+
+    let store = SnapshotStore::in_memory()?;
+    let now = Utc::now();
+
+    store.record_at(&[snapshot("gone/stale", 10, 0)], now - TimeDelta::days(10))?;
+    store.record_at(
+        &[
+            snapshot("new/rocket", 900, 0),
+            snapshot("gone/deleted", 5, 0),
+        ],
+        now - TimeDelta::days(1),
+    )?;
+
+    let client = GitHubClient::new(None, &search_url)?.with_snapshot_store(store.clone());
+
+    assert_eq!(client.refresh_snapshots(10).await?, 1);
+
+    let mut fetched = mock.lock().unwrap().fetched.clone();
+    fetched.sort();
+    assert_eq!(fetched, vec!["gone/deleted", "new/rocket"]);
+
+    assert_eq!(
+        store
+            .velocity("new/rocket")?
+            .map(|velocity| velocity.stars_per_day),
+        Some(400.0)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_velocities_read_in_one_batch() -> Result<()> {
+    let store = SnapshotStore::in_memory()?;
+    let start = Utc::now() - TimeDelta::days(2);
+
+    store.record_at(&[snapshot("a/b", 100, 0), snapshot("c/d", 10, 0)], start)?;
+    store.record_at(
+        &[
+            snapshot("a/b", 120, 0),
+            snapshot("c/d", 50, 0),
+            snapshot("e/f", 1, 0),
+        ],
+        start + TimeDelta::days(1),
+    )?;
+
+    let velocities = store.velocities(&["a/b", "c/d", "e/f", "x/y"])?;
+
+    assert_eq!(velocities.len(), 2);
+    assert_eq!(velocities["a/b"].stars_per_day, 20.0);
+    assert_eq!(velocities["c/d"].stars_per_day, 40.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_keeps_rankings_apart() {
+    let cache = Cache::new(60);
+
+    let stars = QueryParams::default();
+    let velocity = QueryParams {
+        sort: SortBy::Velocity,
+        ..QueryParams::default()
+    };
+
+    cache.set(
+        None,
+        &stars,
+        Some(SearchResult {
+            queries: vec![],
+            total_count: 1,
+            repos: vec![snapshot("old/giant", 50_000, 0)],
+        }),
+    );
+
+    assert!(cache.get_repo(&stars).is_some());
+    assert!(cache.get_repo(&velocity).is_none());
+}
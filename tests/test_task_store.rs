@@ -0,0 +1,206 @@
+use std::{env, time::Duration};
+
+use gitpulse::{
+    models::a2a::{Message, MessagePart},
+    services::task_store::{TaskStore, TaskStoreError},
+    utils::helpers::create_artifacts,
+};
+use uuid::Uuid;
+
+fn user_message(text: &str) -> Message {
+    Message {
+        kind: "message".to_string(),
+        role: "user".to_string(),
+        parts: vec![MessagePart::Text {
+            kind: "text".to_string(),
+            text: text.to_string(),
+        }],
+        message_id: Uuid::new_v4().to_string(),
+        task_id: None,
+        context_id: None,
+        telex_metadata: None,
+    }
+}
+
+#[test]
+fn test_task_lifecycle() {
+    let store = TaskStore::new(None).unwrap();
+
+    store
+        .submit("task-1", "context-1", &user_message("trending rust"))
+        .unwrap();
+    store.transition(
+        "task-1",
+        "working",
+        Message::agent("Parsing query".to_string(), "task-1", None),
+    );
+    store.complete(
+        "task-1",
+        "completed",
+        Message::agent("Done".to_string(), "task-1", None),
+        create_artifacts("Done".to_string()),
+    );
+
+    let task = store.get("task-1", None).unwrap();
+    assert_eq!(task.status.state, "completed");
+    assert_eq!(task.context_id, "context-1");
+    assert_eq!(task.history.len(), 2);
+    assert_eq!(task.artifacts.len(), 1);
+
+    let states = store
+        .transitions("task-1")
+        .unwrap()
+        .into_iter()
+        .map(|transition| transition.state)
+        .collect::<Vec<_>>();
+    assert_eq!(states, vec!["submitted", "working", "completed"]);
+
+    let trimmed = store.get("task-1", Some(1)).unwrap();
+    assert_eq!(trimmed.history.len(), 1);
+    assert_eq!(trimmed.history[0].role, "agent");
+}
+
+#[test]
+fn test_cancel_task() {
+    let store = TaskStore::new(None).unwrap();
+
+    store
+        .submit("task-2", "context-2", &user_message("trending go"))
+        .unwrap();
+
+    let canceled = store.cancel("task-2").unwrap();
+    assert_eq!(canceled.status.state, "canceled");
+    assert!(store.is_canceled("task-2"));
+
+    let ignored = store.transition(
+        "task-2",
+        "working",
+        Message::agent("Searching GitHub".to_string(), "task-2", None),
+    );
+    assert!(ignored.is_none());
+
+    assert!(matches!(
+        store.cancel("task-2"),
+        Err(TaskStoreError::NotCancelable(_))
+    ));
+    assert!(matches!(
+        store.cancel("missing"),
+        Err(TaskStoreError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_persisted_tasks_reload() {
+    let dir = env::temp_dir().join(format!("gitpulse-tasks-{}", Uuid::new_v4()));
+    let dir = dir.to_str().unwrap();
+
+    let store = TaskStore::new(Some(dir)).unwrap();
+    store
+        .submit("task/3", "context-3", &user_message("trending python"))
+        .unwrap();
+
+    let reloaded = TaskStore::new(Some(dir)).unwrap();
+    let task = reloaded.get("task/3", None).unwrap();
+    assert_eq!(task.status.state, "submitted");
+    assert_eq!(task.history.len(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_closed_and_foreign_tasks_reject_messages() {
+    let store = TaskStore::new(None).unwrap();
+
+    store
+        .submit("task-4", "context-4", &user_message("trending rust"))
+        .unwrap();
+
+    assert!(matches!(
+        store.submit("task-4", "intruder", &user_message("trending go")),
+        Err(TaskStoreError::NotFound(_))
+    ));
+    store.transition(
+        "task-4",
+        "input-required",
+        Message::agent("Which language?".to_string(), "task-4", None),
+    );
+    assert!(store.accepts("task-4", "context-4").is_ok());
+
+    store.complete(
+        "task-4",
+        "completed",
+        Message::agent("Done".to_string(), "task-4", None),
+        vec![],
+    );
+
+    assert!(matches!(
+        store.submit("task-4", "context-4", &user_message("again")),
+        Err(TaskStoreError::Closed(state)) if state == "completed"
+    ));
+
+    let task = store.get("task-4", None).unwrap();
+    assert_eq!(task.status.state, "completed");
+    assert_eq!(task.history.len(), 2);
+}
+
+#[test]
+fn test_running_tasks_reject_messages() {
+    let store = TaskStore::new(None).unwrap();
+
+    store
+        .submit("task-6", "context-6", &user_message("trending rust"))
+        .unwrap();
+
+    assert!(matches!(
+        store.submit("task-6", "context-6", &user_message("trending rust")),
+        Err(TaskStoreError::Busy(state)) if state == "submitted"
+    ));
+
+    store.transition(
+        "task-6",
+        "working",
+        Message::agent("Searching GitHub".to_string(), "task-6", None),
+    );
+
+    assert!(matches!(
+        store.accepts("task-6", "context-6"),
+        Err(TaskStoreError::Busy(state)) if state == "working"
+    ));
+    assert!(matches!(
+        store.submit("task-6", "context-6", &user_message("trending go")),
+        Err(TaskStoreError::Busy(state)) if state == "working"
+    ));
+
+    let task = store.get("task-6", None).unwrap();
+    assert_eq!(task.status.state, "working");
+    assert_eq!(task.history.len(), 1);
+}
+
+#[tokio::test]
+async fn test_expired_tasks_are_evicted() {
+    let dir = env::temp_dir().join(format!("gitpulse-tasks-{}", Uuid::new_v4()));
+
+    let store = TaskStore::new(dir.to_str())
+        .unwrap()
+        .with_ttl(Duration::ZERO);
+    store
+        .submit("task-5", "context-5", &user_message("trending zig"))
+        .unwrap();
+
+    // Writes happen on the blocking pool.
+    for _ in 0..50 {
+        if dir.join("task-5.json").exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(dir.join("task-5.json").exists());
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    assert_eq!(store.evict_expired(), 1);
+    assert!(store.get("task-5", None).is_none());
+    assert!(!dir.join("task-5.json").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}